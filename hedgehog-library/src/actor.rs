@@ -1,23 +1,44 @@
-use crate::datasource::{DataProvider, NewFeedMetadata, QueryError};
+use crate::credentials::{redact_url, run_password_command};
+use crate::datasource::{DataProvider, EpisodeWriteResult, NewFeedMetadata, QueryError};
+use crate::downloads::DownloadRequest;
 use crate::model::{
-    Episode, EpisodeId, EpisodePlaybackData, EpisodeStatus, EpisodeSummary, EpisodeSummaryStatus,
-    EpisodesListMetadata, Feed, FeedId, FeedStatus, FeedSummary, GroupId, GroupSummary,
+    Bookmark, BookmarkId, Chapter, Credentials, Download, Episode, EpisodeId, EpisodePlaybackData,
+    EpisodeStatus, EpisodeSummary, EpisodeSummaryStatus, EpisodesFilter, EpisodesListMetadata,
+    EpisodesSortKey, Feed, FeedCredentials, FeedId, FeedPolicy, FeedStatus, FeedSummary, FeedView,
+    GroupId, GroupSummary, ListeningSession, ListeningStats, SavedViewId, SavedViewSummary,
+    TranscriptCue, TranscriptFormat, UpdateSource,
 };
-use crate::rss_client::{fetch_feed, WritableFeed};
+use crate::network::HttpClient;
+use crate::rss_client::{
+    fetch_chapters, fetch_document, fetch_feed, FeedResponse, FetchedFeed, WritableFeed,
+};
+use crate::transcript::parse_transcript;
 use crate::EpisodesQuery;
 use actix::dev::MessageResponse;
 use actix::fut::wrap_future;
 use actix::prelude::*;
+use chrono::{DateTime, Utc};
 use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::future::Future;
+use std::io::ErrorKind;
 use std::ops::Range;
-use std::sync::Arc;
-use tokio::sync::Semaphore;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Scheduled updates are not performed more often than this even if some of the feeds are due
+// but cannot be updated at the moment (e.g. due to `<skipHours>`).
+const MIN_SCHEDULED_UPDATE_DELAY_SECS: i64 = 60;
 
 pub struct Library {
     data_provider: Box<dyn DataProvider>,
     updating_feeds: HashSet<FeedId>,
-    feeds_semaphore: Arc<Semaphore>,
+    http_client: HttpClient,
     update_listener: Option<Recipient<FeedUpdateNotification>>,
+    download_manager: Option<Recipient<DownloadRequest>>,
+    transcripts_path: Option<PathBuf>,
+    update_interval: Option<Duration>,
+    update_timer: Option<SpawnHandle>,
 }
 
 impl Library {
@@ -25,10 +46,24 @@ impl Library {
         Library {
             data_provider: Box::new(data_provider),
             updating_feeds: HashSet::new(),
-            feeds_semaphore: Arc::new(Semaphore::new(8)),
+            http_client: HttpClient::default(),
             update_listener: None,
+            download_manager: None,
+            transcripts_path: None,
+            update_interval: None,
+            update_timer: None,
         }
     }
+
+    pub fn set_transcripts_path(mut self, path: PathBuf) -> Self {
+        self.transcripts_path = Some(path);
+        self
+    }
+
+    pub fn set_http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = http_client;
+        self
+    }
 }
 
 impl Actor for Library {
@@ -91,6 +126,8 @@ impl Handler<EpisodesListMetadataRequest> for Library {
 pub struct FeedSummariesResponse {
     pub feeds: Vec<FeedSummary>,
     pub groups: Vec<GroupSummary>,
+    pub saved_views: Vec<SavedViewSummary>,
+    pub sort_keys: HashMap<FeedView<FeedId, GroupId, SavedViewId>, EpisodesSortKey>,
 }
 
 #[derive(Message)]
@@ -115,7 +152,26 @@ impl Handler<FeedSummariesRequest> for Library {
                 log::error!(target: "sql", "cannot fetch group summaries, {}", error);
                 Vec::new()
             });
-        FeedSummariesResponse { feeds, groups }
+        let saved_views = self
+            .data_provider
+            .get_saved_views()
+            .unwrap_or_else(|error| {
+                log::error!(target: "sql", "cannot fetch saved views, {}", error);
+                Vec::new()
+            });
+        let sort_keys = self
+            .data_provider
+            .get_episodes_sort_keys()
+            .unwrap_or_else(|error| {
+                log::error!(target: "sql", "cannot fetch sort keys, {}", error);
+                HashMap::new()
+            });
+        FeedSummariesResponse {
+            feeds,
+            groups,
+            saved_views,
+            sort_keys,
+        }
     }
 }
 
@@ -124,7 +180,7 @@ impl Handler<FeedSummariesRequest> for Library {
 pub struct EpisodePlaybackDataRequest(pub EpisodeId);
 
 impl Handler<EpisodePlaybackDataRequest> for Library {
-    type Result = Option<EpisodePlaybackData>;
+    type Result = ResponseActFuture<Self, Option<EpisodePlaybackData>>;
 
     fn handle(
         &mut self,
@@ -132,9 +188,87 @@ impl Handler<EpisodePlaybackDataRequest> for Library {
         _ctx: &mut Self::Context,
     ) -> Self::Result {
         match self.data_provider.get_episode_playback_data(msg.0) {
-            Ok(result) => result,
+            Ok(Some(mut playback_data)) => {
+                let credentials = self.resolve_credentials(playback_data.feed_id);
+                Box::pin(wrap_future::<_, Self>(async move {
+                    playback_data.credentials = credentials.await;
+                    Some(playback_data)
+                }))
+            }
+            Ok(None) => Box::pin(actix::fut::ready(None)),
             Err(error) => {
                 log::error!(target: "sql", "cannot get episode playback data, {}", error);
+                Box::pin(actix::fut::ready(None))
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<EpisodeSummary>")]
+pub struct QueueRequest;
+
+impl Handler<QueueRequest> for Library {
+    type Result = Vec<EpisodeSummary>;
+
+    fn handle(&mut self, _msg: QueueRequest, _ctx: &mut Self::Context) -> Self::Result {
+        self.data_provider.get_queue().unwrap_or_else(|error| {
+            log::error!(target: "sql", "cannot fetch the queue, {}", error);
+            Vec::new()
+        })
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<Bookmark>")]
+pub struct BookmarksRequest;
+
+impl Handler<BookmarksRequest> for Library {
+    type Result = Vec<Bookmark>;
+
+    fn handle(&mut self, _msg: BookmarksRequest, _ctx: &mut Self::Context) -> Self::Result {
+        self.data_provider.get_bookmarks().unwrap_or_else(|error| {
+            log::error!(target: "sql", "cannot fetch bookmarks, {}", error);
+            Vec::new()
+        })
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "ListeningStats")]
+pub struct ListeningStatsRequest {
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl Handler<ListeningStatsRequest> for Library {
+    type Result = ListeningStats;
+
+    fn handle(&mut self, msg: ListeningStatsRequest, _ctx: &mut Self::Context) -> Self::Result {
+        match self.data_provider.get_listening_stats(msg.since) {
+            Ok(result) => result,
+            Err(error) => {
+                log::error!(target: "sql", "cannot fetch listening statistics, {}", error);
+                ListeningStats::default()
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<EpisodeId>")]
+pub struct NextEpisodeRequest {
+    pub query: EpisodesQuery,
+    pub after: EpisodeId,
+}
+
+impl Handler<NextEpisodeRequest> for Library {
+    type Result = Option<EpisodeId>;
+
+    fn handle(&mut self, msg: NextEpisodeRequest, _ctx: &mut Self::Context) -> Self::Result {
+        match self.data_provider.get_next_episode(msg.query, msg.after) {
+            Ok(result) => result,
+            Err(error) => {
+                log::error!(target: "sql", "cannot find the next episode, {}", error);
                 None
             }
         }
@@ -177,6 +311,172 @@ impl Handler<FeedRequest> for Library {
     }
 }
 
+#[derive(Message)]
+#[rtype(result = "Vec<Chapter>")]
+pub struct EpisodeChaptersRequest(pub EpisodeId);
+
+impl Handler<EpisodeChaptersRequest> for Library {
+    type Result = ResponseActFuture<Self, Vec<Chapter>>;
+
+    fn handle(&mut self, msg: EpisodeChaptersRequest, _ctx: &mut Self::Context) -> Self::Result {
+        let episode_id = msg.0;
+        let chapters_url = match self.data_provider.get_pending_chapters_url(episode_id) {
+            Ok(Some(chapters_url)) => chapters_url,
+            Ok(None) => {
+                let chapters = match self.data_provider.get_chapters(episode_id) {
+                    Ok(chapters) => chapters,
+                    Err(error) => {
+                        log::error!(target: "sql", "cannot fetch chapters, {}", error);
+                        Vec::new()
+                    }
+                };
+                return Box::pin(actix::fut::ready(chapters));
+            }
+            Err(error) => {
+                log::error!(target: "sql", "cannot fetch chapters, {}", error);
+                return Box::pin(actix::fut::ready(Vec::new()));
+            }
+        };
+
+        let client = self.http_client.clone();
+        let future = wrap_future(async move { fetch_chapters(&client, &chapters_url).await }).map(
+            move |result, library: &mut Library, _ctx| match result {
+                Ok(chapters) => {
+                    if let Err(error) = library.data_provider.set_chapters(episode_id, &chapters) {
+                        log::error!(target: "sql", "cannot store chapters, {}", error);
+                    }
+                    chapters
+                }
+                Err(error) => {
+                    log::error!(target: "networking", "cannot load chapters, {}", error);
+                    Vec::new()
+                }
+            },
+        );
+        Box::pin(future)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Option<Vec<TranscriptCue>>")]
+pub struct EpisodeTranscriptRequest(pub EpisodeId);
+
+impl Handler<EpisodeTranscriptRequest> for Library {
+    type Result = ResponseActFuture<Self, Option<Vec<TranscriptCue>>>;
+
+    fn handle(&mut self, msg: EpisodeTranscriptRequest, _ctx: &mut Self::Context) -> Self::Result {
+        let episode_id = msg.0;
+        let source = match self.data_provider.get_transcript_source(episode_id) {
+            Ok(Some(source)) => source,
+            Ok(None) => return Box::pin(actix::fut::ready(None)),
+            Err(error) => {
+                log::error!(target: "sql", "cannot fetch transcript source, {}", error);
+                return Box::pin(actix::fut::ready(None));
+            }
+        };
+
+        let cache_path = self.transcript_cache_path(episode_id, source.format);
+        let cached_path = cache_path.as_ref().filter(|_| source.is_cached);
+        if let Some(cached_path) = cached_path {
+            match fs::read(cached_path) {
+                // A cached transcript that cannot be parsed is downloaded again.
+                Ok(data) => {
+                    if let Some(cues) = read_transcript(source.format, &data) {
+                        return Box::pin(actix::fut::ready(Some(cues)));
+                    }
+                }
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => {
+                    log::error!(target: "io", "Cannot read the transcript: {}", error);
+                }
+            }
+        }
+
+        let url = source.url.clone();
+        let client = self.http_client.clone();
+        let future = wrap_future(async move { fetch_document(&client, &url).await }).map(
+            move |result, library: &mut Library, _ctx| {
+                let data = match result {
+                    Ok(data) => data,
+                    Err(error) => {
+                        log::error!(target: "networking", "cannot load transcript, {}", error);
+                        return None;
+                    }
+                };
+                let cues = read_transcript(source.format, &data)?;
+                if let Some(cache_path) = cache_path {
+                    let result = cache_path
+                        .parent()
+                        .map(fs::create_dir_all)
+                        .unwrap_or(Ok(()))
+                        .and_then(|_| fs::write(&cache_path, &data));
+                    match result {
+                        Ok(()) => {
+                            let result = library
+                                .data_provider
+                                .set_transcript_cached(episode_id, &source.url);
+                            if let Err(error) = result {
+                                log::error!(target: "sql", "cannot update transcript, {}", error);
+                            }
+                        }
+                        Err(error) => {
+                            log::error!(target: "io", "Cannot save the transcript: {}", error);
+                        }
+                    }
+                }
+                Some(cues)
+            },
+        );
+        Box::pin(future)
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<EpisodeId>")]
+pub struct PendingDownloadsRequest;
+
+impl Handler<PendingDownloadsRequest> for Library {
+    type Result = Vec<EpisodeId>;
+
+    fn handle(&mut self, _msg: PendingDownloadsRequest, _ctx: &mut Self::Context) -> Self::Result {
+        match self.data_provider.get_pending_downloads() {
+            Ok(episode_ids) => episode_ids,
+            Err(error) => {
+                log::error!(target: "sql", "cannot fetch pending downloads, {}", error);
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<(EpisodeId, Download)>")]
+pub struct DeleteDownloadsRequest(pub Vec<EpisodeId>);
+
+impl Handler<DeleteDownloadsRequest> for Library {
+    type Result = Vec<(EpisodeId, Download)>;
+
+    fn handle(&mut self, msg: DeleteDownloadsRequest, _ctx: &mut Self::Context) -> Self::Result {
+        match self.data_provider.delete_downloads(&msg.0) {
+            Ok(downloads) => downloads,
+            Err(error) => {
+                log::error!(target: "sql", "cannot delete downloads, {}", error);
+                Vec::new()
+            }
+        }
+    }
+}
+
+fn read_transcript(format: TranscriptFormat, data: &[u8]) -> Option<Vec<TranscriptCue>> {
+    match parse_transcript(format, data) {
+        Ok(cues) => Some(cues),
+        Err(error) => {
+            log::error!("Cannot parse the transcript: {}", error);
+            None
+        }
+    }
+}
+
 impl Library {
     fn notify_update_listener(&mut self, message: FeedUpdateNotification) {
         if let Some(listener) = &self.update_listener {
@@ -187,43 +487,299 @@ impl Library {
         }
     }
 
+    fn send_download_request(&mut self, request: DownloadRequest) {
+        if let Some(download_manager) = &self.download_manager {
+            let result = download_manager.do_send(request);
+            if let Err(SendError::Closed(_)) = result {
+                self.download_manager = None;
+            }
+        }
+    }
+
+    fn apply_download_policy(&mut self, feed_id: FeedId) {
+        if self.download_manager.is_none() {
+            return;
+        }
+        match self.data_provider.get_auto_download_episodes(feed_id) {
+            Ok(episode_ids) => {
+                for episode_id in episode_ids {
+                    self.send_download_request(DownloadRequest::Download(episode_id));
+                }
+            }
+            Err(error) => {
+                log::error!(target: "sql", "cannot apply download policy, {}", error);
+            }
+        }
+    }
+
+    fn apply_retention_policy(&mut self) {
+        if self.download_manager.is_none() {
+            return;
+        }
+        match self.data_provider.get_expired_downloads(Utc::now()) {
+            Ok(episode_ids) if episode_ids.is_empty() => {}
+            Ok(episode_ids) => self.send_download_request(DownloadRequest::Delete(episode_ids)),
+            Err(error) => {
+                log::error!(target: "sql", "cannot apply retention policy, {}", error);
+            }
+        }
+    }
+
+    fn schedule_next_update(&mut self, ctx: &mut <Library as Actor>::Context) {
+        if let Some(handle) = self.update_timer.take() {
+            ctx.cancel_future(handle);
+        }
+        let next_update = match self.update_interval {
+            Some(interval) => match self
+                .data_provider
+                .get_next_update_time(Utc::now(), interval)
+            {
+                Ok(next_update) => next_update,
+                Err(error) => {
+                    log::error!(target: "sql", "cannot schedule update, {}", error);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let next_update = next_update.map(|next_update| {
+            let now = Utc::now();
+            let next_update =
+                next_update.max(now + chrono::Duration::seconds(MIN_SCHEDULED_UPDATE_DELAY_SECS));
+            let delay = (next_update - now).to_std().unwrap_or(Duration::ZERO);
+            let handle = ctx.run_later(delay, |library, ctx| {
+                library.update_timer = None;
+                library.run_scheduled_update(ctx);
+            });
+            self.update_timer = Some(handle);
+            next_update
+        });
+        self.notify_update_listener(FeedUpdateNotification::NextUpdateScheduled(next_update));
+    }
+
+    fn run_scheduled_update(&mut self, ctx: &mut <Library as Actor>::Context) {
+        if let Some(interval) = self.update_interval {
+            match self
+                .data_provider
+                .get_update_sources(UpdateQuery::Due(Utc::now(), interval))
+            {
+                Ok(sources) => self.schedule_update(sources, ctx),
+                Err(error) => log::error!(target: "sql", "cannot update, {}", error),
+            }
+        }
+        if self.updating_feeds.is_empty() {
+            self.schedule_next_update(ctx);
+        }
+    }
+
+    // The stored credentials are read immediately, while the password command only runs once
+    // the returned future is awaited, so that it does not block the library.
+    fn resolve_credentials(
+        &mut self,
+        feed_id: FeedId,
+    ) -> impl Future<Output = Option<Credentials>> + 'static {
+        let credentials = self.stored_credentials(feed_id);
+        async move {
+            let (credentials, host) = credentials?;
+            let password = match credentials.password_command {
+                Some(command) => match run_password_command(&command).await {
+                    Ok(password) => Some(password),
+                    Err(error) => {
+                        log::error!(target: "io", "Cannot read the password, {}", error);
+                        None
+                    }
+                },
+                None => None,
+            };
+            Some(Credentials {
+                username: credentials.username,
+                password,
+                host,
+            })
+        }
+    }
+
+    fn stored_credentials(&mut self, feed_id: FeedId) -> Option<(FeedCredentials, String)> {
+        let credentials = match self.data_provider.get_feed_credentials(feed_id) {
+            Ok(credentials) => credentials?,
+            Err(error) => {
+                log::error!(target: "sql", "cannot get feed credentials, {}", error);
+                return None;
+            }
+        };
+        // Credentials are scoped to the host of the feed's own source.
+        let host = match self.data_provider.get_feed(feed_id) {
+            Ok(feed) => reqwest::Url::parse(&feed?.source)
+                .ok()?
+                .host_str()?
+                .to_string(),
+            Err(error) => {
+                log::error!(target: "sql", "cannot get feed credentials, {}", error);
+                return None;
+            }
+        };
+        Some((credentials, host))
+    }
+
+    fn transcript_cache_path(
+        &self,
+        episode_id: EpisodeId,
+        format: TranscriptFormat,
+    ) -> Option<PathBuf> {
+        let file_name = format!("{}.{}", episode_id.as_i64(), format.file_extension());
+        Some(self.transcripts_path.as_ref()?.join(file_name))
+    }
+
+    // The format of a transcript may have changed since it was cached, so files in every format
+    // are removed.
+    fn remove_cached_transcripts(&self, episode_ids: &[EpisodeId]) {
+        let formats = [
+            TranscriptFormat::Srt,
+            TranscriptFormat::WebVtt,
+            TranscriptFormat::Json,
+        ];
+        for episode_id in episode_ids {
+            for format in formats {
+                let path = match self.transcript_cache_path(*episode_id, format) {
+                    Some(path) => path,
+                    None => return,
+                };
+                match fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(error) if error.kind() == ErrorKind::NotFound => {}
+                    Err(error) => {
+                        log::error!(target: "io", "Cannot remove the transcript: {}", error);
+                    }
+                }
+            }
+        }
+    }
+
+    // The rows of the downloads are already gone with their episodes, so the download manager
+    // only cancels them and removes the files.
+    fn discard_downloads(&mut self, downloads: Vec<(EpisodeId, Download)>) {
+        if downloads.is_empty() {
+            return;
+        }
+        if self.download_manager.is_some() {
+            self.send_download_request(DownloadRequest::Discard(downloads));
+            return;
+        }
+        for (_, download) in downloads {
+            match fs::remove_file(&download.path) {
+                Ok(()) => {}
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => {
+                    log::error!(target: "io", "Cannot delete {:?}: {}", download.path, error);
+                }
+            }
+        }
+    }
+
+    fn move_feed(
+        &mut self,
+        feed_id: FeedId,
+        old_source: &str,
+        new_source: Option<String>,
+    ) -> Result<(), QueryError> {
+        if let Some(new_source) = new_source.filter(|new_source| new_source != old_source) {
+            self.data_provider.set_feed_source(feed_id, &new_source)?;
+            log::warn!(
+                target: "networking",
+                "The feed {} has moved permanently to {}, the subscription has been updated",
+                redact_url(old_source),
+                redact_url(&new_source)
+            );
+        }
+        Ok(())
+    }
+
     fn schedule_update(
         &mut self,
-        mut feeds: Vec<(FeedId, String)>,
+        mut sources: Vec<UpdateSource>,
         ctx: &mut <Library as Actor>::Context,
     ) {
-        feeds.retain(|(feed_id, _)| !self.updating_feeds.contains(feed_id));
-        if feeds.is_empty() {
+        sources.retain(|source| !self.updating_feeds.contains(&source.feed_id));
+        if sources.is_empty() {
             return;
         }
 
-        let feed_ids: Vec<FeedId> = feeds.iter().map(|(id, _)| id).cloned().collect();
+        let feed_ids: Vec<FeedId> = sources.iter().map(|source| source.feed_id).collect();
         self.updating_feeds.extend(feed_ids.iter().cloned());
         self.notify_update_listener(FeedUpdateNotification::UpdateStarted(feed_ids));
 
-        for (feed_id, source) in feeds {
-            let permit_fut = Arc::clone(&self.feeds_semaphore).acquire_owned();
+        for UpdateSource {
+            feed_id,
+            source,
+            validators,
+            archive,
+        } in sources
+        {
+            let store_validators = validators.is_some();
+            let old_source = source.clone();
+            let credentials = self.resolve_credentials(feed_id);
+            let client = self.http_client.clone();
             let future = wrap_future(async move {
-                let _permit = permit_fut.await.unwrap();
-                fetch_feed(&source).await
+                let credentials = credentials.await;
+                fetch_feed(&client, &source, validators.as_ref(), credentials.as_ref()).await
             })
-            .map(move |result, library: &mut Library, _ctx| {
+            .map(move |result, library: &mut Library, ctx| {
                 library.updating_feeds.remove(&feed_id);
+                let succeeded = result.is_ok();
                 let result: Result<_, QueryError> = match result {
-                    Ok(mut feed) => (|| {
+                    Ok(FeedResponse {
+                        feed: FetchedFeed::Modified(mut feed, validators),
+                        moved_to,
+                    }) => (|| {
+                        // Episodes of a feed that has never been loaded are not reported as
+                        // added, neither are the ones coming from archives.
+                        let report_added = !archive
+                            && library
+                                .data_provider
+                                .get_feed(feed_id)?
+                                .map_or(false, |feed| feed.status != FeedStatus::Pending);
+                        let mut added_episodes = Vec::new();
+                        let mut deleted_episodes = Vec::new();
+                        let mut deleted_downloads = Vec::new();
                         let mut writer = library.data_provider.writer(feed_id)?;
+                        let refresh_hints = feed.refresh_hints();
                         let feed_metadata = feed.feed_metadata();
                         let mut feed_summary =
                             FeedSummary::from_metadata(feed_id, &feed_metadata, 0);
                         writer.set_feed_metadata(&feed_metadata)?;
+                        // An archive page describes the past of the feed, not how often it is
+                        // refreshed.
+                        if !archive {
+                            writer.set_refresh_hints(&refresh_hints)?;
+                        }
+                        if store_validators {
+                            writer.set_cache_validators(&validators)?;
+                        }
                         while let Some(episode_metadata) = feed.next_episode_metadata() {
                             if episode_metadata.block {
-                                writer.delete_episode(episode_metadata.guid)?;
+                                let deleted = writer.delete_episode(episode_metadata.guid)?;
+                                if let Some((episode_id, download)) = deleted {
+                                    deleted_episodes.push(episode_id);
+                                    deleted_downloads
+                                        .extend(download.map(|download| (episode_id, download)));
+                                }
                             } else {
-                                writer.set_episode_metadata(&episode_metadata)?;
+                                let result = writer.set_episode_metadata(&episode_metadata)?;
+                                if let EpisodeWriteResult::Inserted(episode_id) = result {
+                                    added_episodes.push(episode_id);
+                                }
                             }
                         }
                         writer.close()?;
+                        library.remove_cached_transcripts(&deleted_episodes);
+                        library.discard_downloads(deleted_downloads);
+                        // Redirects of an archive page do not move the feed itself.
+                        if !archive {
+                            let new_source = feed.new_feed_url().map(str::to_string).or(moved_to);
+                            library.move_feed(feed_id, &old_source, new_source)?;
+                        }
+                        library.apply_download_policy(feed_id);
 
                         let new_episodes_query = EpisodesQuery::default()
                             .feed_id(feed_id)
@@ -241,12 +797,38 @@ impl Library {
                             }
                         }
 
+                        if report_added && !added_episodes.is_empty() {
+                            library.notify_update_listener(FeedUpdateNotification::EpisodesAdded(
+                                feed_id,
+                                added_episodes,
+                            ));
+                        }
                         library.notify_update_listener(FeedUpdateNotification::UpdateFinished(
                             feed_id,
                             FeedUpdateResult::Updated(feed_summary),
                         ));
                         Ok(())
                     })(),
+                    Ok(FeedResponse {
+                        feed: FetchedFeed::NotModified,
+                        moved_to,
+                    }) => (|| {
+                        if !archive {
+                            library.move_feed(feed_id, &old_source, moved_to)?;
+                        }
+                        library
+                            .data_provider
+                            .set_feed_status(feed_id, FeedStatus::Loaded)?;
+                        if let Some(feed_summary) =
+                            library.data_provider.get_feed_summary(feed_id)?
+                        {
+                            library.notify_update_listener(FeedUpdateNotification::UpdateFinished(
+                                feed_id,
+                                FeedUpdateResult::Updated(feed_summary),
+                            ));
+                        }
+                        Ok(())
+                    })(),
                     Err(err) => {
                         log::error!(target: "networking", "{}", err);
                         let new_status = FeedStatus::Error(err.as_feed_error());
@@ -266,6 +848,21 @@ impl Library {
                 if let Err(error) = result {
                     log::error!(target: "sql", "cannot update, {}", error);
                 };
+
+                // Fetching an archive page says nothing about whether the feed is due.
+                if !archive {
+                    let now = Utc::now();
+                    let result = library
+                        .data_provider
+                        .set_feed_checked(feed_id, now, succeeded);
+                    if let Err(error) = result {
+                        log::error!(target: "sql", "cannot update, {}", error);
+                    }
+                }
+                if library.updating_feeds.is_empty() {
+                    library.apply_retention_policy();
+                    library.schedule_next_update(ctx);
+                }
             });
             ctx.spawn(future);
         }
@@ -283,10 +880,13 @@ pub enum FeedUpdateResult {
 pub enum FeedUpdateNotification {
     UpdateStarted(Vec<FeedId>),
     UpdateFinished(FeedId, FeedUpdateResult),
+    EpisodesAdded(FeedId, Vec<EpisodeId>),
     FeedAdded(FeedSummary),
     FeedDeleted(FeedId),
     GroupAdded(GroupSummary),
+    SavedViewAdded(SavedViewSummary),
     NewCountUpdated(HashMap<FeedId, usize>),
+    NextUpdateScheduled(Option<DateTime<Utc>>),
 }
 
 #[derive(Debug)]
@@ -294,6 +894,8 @@ pub enum UpdateQuery {
     Single(FeedId),
     All,
     Pending,
+    Failed,
+    Due(DateTime<Utc>, Duration),
 }
 
 #[derive(Debug, Message)]
@@ -304,7 +906,16 @@ pub enum FeedUpdateRequest {
     AddGroup(String),
     DeleteFeed(FeedId),
     DeleteGroup(GroupId),
+    AddSavedView(String, EpisodesFilter),
+    DeleteSavedView(SavedViewId),
     SetGroupPosition(GroupId, usize),
+    AddToQueue(EpisodeId),
+    RemoveFromQueue(EpisodeId),
+    SetQueuePosition(EpisodeId, usize),
+    ClearQueue,
+    AddListeningSession(ListeningSession),
+    AddBookmark(EpisodeId, Duration, Option<String>),
+    DeleteBookmark(BookmarkId),
     RenameFeed(FeedId, String),
     RenameGroup(GroupId, String),
     Update(UpdateQuery),
@@ -314,6 +925,16 @@ pub enum FeedUpdateRequest {
     SetHidden(EpisodesQuery, bool),
     SetFeedEnabled(FeedId, bool),
     ReverseFeedOrder(FeedId),
+    SetSortKey(FeedView<FeedId, GroupId, SavedViewId>, EpisodesSortKey),
+    SetFeedPolicy(FeedId, FeedPolicy),
+    SetFeedPlaybackRate(FeedId, f64),
+    SetFeedVolumeOffset(FeedId, f64),
+    SetFeedIntroSkip(FeedId, Duration),
+    SetFeedOutroSkip(FeedId, Duration),
+    SetFeedSkipSilence(FeedId, Option<bool>),
+    SetDownloadManager(Recipient<DownloadRequest>),
+    SetUpdateInterval(Option<Duration>),
+    SetDownload(EpisodeId, Download),
 }
 
 impl Handler<FeedUpdateRequest> for Library {
@@ -332,7 +953,8 @@ impl Handler<FeedUpdateRequest> for Library {
             }
 
             FeedUpdateRequest::AddArchive(feed_id, feed_url) => {
-                self.schedule_update(vec![(feed_id, feed_url)], ctx);
+                let source = UpdateSource::archive(feed_id, feed_url);
+                self.schedule_update(vec![source], ctx);
             }
             FeedUpdateRequest::AddFeed(data) => {
                 let feed_id = match self.data_provider.create_feed_pending(&data) {
@@ -347,11 +969,11 @@ impl Handler<FeedUpdateRequest> for Library {
                     }
                 };
 
-                let source = data.source.clone();
+                let source = UpdateSource::new(feed_id, data.source.clone());
                 self.notify_update_listener(FeedUpdateNotification::FeedAdded(
                     FeedSummary::new_created(feed_id, data),
                 ));
-                self.schedule_update(vec![(feed_id, source)], ctx);
+                self.schedule_update(vec![source], ctx);
             }
             FeedUpdateRequest::AddGroup(name) => match self.data_provider.create_group(&name) {
                 Ok(Some(group_id)) => {
@@ -366,8 +988,24 @@ impl Handler<FeedUpdateRequest> for Library {
                 }
             },
             FeedUpdateRequest::DeleteFeed(feed_id) => {
+                let cached_transcripts = match self.data_provider.get_cached_transcripts(feed_id) {
+                    Ok(episode_ids) => episode_ids,
+                    Err(error) => {
+                        log::error!(target: "sql", "cannot fetch cached transcripts, {}", error);
+                        Vec::new()
+                    }
+                };
+                let downloads = match self.data_provider.get_feed_downloads(feed_id) {
+                    Ok(downloads) => downloads,
+                    Err(error) => {
+                        log::error!(target: "sql", "cannot fetch downloads, {}", error);
+                        Vec::new()
+                    }
+                };
                 match self.data_provider.delete_feed(feed_id) {
                     Ok(_) => {
+                        self.remove_cached_transcripts(&cached_transcripts);
+                        self.discard_downloads(downloads);
                         self.notify_update_listener(FeedUpdateNotification::FeedDeleted(feed_id));
                     }
                     Err(error) => {
@@ -380,11 +1018,74 @@ impl Handler<FeedUpdateRequest> for Library {
                     log::error!(target: "sql", "cannot delete group, {}", error);
                 }
             }
+            FeedUpdateRequest::AddSavedView(name, filter) => {
+                match self.data_provider.create_saved_view(&name, &filter) {
+                    Ok(Some(view_id)) => {
+                        let summary = SavedViewSummary {
+                            id: view_id,
+                            name,
+                            filter,
+                        };
+                        self.notify_update_listener(FeedUpdateNotification::SavedViewAdded(
+                            summary,
+                        ));
+                    }
+                    Ok(None) => {
+                        log::warn!("The view with this name already exists");
+                    }
+                    Err(error) => {
+                        log::error!(target: "sql", "cannot create view, {}", error);
+                    }
+                }
+            }
+            FeedUpdateRequest::DeleteSavedView(view_id) => {
+                if let Err(error) = self.data_provider.delete_saved_view(view_id) {
+                    log::error!(target: "sql", "cannot delete view, {}", error);
+                }
+            }
             FeedUpdateRequest::SetGroupPosition(group_id, position) => {
                 if let Err(error) = self.data_provider.set_group_position(group_id, position) {
                     log::error!(target: "sql", "cannot change group position, {}", error);
                 }
             }
+            FeedUpdateRequest::AddToQueue(episode_id) => {
+                if let Err(error) = self.data_provider.add_to_queue(episode_id) {
+                    log::error!(target: "sql", "cannot add to the queue, {}", error);
+                }
+            }
+            FeedUpdateRequest::RemoveFromQueue(episode_id) => {
+                if let Err(error) = self.data_provider.remove_from_queue(episode_id) {
+                    log::error!(target: "sql", "cannot remove from the queue, {}", error);
+                }
+            }
+            FeedUpdateRequest::SetQueuePosition(episode_id, position) => {
+                if let Err(error) = self.data_provider.set_queue_position(episode_id, position) {
+                    log::error!(target: "sql", "cannot change queue position, {}", error);
+                }
+            }
+            FeedUpdateRequest::ClearQueue => {
+                if let Err(error) = self.data_provider.clear_queue() {
+                    log::error!(target: "sql", "cannot clear the queue, {}", error);
+                }
+            }
+            FeedUpdateRequest::AddListeningSession(session) => {
+                if let Err(error) = self.data_provider.add_listening_session(&session) {
+                    log::error!(target: "sql", "cannot record the listening session, {}", error);
+                }
+            }
+            FeedUpdateRequest::AddBookmark(episode_id, position, note) => {
+                let result = self
+                    .data_provider
+                    .add_bookmark(episode_id, position, note.as_deref());
+                if let Err(error) = result {
+                    log::error!(target: "sql", "cannot add the bookmark, {}", error);
+                }
+            }
+            FeedUpdateRequest::DeleteBookmark(bookmark_id) => {
+                if let Err(error) = self.data_provider.delete_bookmark(bookmark_id) {
+                    log::error!(target: "sql", "cannot delete the bookmark, {}", error);
+                }
+            }
             FeedUpdateRequest::RenameFeed(feed_id, name) => {
                 if let Err(error) = self.data_provider.rename_feed(feed_id, name) {
                     log::error!(target: "sql", "cannot rename feed, {}", error);
@@ -396,6 +1097,7 @@ impl Handler<FeedUpdateRequest> for Library {
                 }
             }
             FeedUpdateRequest::SetStatus(query, status) => {
+                let finished = status == EpisodeStatus::Finished;
                 let result: Result<(), QueryError> = (|| {
                     let updated_feeds = self.data_provider.set_episode_status(query, status)?;
                     let new_episodes_count =
@@ -408,6 +1110,9 @@ impl Handler<FeedUpdateRequest> for Library {
                 if let Err(error) = result {
                     log::error!(target: "sql", "cannot update status, {}", error);
                 }
+                if finished {
+                    self.apply_retention_policy();
+                }
             }
             FeedUpdateRequest::SetHidden(query, hidden) => {
                 if let Err(error) = self.data_provider.set_episode_hidden(query, hidden) {
@@ -424,11 +1129,62 @@ impl Handler<FeedUpdateRequest> for Library {
                     log::error!(target: "sql", "cannot reverse order, {}", error);
                 }
             }
+            FeedUpdateRequest::SetSortKey(view, sort_key) => {
+                if let Err(error) = self.data_provider.set_episodes_sort_key(view, sort_key) {
+                    log::error!(target: "sql", "cannot set sort key, {}", error);
+                }
+            }
             FeedUpdateRequest::SetGroup(group_id, feed_id) => {
                 if let Err(error) = self.data_provider.set_feed_for_group(group_id, feed_id) {
                     log::error!(target: "sql", "cannot assign group, {}", error);
                 }
             }
+            FeedUpdateRequest::SetFeedPolicy(feed_id, policy) => {
+                if let Err(error) = self.data_provider.set_feed_policy(feed_id, &policy) {
+                    log::error!(target: "sql", "cannot update feed policy, {}", error);
+                    return;
+                }
+                self.apply_download_policy(feed_id);
+                self.apply_retention_policy();
+            }
+            FeedUpdateRequest::SetFeedPlaybackRate(feed_id, rate) => {
+                if let Err(error) = self.data_provider.set_feed_playback_rate(feed_id, rate) {
+                    log::error!(target: "sql", "cannot update feed playback rate, {}", error);
+                }
+            }
+            FeedUpdateRequest::SetFeedVolumeOffset(feed_id, offset) => {
+                if let Err(error) = self.data_provider.set_feed_volume_offset(feed_id, offset) {
+                    log::error!(target: "sql", "cannot update feed volume, {}", error);
+                }
+            }
+            FeedUpdateRequest::SetFeedIntroSkip(feed_id, duration) => {
+                if let Err(error) = self.data_provider.set_feed_intro_skip(feed_id, duration) {
+                    log::error!(target: "sql", "cannot update feed intro, {}", error);
+                }
+            }
+            FeedUpdateRequest::SetFeedOutroSkip(feed_id, duration) => {
+                if let Err(error) = self.data_provider.set_feed_outro_skip(feed_id, duration) {
+                    log::error!(target: "sql", "cannot update feed outro, {}", error);
+                }
+            }
+            FeedUpdateRequest::SetFeedSkipSilence(feed_id, skip) => {
+                if let Err(error) = self.data_provider.set_feed_skip_silence(feed_id, skip) {
+                    log::error!(target: "sql", "cannot update feed silence skipping, {}", error);
+                }
+            }
+            FeedUpdateRequest::SetDownloadManager(recipient) => {
+                self.download_manager = Some(recipient);
+                self.apply_retention_policy();
+            }
+            FeedUpdateRequest::SetUpdateInterval(interval) => {
+                self.update_interval = interval;
+                self.schedule_next_update(ctx);
+            }
+            FeedUpdateRequest::SetDownload(episode_id, download) => {
+                if let Err(error) = self.data_provider.set_download(episode_id, &download) {
+                    log::error!(target: "sql", "cannot update download, {}", error);
+                }
+            }
         }
     }
 }
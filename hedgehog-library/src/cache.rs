@@ -1,11 +1,16 @@
 use crate::datasource::{DataProvider, DbResult, EpisodeWriter};
 use crate::model::{
-    Episode, EpisodeId, EpisodePlaybackData, EpisodeStatus, EpisodeSummary, EpisodesListMetadata,
-    Feed, FeedId, FeedOMPLEntry, FeedStatus, FeedSummary, GroupId,
+    Bookmark, BookmarkId, Chapter, Download, Episode, EpisodeId, EpisodePlaybackData,
+    EpisodeStatus, EpisodeSummary, EpisodesFilter, EpisodesListMetadata, EpisodesSortKey, Feed,
+    FeedCredentials, FeedId, FeedOMPLEntry, FeedPolicy, FeedStatus, FeedSummary, FeedView, GroupId,
+    ListeningSession, ListeningStats, SavedViewId, SavedViewSummary, TranscriptSource,
+    UpdateSource,
 };
 use crate::{EpisodesQuery, NewFeedMetadata, UpdateQuery};
+use chrono::{DateTime, Utc};
 use std::collections::{HashMap, HashSet};
 use std::ops::Range;
+use std::time::Duration;
 
 pub struct InMemoryCache<D> {
     data_provider: D,
@@ -49,11 +54,15 @@ impl<D: DataProvider> DataProvider for InMemoryCache<D> {
         self.data_provider.get_feed_summaries()
     }
 
+    fn get_feed_summary(&mut self, id: FeedId) -> DbResult<Option<FeedSummary>> {
+        self.data_provider.get_feed_summary(id)
+    }
+
     fn get_feed_opml_entries(&mut self) -> DbResult<Vec<FeedOMPLEntry>> {
         self.data_provider.get_feed_opml_entries()
     }
 
-    fn get_update_sources(&mut self, update: UpdateQuery) -> DbResult<Vec<(FeedId, String)>> {
+    fn get_update_sources(&mut self, update: UpdateQuery) -> DbResult<Vec<UpdateSource>> {
         self.data_provider.get_update_sources(update)
     }
 
@@ -64,8 +73,60 @@ impl<D: DataProvider> DataProvider for InMemoryCache<D> {
         self.data_provider.get_new_episodes_count(feed_ids)
     }
 
+    fn get_next_update_time(
+        &mut self,
+        now: DateTime<Utc>,
+        interval: Duration,
+    ) -> DbResult<Option<DateTime<Utc>>> {
+        self.data_provider.get_next_update_time(now, interval)
+    }
+
+    fn set_feed_checked(
+        &mut self,
+        feed_id: FeedId,
+        checked_at: DateTime<Utc>,
+        succeeded: bool,
+    ) -> DbResult<()> {
+        self.data_provider
+            .set_feed_checked(feed_id, checked_at, succeeded)
+    }
+
     fn rename_feed(&mut self, feed_id: FeedId, name: String) -> DbResult<()> {
-        self.data_provider.rename_feed(feed_id, name)
+        self.data_provider.rename_feed(feed_id, name)?;
+        self.invalidate_where(|query| query.text.is_some());
+        Ok(())
+    }
+
+    fn get_feed_credentials(&mut self, feed_id: FeedId) -> DbResult<Option<FeedCredentials>> {
+        self.data_provider.get_feed_credentials(feed_id)
+    }
+
+    fn set_feed_source(&mut self, feed_id: FeedId, source: &str) -> DbResult<()> {
+        self.data_provider.set_feed_source(feed_id, source)
+    }
+
+    fn set_feed_policy(&mut self, feed_id: FeedId, policy: &FeedPolicy) -> DbResult<()> {
+        self.data_provider.set_feed_policy(feed_id, policy)
+    }
+
+    fn set_feed_playback_rate(&mut self, feed_id: FeedId, rate: f64) -> DbResult<()> {
+        self.data_provider.set_feed_playback_rate(feed_id, rate)
+    }
+
+    fn set_feed_volume_offset(&mut self, feed_id: FeedId, offset: f64) -> DbResult<()> {
+        self.data_provider.set_feed_volume_offset(feed_id, offset)
+    }
+
+    fn set_feed_intro_skip(&mut self, feed_id: FeedId, duration: Duration) -> DbResult<()> {
+        self.data_provider.set_feed_intro_skip(feed_id, duration)
+    }
+
+    fn set_feed_outro_skip(&mut self, feed_id: FeedId, duration: Duration) -> DbResult<()> {
+        self.data_provider.set_feed_outro_skip(feed_id, duration)
+    }
+
+    fn set_feed_skip_silence(&mut self, feed_id: FeedId, skip: Option<bool>) -> DbResult<()> {
+        self.data_provider.set_feed_skip_silence(feed_id, skip)
     }
 
     fn create_group(&mut self, name: &str) -> DbResult<Option<GroupId>> {
@@ -92,6 +153,67 @@ impl<D: DataProvider> DataProvider for InMemoryCache<D> {
         self.data_provider.set_group_position(group_id, position)
     }
 
+    fn create_saved_view(
+        &mut self,
+        name: &str,
+        filter: &EpisodesFilter,
+    ) -> DbResult<Option<SavedViewId>> {
+        self.data_provider.create_saved_view(name, filter)
+    }
+
+    fn get_saved_views(&mut self) -> DbResult<Vec<SavedViewSummary>> {
+        self.data_provider.get_saved_views()
+    }
+
+    fn delete_saved_view(&mut self, view_id: SavedViewId) -> DbResult<()> {
+        self.data_provider.delete_saved_view(view_id)
+    }
+
+    fn get_queue(&mut self) -> DbResult<Vec<EpisodeSummary>> {
+        self.data_provider.get_queue()
+    }
+
+    fn add_to_queue(&mut self, episode_id: EpisodeId) -> DbResult<()> {
+        self.data_provider.add_to_queue(episode_id)
+    }
+
+    fn remove_from_queue(&mut self, episode_id: EpisodeId) -> DbResult<()> {
+        self.data_provider.remove_from_queue(episode_id)
+    }
+
+    fn set_queue_position(&mut self, episode_id: EpisodeId, position: usize) -> DbResult<()> {
+        self.data_provider.set_queue_position(episode_id, position)
+    }
+
+    fn clear_queue(&mut self) -> DbResult<()> {
+        self.data_provider.clear_queue()
+    }
+
+    fn add_listening_session(&mut self, session: &ListeningSession) -> DbResult<()> {
+        self.data_provider.add_listening_session(session)
+    }
+
+    fn get_listening_stats(&mut self, since: Option<DateTime<Utc>>) -> DbResult<ListeningStats> {
+        self.data_provider.get_listening_stats(since)
+    }
+
+    fn add_bookmark(
+        &mut self,
+        episode_id: EpisodeId,
+        position: Duration,
+        note: Option<&str>,
+    ) -> DbResult<()> {
+        self.data_provider.add_bookmark(episode_id, position, note)
+    }
+
+    fn get_bookmarks(&mut self) -> DbResult<Vec<Bookmark>> {
+        self.data_provider.get_bookmarks()
+    }
+
+    fn delete_bookmark(&mut self, bookmark_id: BookmarkId) -> DbResult<()> {
+        self.data_provider.delete_bookmark(bookmark_id)
+    }
+
     fn get_episode(&mut self, episode_id: EpisodeId) -> DbResult<Option<Episode>> {
         self.data_provider.get_episode(episode_id)
     }
@@ -103,6 +225,62 @@ impl<D: DataProvider> DataProvider for InMemoryCache<D> {
         self.data_provider.get_episode_playback_data(episode_id)
     }
 
+    fn get_pending_chapters_url(&mut self, episode_id: EpisodeId) -> DbResult<Option<String>> {
+        self.data_provider.get_pending_chapters_url(episode_id)
+    }
+
+    fn get_chapters(&mut self, episode_id: EpisodeId) -> DbResult<Vec<Chapter>> {
+        self.data_provider.get_chapters(episode_id)
+    }
+
+    fn set_chapters(&mut self, episode_id: EpisodeId, chapters: &[Chapter]) -> DbResult<()> {
+        self.data_provider.set_chapters(episode_id, chapters)
+    }
+
+    fn get_transcript_source(
+        &mut self,
+        episode_id: EpisodeId,
+    ) -> DbResult<Option<TranscriptSource>> {
+        self.data_provider.get_transcript_source(episode_id)
+    }
+
+    fn set_transcript_cached(&mut self, episode_id: EpisodeId, url: &str) -> DbResult<()> {
+        self.data_provider.set_transcript_cached(episode_id, url)
+    }
+
+    fn get_cached_transcripts(&mut self, feed_id: FeedId) -> DbResult<Vec<EpisodeId>> {
+        self.data_provider.get_cached_transcripts(feed_id)
+    }
+
+    fn get_pending_downloads(&mut self) -> DbResult<Vec<EpisodeId>> {
+        self.data_provider.get_pending_downloads()
+    }
+
+    fn get_feed_downloads(&mut self, feed_id: FeedId) -> DbResult<Vec<(EpisodeId, Download)>> {
+        self.data_provider.get_feed_downloads(feed_id)
+    }
+
+    fn set_download(&mut self, episode_id: EpisodeId, download: &Download) -> DbResult<()> {
+        self.invalidate_all();
+        self.data_provider.set_download(episode_id, download)
+    }
+
+    fn delete_downloads(
+        &mut self,
+        episode_ids: &[EpisodeId],
+    ) -> DbResult<Vec<(EpisodeId, Download)>> {
+        self.invalidate_all();
+        self.data_provider.delete_downloads(episode_ids)
+    }
+
+    fn get_auto_download_episodes(&mut self, feed_id: FeedId) -> DbResult<Vec<EpisodeId>> {
+        self.data_provider.get_auto_download_episodes(feed_id)
+    }
+
+    fn get_expired_downloads(&mut self, now: DateTime<Utc>) -> DbResult<Vec<EpisodeId>> {
+        self.data_provider.get_expired_downloads(now)
+    }
+
     fn get_episodes_list_metadata(
         &mut self,
         query: EpisodesQuery,
@@ -151,6 +329,14 @@ impl<D: DataProvider> DataProvider for InMemoryCache<D> {
         self.data_provider.count_episodes(query)
     }
 
+    fn get_next_episode(
+        &mut self,
+        query: EpisodesQuery,
+        after: EpisodeId,
+    ) -> DbResult<Option<EpisodeId>> {
+        self.data_provider.get_next_episode(query, after)
+    }
+
     fn create_feed_pending(&mut self, data: &NewFeedMetadata) -> DbResult<Option<FeedId>> {
         self.data_provider.create_feed_pending(data)
     }
@@ -174,6 +360,20 @@ impl<D: DataProvider> DataProvider for InMemoryCache<D> {
         self.data_provider.reverse_feed_order(feed_id)
     }
 
+    fn get_episodes_sort_keys(
+        &mut self,
+    ) -> DbResult<HashMap<FeedView<FeedId, GroupId, SavedViewId>, EpisodesSortKey>> {
+        self.data_provider.get_episodes_sort_keys()
+    }
+
+    fn set_episodes_sort_key(
+        &mut self,
+        view: FeedView<FeedId, GroupId, SavedViewId>,
+        sort_key: EpisodesSortKey,
+    ) -> DbResult<()> {
+        self.data_provider.set_episodes_sort_key(view, sort_key)
+    }
+
     fn set_episode_status(
         &mut self,
         query: EpisodesQuery,
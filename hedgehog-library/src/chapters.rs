@@ -0,0 +1,81 @@
+use crate::model::Chapter;
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Debug, Deserialize)]
+struct ChaptersDocument {
+    chapters: Vec<ChapterEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChapterEntry {
+    #[serde(rename = "startTime")]
+    start_time: f64,
+    title: Option<String>,
+    #[serde(default = "default_toc")]
+    toc: bool,
+}
+
+fn default_toc() -> bool {
+    true
+}
+
+pub(crate) fn parse_chapters(data: &[u8]) -> Result<Vec<Chapter>, serde_json::Error> {
+    let document: ChaptersDocument = serde_json::from_slice(data)?;
+    let mut chapters: Vec<Chapter> = document
+        .chapters
+        .into_iter()
+        .filter(|entry| entry.toc && entry.start_time.is_finite() && entry.start_time >= 0.0)
+        .map(|entry| Chapter {
+            start: Duration::from_secs_f64(entry.start_time),
+            title: entry.title,
+        })
+        .collect();
+    chapters.sort_by_key(|chapter| chapter.start);
+    Ok(chapters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_chapters;
+    use crate::model::Chapter;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_chapters() {
+        let chapters = parse_chapters(
+            br#"{
+                "version": "1.2.0",
+                "chapters": [
+                    {"startTime": 0, "title": "Intro"},
+                    {"startTime": 95.5, "title": "Sponsor", "toc": false},
+                    {"startTime": 1230, "title": "Outro"},
+                    {"startTime": 120.25}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            chapters,
+            vec![
+                Chapter {
+                    start: Duration::ZERO,
+                    title: Some("Intro".to_string()),
+                },
+                Chapter {
+                    start: Duration::from_secs_f64(120.25),
+                    title: None,
+                },
+                Chapter {
+                    start: Duration::from_secs(1230),
+                    title: Some("Outro".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_chapters() {
+        assert!(parse_chapters(b"{\"version\": \"1.2.0\"}").is_err());
+    }
+}
@@ -1,13 +1,17 @@
 use crate::actor::UpdateQuery;
 use crate::metadata::{EpisodeMetadata, FeedMetadata};
 use crate::model::{
-    Episode, EpisodeId, EpisodePlaybackData, EpisodeStatus, EpisodeSummary, EpisodeSummaryStatus,
-    EpisodesListMetadata, Feed, FeedId, FeedOMPLEntry, FeedStatus, FeedSummary, FeedView, GroupId,
-    GroupSummary,
+    Bookmark, BookmarkId, CacheValidators, Chapter, Download, Episode, EpisodeId,
+    EpisodePlaybackData, EpisodeStatus, EpisodeSummary, EpisodeSummaryStatus, EpisodesFilter,
+    EpisodesListMetadata, EpisodesSortKey, Feed, FeedCredentials, FeedId, FeedOMPLEntry,
+    FeedPolicy, FeedStatus, FeedSummary, FeedView, GroupId, GroupSummary, ListeningSession,
+    ListeningStats, RefreshHints, SavedViewId, SavedViewSummary, TranscriptSource, UpdateSource,
 };
+use chrono::{DateTime, Utc};
 use std::collections::{HashMap, HashSet};
 use std::marker::Unpin;
 use std::ops::Range;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -25,8 +29,14 @@ pub struct EpisodesQuery {
     pub(crate) feed_id: Option<FeedId>,
     pub(crate) group_id: Option<GroupId>,
     pub(crate) status: Option<EpisodeSummaryStatus>,
+    pub(crate) text: Option<String>,
+    pub(crate) min_duration: Option<Duration>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) published_after: Option<DateTime<Utc>>,
+    pub(crate) published_before: Option<DateTime<Utc>>,
     pub(crate) with_hidden: bool,
     pub(crate) include_feed_title: bool,
+    pub(crate) sort_key: EpisodesSortKey,
     pub(crate) reversed_order: bool,
 }
 
@@ -37,8 +47,10 @@ impl Default for EpisodesQuery {
             feed_id: None,
             group_id: None,
             status: None,
+            text: None,
             with_hidden: true,
             include_feed_title: false,
+            sort_key: EpisodesSortKey::default(),
             reversed_order: false,
         }
     }
@@ -65,6 +77,33 @@ impl EpisodesQuery {
         self
     }
 
+    /// Limits the query to the episodes whose title, description or feed title contain all the
+    /// words of `text` (or words starting with them).
+    pub fn text(mut self, text: String) -> Self {
+        self.text = Some(text);
+        self
+    }
+
+    pub fn duration_range(
+        mut self,
+        min_duration: Option<Duration>,
+        max_duration: Option<Duration>,
+    ) -> Self {
+        self.min_duration = min_duration;
+        self.max_duration = max_duration;
+        self
+    }
+
+    pub fn publication_range(
+        mut self,
+        published_after: Option<DateTime<Utc>>,
+        published_before: Option<DateTime<Utc>>,
+    ) -> Self {
+        self.published_after = published_after;
+        self.published_before = published_before;
+        self
+    }
+
     pub fn include_feed_title(mut self) -> Self {
         self.include_feed_title = true;
         self
@@ -75,23 +114,46 @@ impl EpisodesQuery {
         self
     }
 
+    pub fn sort_key(mut self, sort_key: EpisodesSortKey) -> Self {
+        self.sort_key = sort_key;
+        self
+    }
+
     pub fn reversed_order(mut self, reversed_order: bool) -> Self {
         self.reversed_order = reversed_order;
         self
     }
 
-    pub fn from_feed_view(feed_id: FeedView<FeedId, GroupId>) -> Self {
+    pub fn from_feed_view(feed_id: FeedView<FeedId, GroupId, &EpisodesFilter>) -> Self {
         match feed_id {
             FeedView::All => EpisodesQuery::default().include_feed_title(),
             FeedView::New => EpisodesQuery::default()
                 .status(EpisodeSummaryStatus::New)
                 .include_feed_title(),
+            FeedView::Saved(filter) => EpisodesQuery::from_filter(filter).include_feed_title(),
             FeedView::Feed(feed_id) => EpisodesQuery::default().feed_id(feed_id),
             FeedView::Group(feed_id) => EpisodesQuery::default()
                 .group_id(feed_id)
                 .include_feed_title(),
         }
     }
+
+    // Relative dates are counted from the start of the current day, so that the query (and
+    // therefore its cache key) only changes once a day.
+    fn from_filter(filter: &EpisodesFilter) -> Self {
+        let today = Utc::today().and_hms(0, 0, 0);
+        let days_ago = |days: u32| today - chrono::Duration::days(days as i64);
+        let mut query = EpisodesQuery::default()
+            .duration_range(filter.min_duration, filter.max_duration)
+            .publication_range(
+                filter.newer_than_days.map(days_ago),
+                filter.older_than_days.map(days_ago),
+            );
+        query.status = filter.status;
+        query.group_id = filter.group_id;
+        query.text = filter.text.clone();
+        query
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -99,6 +161,7 @@ pub struct NewFeedMetadata {
     pub(crate) source: String,
     pub(crate) title: Option<String>,
     pub(crate) link: Option<String>,
+    pub(crate) credentials: Option<FeedCredentials>,
 }
 
 impl NewFeedMetadata {
@@ -107,6 +170,7 @@ impl NewFeedMetadata {
             source,
             title: None,
             link: None,
+            credentials: None,
         }
     }
 
@@ -119,18 +183,43 @@ impl NewFeedMetadata {
         self.link = link.into();
         self
     }
+
+    pub fn with_credentials(mut self, credentials: impl Into<Option<FeedCredentials>>) -> Self {
+        self.credentials = credentials.into();
+        self
+    }
 }
 
 pub trait DataProvider: Unpin {
     fn get_feed(&mut self, id: FeedId) -> DbResult<Option<Feed>>;
     fn get_feed_summaries(&mut self) -> DbResult<Vec<FeedSummary>>;
+    fn get_feed_summary(&mut self, id: FeedId) -> DbResult<Option<FeedSummary>>;
     fn get_feed_opml_entries(&mut self) -> DbResult<Vec<FeedOMPLEntry>>;
-    fn get_update_sources(&mut self, update: UpdateQuery) -> DbResult<Vec<(FeedId, String)>>;
+    fn get_update_sources(&mut self, update: UpdateQuery) -> DbResult<Vec<UpdateSource>>;
+    fn get_next_update_time(
+        &mut self,
+        now: DateTime<Utc>,
+        interval: Duration,
+    ) -> DbResult<Option<DateTime<Utc>>>;
+    fn set_feed_checked(
+        &mut self,
+        feed_id: FeedId,
+        checked_at: DateTime<Utc>,
+        succeeded: bool,
+    ) -> DbResult<()>;
     fn get_new_episodes_count(
         &mut self,
         feed_ids: HashSet<FeedId>,
     ) -> DbResult<HashMap<FeedId, usize>>;
     fn rename_feed(&mut self, feed_id: FeedId, name: String) -> DbResult<()>;
+    fn get_feed_credentials(&mut self, feed_id: FeedId) -> DbResult<Option<FeedCredentials>>;
+    fn set_feed_source(&mut self, feed_id: FeedId, source: &str) -> DbResult<()>;
+    fn set_feed_policy(&mut self, feed_id: FeedId, policy: &FeedPolicy) -> DbResult<()>;
+    fn set_feed_playback_rate(&mut self, feed_id: FeedId, rate: f64) -> DbResult<()>;
+    fn set_feed_volume_offset(&mut self, feed_id: FeedId, offset: f64) -> DbResult<()>;
+    fn set_feed_intro_skip(&mut self, feed_id: FeedId, duration: Duration) -> DbResult<()>;
+    fn set_feed_outro_skip(&mut self, feed_id: FeedId, duration: Duration) -> DbResult<()>;
+    fn set_feed_skip_silence(&mut self, feed_id: FeedId, skip: Option<bool>) -> DbResult<()>;
 
     fn create_group(&mut self, name: &str) -> DbResult<Option<GroupId>>;
     fn get_group_summaries(&mut self) -> DbResult<Vec<GroupSummary>>;
@@ -139,11 +228,55 @@ pub trait DataProvider: Unpin {
     fn delete_group(&mut self, group_id: GroupId) -> DbResult<()>;
     fn set_group_position(&mut self, group_id: GroupId, position: usize) -> DbResult<()>;
 
+    fn create_saved_view(
+        &mut self,
+        name: &str,
+        filter: &EpisodesFilter,
+    ) -> DbResult<Option<SavedViewId>>;
+    fn get_saved_views(&mut self) -> DbResult<Vec<SavedViewSummary>>;
+    fn delete_saved_view(&mut self, view_id: SavedViewId) -> DbResult<()>;
+
+    fn get_queue(&mut self) -> DbResult<Vec<EpisodeSummary>>;
+    fn add_to_queue(&mut self, episode_id: EpisodeId) -> DbResult<()>;
+    fn remove_from_queue(&mut self, episode_id: EpisodeId) -> DbResult<()>;
+    fn set_queue_position(&mut self, episode_id: EpisodeId, position: usize) -> DbResult<()>;
+    fn clear_queue(&mut self) -> DbResult<()>;
+
+    fn add_listening_session(&mut self, session: &ListeningSession) -> DbResult<()>;
+    fn get_listening_stats(&mut self, since: Option<DateTime<Utc>>) -> DbResult<ListeningStats>;
+
+    fn add_bookmark(
+        &mut self,
+        episode_id: EpisodeId,
+        position: Duration,
+        note: Option<&str>,
+    ) -> DbResult<()>;
+    fn get_bookmarks(&mut self) -> DbResult<Vec<Bookmark>>;
+    fn delete_bookmark(&mut self, bookmark_id: BookmarkId) -> DbResult<()>;
+
     fn get_episode(&mut self, episode_id: EpisodeId) -> DbResult<Option<Episode>>;
     fn get_episode_playback_data(
         &mut self,
         episode_id: EpisodeId,
     ) -> DbResult<Option<EpisodePlaybackData>>;
+    fn get_pending_chapters_url(&mut self, episode_id: EpisodeId) -> DbResult<Option<String>>;
+    fn get_chapters(&mut self, episode_id: EpisodeId) -> DbResult<Vec<Chapter>>;
+    fn set_chapters(&mut self, episode_id: EpisodeId, chapters: &[Chapter]) -> DbResult<()>;
+    fn get_transcript_source(
+        &mut self,
+        episode_id: EpisodeId,
+    ) -> DbResult<Option<TranscriptSource>>;
+    fn set_transcript_cached(&mut self, episode_id: EpisodeId, url: &str) -> DbResult<()>;
+    fn get_cached_transcripts(&mut self, feed_id: FeedId) -> DbResult<Vec<EpisodeId>>;
+    fn get_pending_downloads(&mut self) -> DbResult<Vec<EpisodeId>>;
+    fn get_feed_downloads(&mut self, feed_id: FeedId) -> DbResult<Vec<(EpisodeId, Download)>>;
+    fn set_download(&mut self, episode_id: EpisodeId, download: &Download) -> DbResult<()>;
+    fn delete_downloads(
+        &mut self,
+        episode_ids: &[EpisodeId],
+    ) -> DbResult<Vec<(EpisodeId, Download)>>;
+    fn get_auto_download_episodes(&mut self, feed_id: FeedId) -> DbResult<Vec<EpisodeId>>;
+    fn get_expired_downloads(&mut self, now: DateTime<Utc>) -> DbResult<Vec<EpisodeId>>;
     fn get_episodes_list_metadata(
         &mut self,
         query: EpisodesQuery,
@@ -154,12 +287,25 @@ pub trait DataProvider: Unpin {
         range: Range<usize>,
     ) -> DbResult<Vec<EpisodeSummary>>;
     fn count_episodes(&mut self, query: EpisodesQuery) -> DbResult<usize>;
+    fn get_next_episode(
+        &mut self,
+        query: EpisodesQuery,
+        after: EpisodeId,
+    ) -> DbResult<Option<EpisodeId>>;
 
     fn create_feed_pending(&mut self, data: &NewFeedMetadata) -> DbResult<Option<FeedId>>;
     fn delete_feed(&mut self, id: FeedId) -> DbResult<()>;
     fn set_feed_status(&mut self, feed_id: FeedId, status: FeedStatus) -> DbResult<()>;
     fn set_feed_enabled(&mut self, feed_id: FeedId, enabled: bool) -> DbResult<()>;
     fn reverse_feed_order(&mut self, feed_id: FeedId) -> DbResult<()>;
+    fn get_episodes_sort_keys(
+        &mut self,
+    ) -> DbResult<HashMap<FeedView<FeedId, GroupId, SavedViewId>, EpisodesSortKey>>;
+    fn set_episodes_sort_key(
+        &mut self,
+        view: FeedView<FeedId, GroupId, SavedViewId>,
+        sort_key: EpisodesSortKey,
+    ) -> DbResult<()>;
 
     fn set_episode_status(
         &mut self,
@@ -171,9 +317,25 @@ pub trait DataProvider: Unpin {
     fn writer<'a>(&'a mut self, feed_id: FeedId) -> DbResult<Box<dyn EpisodeWriter + 'a>>;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpisodeWriteResult {
+    Inserted(EpisodeId),
+    Updated(EpisodeId),
+}
+
+impl EpisodeWriteResult {
+    pub fn id(self) -> EpisodeId {
+        match self {
+            EpisodeWriteResult::Inserted(id) | EpisodeWriteResult::Updated(id) => id,
+        }
+    }
+}
+
 pub trait EpisodeWriter {
     fn set_feed_metadata(&mut self, metadata: &FeedMetadata) -> DbResult<()>;
-    fn set_episode_metadata(&mut self, metadata: &EpisodeMetadata) -> DbResult<EpisodeId>;
-    fn delete_episode(&mut self, guid: &str) -> DbResult<()>;
+    fn set_cache_validators(&mut self, validators: &CacheValidators) -> DbResult<()>;
+    fn set_refresh_hints(&mut self, hints: &RefreshHints) -> DbResult<()>;
+    fn set_episode_metadata(&mut self, metadata: &EpisodeMetadata) -> DbResult<EpisodeWriteResult>;
+    fn delete_episode(&mut self, guid: &str) -> DbResult<Option<(EpisodeId, Option<Download>)>>;
     fn close(self: Box<Self>) -> DbResult<()>;
 }
@@ -0,0 +1,436 @@
+use crate::model::{Credentials, Download, DownloadProgress, DownloadState, EpisodeId};
+use crate::network::{HttpClient, RequestError};
+use crate::{
+    DeleteDownloadsRequest, EpisodePlaybackDataRequest, FeedUpdateRequest, Library,
+    PendingDownloadsRequest,
+};
+use actix::fut::wrap_future;
+use actix::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+const MAX_CONCURRENT_DOWNLOADS: usize = 2;
+const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Error)]
+enum DownloadError {
+    #[error("Networking error: {0}")]
+    HttpError(#[from] RequestError),
+
+    #[error("Request failed: {0}")]
+    FailedStatusCode(reqwest::StatusCode),
+
+    #[error("Cannot save the episode: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+struct QueuedDownload {
+    episode_id: EpisodeId,
+    url: String,
+    path: PathBuf,
+    credentials: Option<Credentials>,
+}
+
+pub struct DownloadManager {
+    library: Addr<Library>,
+    downloads_path: PathBuf,
+    http_client: HttpClient,
+    queue: VecDeque<QueuedDownload>,
+    active: HashMap<EpisodeId, SpawnHandle>,
+    listener: Option<Recipient<DownloadNotification>>,
+}
+
+impl DownloadManager {
+    pub fn new(library: Addr<Library>, downloads_path: PathBuf) -> Self {
+        DownloadManager {
+            library,
+            downloads_path,
+            http_client: HttpClient::default(),
+            queue: VecDeque::new(),
+            active: HashMap::new(),
+            listener: None,
+        }
+    }
+
+    pub fn set_http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    fn notify_listener(&mut self, message: DownloadNotification) {
+        if let Some(listener) = &self.listener {
+            let result = listener.do_send(message);
+            if let Err(SendError::Closed(_)) = result {
+                self.listener = None;
+            }
+        }
+    }
+
+    fn is_scheduled(&self, episode_id: EpisodeId) -> bool {
+        self.active.contains_key(&episode_id)
+            || self
+                .queue
+                .iter()
+                .any(|download| download.episode_id == episode_id)
+    }
+
+    fn set_progress(&mut self, episode_id: EpisodeId, path: &Path, progress: DownloadProgress) {
+        let download = Download {
+            path: path.to_path_buf(),
+            progress,
+        };
+        self.library
+            .do_send(FeedUpdateRequest::SetDownload(episode_id, download));
+        self.notify_listener(DownloadNotification::Progress(episode_id, progress));
+    }
+
+    fn schedule(&mut self, episode_id: EpisodeId, ctx: &mut <Self as Actor>::Context) {
+        if self.is_scheduled(episode_id) {
+            return;
+        }
+        let future = wrap_future(self.library.send(EpisodePlaybackDataRequest(episode_id))).map(
+            move |result, manager: &mut DownloadManager, ctx| {
+                let playback_data = match result {
+                    Ok(Some(playback_data)) => playback_data,
+                    Ok(None) => return,
+                    Err(error) => {
+                        log::error!(target: "actix", "{}", error);
+                        return;
+                    }
+                };
+                if playback_data.local_path.map(|path| path.is_file()) == Some(true) {
+                    log::info!("This episode has already been downloaded");
+                    return;
+                }
+                if manager.is_scheduled(episode_id) {
+                    return;
+                }
+
+                let path = manager.downloads_path.join(format!(
+                    "{}.{}",
+                    episode_id.as_i64(),
+                    media_extension(&playback_data.media_url)
+                ));
+                manager.set_progress(
+                    episode_id,
+                    &path,
+                    DownloadProgress::new(DownloadState::Queued),
+                );
+                manager.queue.push_back(QueuedDownload {
+                    episode_id,
+                    url: playback_data.media_url,
+                    path,
+                    credentials: playback_data.credentials,
+                });
+                manager.process_queue(ctx);
+            },
+        );
+        ctx.spawn(future);
+    }
+
+    fn process_queue(&mut self, ctx: &mut <Self as Actor>::Context) {
+        while self.active.len() < MAX_CONCURRENT_DOWNLOADS {
+            match self.queue.pop_front() {
+                Some(download) => self.start_download(download, ctx),
+                None => break,
+            }
+        }
+    }
+
+    fn start_download(&mut self, download: QueuedDownload, ctx: &mut <Self as Actor>::Context) {
+        let QueuedDownload {
+            episode_id,
+            url,
+            path,
+            credentials,
+        } = download;
+        self.set_progress(
+            episode_id,
+            &path,
+            DownloadProgress::new(DownloadState::InProgress),
+        );
+
+        let address = ctx.address();
+        let file_path = path.clone();
+        let client = self.http_client.clone();
+        let future = wrap_future(async move {
+            fetch_episode(
+                &client,
+                &url,
+                &file_path,
+                credentials,
+                |bytes, total_bytes| {
+                    address.do_send(ProgressReport(episode_id, bytes, total_bytes));
+                },
+            )
+            .await
+        })
+        .map(move |result, manager: &mut DownloadManager, ctx| {
+            manager.active.remove(&episode_id);
+            let progress = match result {
+                Ok(bytes) => DownloadProgress {
+                    state: DownloadState::Completed,
+                    bytes,
+                    total_bytes: Some(bytes),
+                },
+                Err(error) => {
+                    match error {
+                        DownloadError::IoError(error) => {
+                            log::error!(target: "io", "Cannot save the episode: {}", error);
+                        }
+                        error => log::error!(target: "networking", "{}", error),
+                    }
+                    remove_file(&path);
+                    DownloadProgress::new(DownloadState::Failed)
+                }
+            };
+            manager.set_progress(episode_id, &path, progress);
+            manager.process_queue(ctx);
+        });
+        let handle = ctx.spawn(future);
+        self.active.insert(episode_id, handle);
+    }
+
+    fn cancel(&mut self, episode_ids: &[EpisodeId], ctx: &mut <Self as Actor>::Context) {
+        for episode_id in episode_ids {
+            self.queue
+                .retain(|download| download.episode_id != *episode_id);
+            if let Some(handle) = self.active.remove(episode_id) {
+                ctx.cancel_future(handle);
+            }
+        }
+    }
+
+    fn remove(&mut self, episode_ids: Vec<EpisodeId>, ctx: &mut <Self as Actor>::Context) {
+        self.cancel(&episode_ids, ctx);
+        let future = wrap_future(self.library.send(DeleteDownloadsRequest(episode_ids))).map(
+            move |result, manager: &mut DownloadManager, ctx| {
+                let downloads = match result {
+                    Ok(downloads) => downloads,
+                    Err(error) => {
+                        log::error!(target: "actix", "{}", error);
+                        return;
+                    }
+                };
+                manager.remove_files(downloads);
+                manager.process_queue(ctx);
+            },
+        );
+        ctx.spawn(future);
+    }
+
+    fn discard(
+        &mut self,
+        downloads: Vec<(EpisodeId, Download)>,
+        ctx: &mut <Self as Actor>::Context,
+    ) {
+        let episode_ids: Vec<EpisodeId> = downloads.iter().map(|(id, _)| *id).collect();
+        self.cancel(&episode_ids, ctx);
+        self.remove_files(downloads);
+        self.process_queue(ctx);
+    }
+
+    fn remove_files(&mut self, downloads: Vec<(EpisodeId, Download)>) {
+        let mut reclaimed = 0;
+        for (episode_id, download) in &downloads {
+            reclaimed += remove_file(&download.path);
+            self.notify_listener(DownloadNotification::Removed(*episode_id));
+        }
+        match downloads.len() {
+            0 => log::warn!("This episode has not been downloaded"),
+            1 => log::info!(
+                "Deleted the downloaded episode, {} reclaimed",
+                format_size(reclaimed)
+            ),
+            count => log::info!(
+                "Deleted {} downloaded episodes, {} reclaimed",
+                count,
+                format_size(reclaimed)
+            ),
+        }
+    }
+}
+
+impl Actor for DownloadManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.library.do_send(FeedUpdateRequest::SetDownloadManager(
+            ctx.address().recipient(),
+        ));
+
+        let future = wrap_future(self.library.send(PendingDownloadsRequest)).map(
+            |result, manager: &mut DownloadManager, ctx| match result {
+                Ok(episode_ids) => {
+                    for episode_id in episode_ids {
+                        manager.schedule(episode_id, ctx);
+                    }
+                }
+                Err(error) => log::error!(target: "actix", "{}", error),
+            },
+        );
+        ctx.spawn(future);
+    }
+}
+
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+pub enum DownloadNotification {
+    Progress(EpisodeId, DownloadProgress),
+    Removed(EpisodeId),
+}
+
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub enum DownloadRequest {
+    Subscribe(Recipient<DownloadNotification>),
+    Download(EpisodeId),
+    Cancel(EpisodeId),
+    Delete(Vec<EpisodeId>),
+    // Downloads whose rows the library has already deleted along with their episodes.
+    Discard(Vec<(EpisodeId, Download)>),
+}
+
+impl Handler<DownloadRequest> for DownloadManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: DownloadRequest, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            DownloadRequest::Subscribe(recipient) => self.listener = Some(recipient),
+            DownloadRequest::Download(episode_id) => self.schedule(episode_id, ctx),
+            DownloadRequest::Cancel(episode_id) => self.remove(vec![episode_id], ctx),
+            DownloadRequest::Delete(episode_ids) => self.remove(episode_ids, ctx),
+            DownloadRequest::Discard(downloads) => self.discard(downloads, ctx),
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct ProgressReport(EpisodeId, u64, Option<u64>);
+
+impl Handler<ProgressReport> for DownloadManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: ProgressReport, _ctx: &mut Self::Context) -> Self::Result {
+        let ProgressReport(episode_id, bytes, total_bytes) = msg;
+        if self.active.contains_key(&episode_id) {
+            let progress = DownloadProgress {
+                state: DownloadState::InProgress,
+                bytes,
+                total_bytes,
+            };
+            self.notify_listener(DownloadNotification::Progress(episode_id, progress));
+        }
+    }
+}
+
+async fn fetch_episode(
+    client: &HttpClient,
+    url: &str,
+    path: &Path,
+    credentials: Option<Credentials>,
+    report_progress: impl Fn(u64, Option<u64>),
+) -> Result<u64, DownloadError> {
+    let client = client.state();
+    let _permit = client.acquire_fetch(url).await;
+    let mut request = client.get(url);
+    if let Some(credentials) = credentials.filter(|credentials| credentials.applies_to(url)) {
+        request = request.basic_auth(credentials.username, credentials.password);
+    }
+    let mut response = client.send(request).await?;
+    if !response.status().is_success() {
+        return Err(DownloadError::FailedStatusCode(response.status()));
+    }
+
+    let total_bytes = response.content_length();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::File::create(path)?;
+    let mut bytes = 0;
+    let mut reported_at = Instant::now();
+    while let Some(chunk) = client.read_chunk(&mut response).await? {
+        file.write_all(&chunk)?;
+        bytes += chunk.len() as u64;
+        if reported_at.elapsed() >= PROGRESS_REPORT_INTERVAL {
+            report_progress(bytes, total_bytes);
+            reported_at = Instant::now();
+        }
+    }
+    file.sync_all()?;
+    Ok(bytes)
+}
+
+// Returns the number of bytes freed by removing the file.
+fn remove_file(path: &Path) -> u64 {
+    let size = fs::metadata(path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    match fs::remove_file(path) {
+        Ok(()) => size,
+        Err(error) if error.kind() == ErrorKind::NotFound => 0,
+        Err(error) => {
+            log::error!(target: "io", "Cannot delete {:?}: {}", path, error);
+            0
+        }
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn media_extension(url: &str) -> &str {
+    let path = url.split(|ch| ch == '?' || ch == '#').next().unwrap_or(url);
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    match file_name.rsplit_once('.') {
+        Some((_, extension))
+            if (1..=4).contains(&extension.len())
+                && extension.chars().all(|ch| ch.is_ascii_alphanumeric()) =>
+        {
+            extension
+        }
+        _ => "audio",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_size, media_extension};
+
+    #[test]
+    fn media_extension_from_url() {
+        assert_eq!(media_extension("http://example.com/episode.mp3"), "mp3");
+        assert_eq!(
+            media_extension("http://example.com/ep.1.m4a?token=a.b#t=10"),
+            "m4a"
+        );
+        assert_eq!(media_extension("http://example.com/episode"), "audio");
+        assert_eq!(media_extension("http://example.com/v1.0/episode"), "audio");
+        assert_eq!(media_extension("http://example.com/file.download"), "audio");
+    }
+
+    #[test]
+    fn formats_reclaimed_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(45 * 1024 * 1024), "45.0 MB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 + 1), "3.0 GB");
+        assert_eq!(format_size(5000 * 1024 * 1024 * 1024), "5000.0 GB");
+    }
+}
@@ -0,0 +1,58 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonFeed {
+    pub(crate) title: String,
+    pub(crate) home_page_url: Option<String>,
+    pub(crate) description: Option<String>,
+    #[serde(default)]
+    pub(crate) authors: Vec<JsonFeedAuthor>,
+    pub(crate) author: Option<JsonFeedAuthor>,
+    #[serde(default)]
+    pub(crate) items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonFeedAuthor {
+    pub(crate) name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonFeedItem {
+    #[serde(deserialize_with = "deserialize_id")]
+    pub(crate) id: String,
+    pub(crate) url: Option<String>,
+    pub(crate) title: Option<String>,
+    pub(crate) content_html: Option<String>,
+    pub(crate) content_text: Option<String>,
+    pub(crate) summary: Option<String>,
+    pub(crate) date_published: Option<String>,
+    #[serde(default)]
+    pub(crate) attachments: Vec<JsonFeedAttachment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct JsonFeedAttachment {
+    pub(crate) url: String,
+    pub(crate) mime_type: Option<String>,
+    pub(crate) duration_in_seconds: Option<f64>,
+}
+
+impl JsonFeedAttachment {
+    pub(crate) fn is_audio(&self) -> bool {
+        self.mime_type
+            .as_deref()
+            .map(|mime_type| mime_type.starts_with("audio/"))
+            .unwrap_or(false)
+    }
+}
+
+// JSON Feed 1.0 allowed numeric ids, and some publishers still use them.
+fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(id) => Ok(id),
+        serde_json::Value::Number(id) => Ok(id.to_string()),
+        _ => Err(D::Error::custom("item id must be a string")),
+    }
+}
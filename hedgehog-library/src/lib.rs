@@ -1,8 +1,13 @@
 mod actor;
 mod cache;
+mod chapters;
+mod credentials;
 pub mod datasource;
+pub mod downloads;
+mod json_feed;
 pub mod metadata;
 pub mod model;
+pub mod network;
 pub mod opml;
 mod rss_client;
 pub mod search;
@@ -10,12 +15,16 @@ mod search_query;
 mod sqlite;
 pub mod status_writer;
 mod tests;
+mod transcript;
 
 pub use actor::{
-    EpisodePlaybackDataRequest, EpisodeRequest, EpisodeSummariesRequest,
-    EpisodesListMetadataRequest, FeedRequest, FeedSummariesRequest, FeedSummariesResponse,
-    FeedUpdateNotification, FeedUpdateRequest, FeedUpdateResult, Library, UpdateQuery,
+    BookmarksRequest, DeleteDownloadsRequest, EpisodeChaptersRequest, EpisodePlaybackDataRequest,
+    EpisodeRequest, EpisodeSummariesRequest, EpisodeTranscriptRequest, EpisodesListMetadataRequest,
+    FeedRequest, FeedSummariesRequest, FeedSummariesResponse, FeedUpdateNotification,
+    FeedUpdateRequest, FeedUpdateResult, Library, ListeningStatsRequest, NextEpisodeRequest,
+    PendingDownloadsRequest, QueueRequest, UpdateQuery,
 };
 pub use cache::InMemoryCache;
+pub use credentials::redact_url;
 pub use datasource::{EpisodesQuery, NewFeedMetadata, QueryError};
 pub use sqlite::SqliteDataProvider;
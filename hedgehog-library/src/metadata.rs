@@ -1,4 +1,7 @@
+use crate::json_feed::{JsonFeed, JsonFeedItem};
+use crate::model::{RefreshHints, TranscriptFormat};
 use chrono::{DateTime, Utc};
+use rss::extension::syndication::UpdatePeriod;
 use std::time::Duration;
 
 #[derive(Debug, PartialEq)]
@@ -23,6 +26,34 @@ impl<'a> FeedMetadata<'a> {
             copyright: channel.copyright.as_deref(),
         }
     }
+
+    pub fn from_atom_feed(feed: &'a atom_syndication::Feed) -> Self {
+        FeedMetadata {
+            title: feed.title.value.trim(),
+            description: feed
+                .subtitle
+                .as_ref()
+                .map(|subtitle| subtitle.value.as_str())
+                .unwrap_or(""),
+            link: find_atom_link(&feed.links, "alternate").unwrap_or(""),
+            author: feed.authors.first().map(|person| person.name.as_str()),
+            copyright: feed.rights.as_ref().map(|rights| rights.value.as_str()),
+        }
+    }
+
+    pub(crate) fn from_json_feed(feed: &'a JsonFeed) -> Self {
+        FeedMetadata {
+            title: feed.title.trim(),
+            description: feed.description.as_deref().unwrap_or(""),
+            link: feed.home_page_url.as_deref().unwrap_or(""),
+            author: feed
+                .authors
+                .first()
+                .or_else(|| feed.author.as_ref())
+                .and_then(|author| author.name.as_deref()),
+            copyright: None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -37,6 +68,8 @@ pub struct EpisodeMetadata<'a> {
     pub(crate) season_number: Option<i64>,
     pub(crate) media_url: &'a str,
     pub(crate) block: bool,
+    pub(crate) chapters_url: Option<&'a str>,
+    pub(crate) transcript: Option<(&'a str, TranscriptFormat)>,
 }
 
 impl<'a> EpisodeMetadata<'a> {
@@ -87,10 +120,208 @@ impl<'a> EpisodeMetadata<'a> {
             media_url,
             season_number,
             block,
+            chapters_url: rss_podcast_chapters_url(item),
+            transcript: rss_podcast_transcript(item),
+        })
+    }
+
+    pub fn from_atom_entry(entry: &'a atom_syndication::Entry) -> Option<Self> {
+        let media_url = find_atom_link(&entry.links, "enclosure")?;
+        let publication_date = entry.published.unwrap_or(entry.updated).with_timezone(&Utc);
+        let description = entry
+            .summary
+            .as_ref()
+            .map(|summary| summary.value.as_str())
+            .or_else(|| {
+                entry
+                    .content
+                    .as_ref()
+                    .and_then(|content| content.value.as_deref())
+            });
+        let itunes_value = |name| atom_extension_value(&entry.extensions, "itunes", name);
+
+        Some(Self {
+            title: Some(entry.title.value.trim()),
+            description,
+            link: find_atom_link(&entry.links, "alternate"),
+            guid: &entry.id,
+            duration: itunes_value("duration").and_then(parse_itunes_duration),
+            publication_date: Some(publication_date),
+            episode_number: itunes_value("episode").and_then(|episode| episode.parse().ok()),
+            media_url,
+            season_number: itunes_value("season").and_then(|season| season.parse().ok()),
+            block: itunes_value("block")
+                .map(|val| val.eq_ignore_ascii_case("Yes"))
+                .unwrap_or(false),
+            chapters_url: atom_podcast_chapters_url(entry),
+            transcript: atom_podcast_transcript(entry),
+        })
+    }
+
+    pub(crate) fn from_json_feed_item(item: &'a JsonFeedItem) -> Option<Self> {
+        let publication_date = item
+            .date_published
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|datetime| datetime.with_timezone(&Utc));
+        // Attachments without a MIME type are assumed to be audio, other types are not
+        // episodes' media.
+        let attachment = (item.attachments.iter())
+            .find(|attachment| attachment.is_audio())
+            .or_else(|| {
+                (item.attachments.iter()).find(|attachment| attachment.mime_type.is_none())
+            })?;
+        let duration = attachment
+            .duration_in_seconds
+            .filter(|duration| duration.is_finite() && *duration >= 0.0)
+            .map(Duration::from_secs_f64);
+        let description = item
+            .content_html
+            .as_deref()
+            .or_else(|| item.content_text.as_deref())
+            .or_else(|| item.summary.as_deref());
+
+        Some(Self {
+            title: item.title.as_deref().map(str::trim),
+            description,
+            link: item.url.as_deref(),
+            guid: &item.id,
+            duration,
+            publication_date,
+            episode_number: None,
+            season_number: None,
+            media_url: &attachment.url,
+            block: false,
+            chapters_url: None,
+            transcript: None,
         })
     }
 }
 
+pub(crate) fn rss_refresh_hints(channel: &rss::Channel) -> RefreshHints {
+    let ttl = channel
+        .ttl
+        .as_deref()
+        .and_then(|ttl| ttl.trim().parse::<u64>().ok())
+        .map(|minutes| Duration::from_secs(minutes * 60));
+    let update_period = channel.syndication_ext.as_ref().map(|ext| {
+        let period = match ext.period() {
+            UpdatePeriod::HOURLY => 60 * 60,
+            UpdatePeriod::DAILY => 24 * 60 * 60,
+            UpdatePeriod::WEEKLY => 7 * 24 * 60 * 60,
+            UpdatePeriod::MONTHLY => 30 * 24 * 60 * 60,
+            UpdatePeriod::YEARLY => 365 * 24 * 60 * 60,
+        };
+        Duration::from_secs(period / ext.frequency().max(1) as u64)
+    });
+
+    let mut skip_hours = 0;
+    for hour in &channel.skip_hours {
+        match hour.trim().parse::<u32>() {
+            // Some publishers use 24 for midnight
+            Ok(hour) if hour <= 24 => skip_hours |= 1 << (hour % 24),
+            _ => {}
+        }
+    }
+    let mut skip_days = 0;
+    for day in &channel.skip_days {
+        if let Ok(day) = day.trim().parse::<chrono::Weekday>() {
+            skip_days |= 1 << day.num_days_from_monday();
+        }
+    }
+
+    RefreshHints {
+        min_interval: ttl.max(update_period),
+        skip_hours,
+        skip_days,
+        complete: channel
+            .itunes_ext
+            .as_ref()
+            .and_then(|ext| ext.complete.as_deref())
+            .map(|complete| complete.trim().eq_ignore_ascii_case("yes"))
+            .unwrap_or(false),
+    }
+}
+
+fn find_atom_link<'a>(links: &'a [atom_syndication::Link], rel: &str) -> Option<&'a str> {
+    links
+        .iter()
+        .find(|link| link.rel == rel)
+        .map(|link| link.href.as_str())
+}
+
+fn atom_extension_value<'a>(
+    extensions: &'a atom_syndication::extension::ExtensionMap,
+    namespace: &str,
+    name: &str,
+) -> Option<&'a str> {
+    extensions
+        .get(namespace)
+        .and_then(|elements| elements.get(name))
+        .and_then(|elements| elements.first())
+        .and_then(|element| element.value.as_deref())
+        .map(str::trim)
+}
+
+fn is_json_chapters(mime_type: Option<&String>) -> bool {
+    mime_type
+        .map(|mime_type| mime_type.contains("json"))
+        .unwrap_or(true)
+}
+
+fn rss_podcast_chapters_url(item: &rss::Item) -> Option<&str> {
+    let chapters = item.extensions.get("podcast")?.get("chapters")?;
+    chapters
+        .iter()
+        .find(|chapters| is_json_chapters(chapters.attrs.get("type")))
+        .and_then(|chapters| chapters.attrs.get("url"))
+        .map(String::as_str)
+}
+
+fn atom_podcast_chapters_url(entry: &atom_syndication::Entry) -> Option<&str> {
+    let chapters = entry.extensions.get("podcast")?.get("chapters")?;
+    chapters
+        .iter()
+        .find(|chapters| is_json_chapters(chapters.attrs.get("type")))
+        .and_then(|chapters| chapters.attrs.get("url"))
+        .map(String::as_str)
+}
+
+fn rss_podcast_transcript(item: &rss::Item) -> Option<(&str, TranscriptFormat)> {
+    let transcripts = item.extensions.get("podcast")?.get("transcript")?;
+    select_transcript(
+        transcripts
+            .iter()
+            .map(|transcript| (transcript.attrs.get("url"), transcript.attrs.get("type"))),
+    )
+}
+
+fn atom_podcast_transcript(entry: &atom_syndication::Entry) -> Option<(&str, TranscriptFormat)> {
+    let transcripts = entry.extensions.get("podcast")?.get("transcript")?;
+    select_transcript(
+        transcripts
+            .iter()
+            .map(|transcript| (transcript.attrs.get("url"), transcript.attrs.get("type"))),
+    )
+}
+
+// Feeds often provide the same transcript in several formats, the ones with timing information
+// are preferred, and JSON is the only one that reliably identifies speakers.
+fn select_transcript<'a>(
+    transcripts: impl Iterator<Item = (Option<&'a String>, Option<&'a String>)>,
+) -> Option<(&'a str, TranscriptFormat)> {
+    transcripts
+        .filter_map(|(url, mime_type)| {
+            let format = TranscriptFormat::from_mime_type(mime_type?)?;
+            Some((url?.as_str(), format))
+        })
+        .min_by_key(|(_, format)| match format {
+            TranscriptFormat::Json => 0,
+            TranscriptFormat::WebVtt => 1,
+            TranscriptFormat::Srt => 2,
+        })
+}
+
 fn parse_itunes_duration(duration: &str) -> Option<Duration> {
     let mut seconds = 0;
     for part in duration.splitn(3, ':') {
@@ -102,7 +333,8 @@ fn parse_itunes_duration(duration: &str) -> Option<Duration> {
 
 #[cfg(test)]
 mod tests {
-    use super::{EpisodeMetadata, FeedMetadata};
+    use super::{rss_refresh_hints, EpisodeMetadata, FeedMetadata};
+    use crate::model::{RefreshHints, TranscriptFormat};
     use chrono::TimeZone;
     use pretty_assertions::assert_eq;
     use std::collections::HashMap;
@@ -156,7 +388,23 @@ mod tests {
             pub_date: Some("Wed, 01 Sep 2021 14:30:00 GMT".to_string()),
             source: Some(rss::Source::default()),
             content: Some("content".to_string()),
-            extensions: HashMap::new(),
+            extensions: HashMap::from([(
+                "podcast".to_string(),
+                HashMap::from([(
+                    "chapters".to_string(),
+                    vec![rss::extension::Extension {
+                        name: "podcast:chapters".to_string(),
+                        attrs: HashMap::from([
+                            (
+                                "url".to_string(),
+                                "http://example.com/chapters.json".to_string(),
+                            ),
+                            ("type".to_string(), "application/json+chapters".to_string()),
+                        ]),
+                        ..Default::default()
+                    }],
+                )]),
+            )]),
             itunes_ext: Some(rss::extension::itunes::ITunesItemExtension {
                 duration: Some("30:00".to_string()),
                 episode: Some("4".to_string()),
@@ -181,10 +429,60 @@ mod tests {
                 season_number: Some(2),
                 media_url: "http://example.com/episode.mp3",
                 block: true,
+                chapters_url: Some("http://example.com/chapters.json"),
+                transcript: None,
             }
         );
     }
 
+    #[test]
+    fn episode_transcript_format_preference() {
+        let transcript = |url: &str, mime_type: &str| rss::extension::Extension {
+            name: "podcast:transcript".to_string(),
+            attrs: HashMap::from([
+                ("url".to_string(), url.to_string()),
+                ("type".to_string(), mime_type.to_string()),
+            ]),
+            ..Default::default()
+        };
+        let mut item = rss::Item {
+            enclosure: Some(rss::Enclosure {
+                url: "http://example.com/episode.mp3".to_string(),
+                length: "1000".to_string(),
+                mime_type: "audio/mpeg".to_string(),
+            }),
+            extensions: HashMap::from([(
+                "podcast".to_string(),
+                HashMap::from([(
+                    "transcript".to_string(),
+                    vec![
+                        transcript("http://example.com/transcript.html", "text/html"),
+                        transcript("http://example.com/transcript.srt", "application/srt"),
+                        transcript("http://example.com/transcript.vtt", "text/vtt"),
+                    ],
+                )]),
+            )]),
+            ..Default::default()
+        };
+
+        let episode = EpisodeMetadata::from_rss_item(&item).unwrap();
+        assert_eq!(
+            episode.transcript,
+            Some((
+                "http://example.com/transcript.vtt",
+                TranscriptFormat::WebVtt
+            ))
+        );
+
+        item.extensions
+            .get_mut("podcast")
+            .and_then(|podcast| podcast.get_mut("transcript"))
+            .unwrap()
+            .retain(|transcript| transcript.attrs["type"] == "text/html");
+        let episode = EpisodeMetadata::from_rss_item(&item).unwrap();
+        assert_eq!(episode.transcript, None);
+    }
+
     #[test]
     fn episode_from_mimimal() {
         let item = rss::Item {
@@ -210,6 +508,8 @@ mod tests {
                 season_number: None,
                 media_url: "http://example.com/episode.mp3",
                 block: false,
+                chapters_url: None,
+                transcript: None,
             }
         );
     }
@@ -221,6 +521,91 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn feed_from_atom() {
+        let feed: atom_syndication::Feed = include_str!("./test_data/rss/simple-atom-feed.xml")
+            .parse()
+            .unwrap();
+
+        let metadata = FeedMetadata::from_atom_feed(&feed);
+        assert_eq!(
+            metadata,
+            FeedMetadata {
+                title: "Feed title",
+                description: "Mock feed",
+                link: "https://example.com/",
+                author: Some("Author"),
+                copyright: Some("(c) Copyright"),
+            }
+        );
+    }
+
+    #[test]
+    fn episode_from_atom_entry() {
+        let feed: atom_syndication::Feed = include_str!("./test_data/rss/simple-atom-feed.xml")
+            .parse()
+            .unwrap();
+
+        let episode = EpisodeMetadata::from_atom_entry(&feed.entries[0]).unwrap();
+        assert_eq!(
+            episode,
+            EpisodeMetadata {
+                title: Some("Episode 1"),
+                description: Some("First episode"),
+                link: Some("https://example.com/ep1.html"),
+                guid: "ep1",
+                duration: Some(Duration::from_secs(12 * 60 + 45)),
+                publication_date: Some(chrono::Utc.ymd(2021, 8, 30).and_hms(10, 0, 0)),
+                episode_number: Some(1),
+                season_number: None,
+                media_url: "https://example.com/ep1.mp3",
+                block: false,
+                chapters_url: None,
+                transcript: None,
+            }
+        );
+    }
+
+    #[test]
+    fn atom_entry_missing_enclosure() {
+        let feed: atom_syndication::Feed = include_str!("./test_data/rss/simple-atom-feed.xml")
+            .parse()
+            .unwrap();
+
+        assert!(EpisodeMetadata::from_atom_entry(&feed.entries[1]).is_none());
+    }
+
+    #[test]
+    fn episode_from_json_feed_item() {
+        let feed: crate::json_feed::JsonFeed =
+            serde_json::from_str(include_str!("./test_data/rss/simple-json-feed.json")).unwrap();
+
+        let episode = EpisodeMetadata::from_json_feed_item(&feed.items[0]).unwrap();
+        assert_eq!(
+            episode,
+            EpisodeMetadata {
+                title: Some("Episode 1"),
+                description: Some("First episode"),
+                link: Some("https://example.com/ep1.html"),
+                guid: "ep1",
+                duration: Some(Duration::from_secs(765)),
+                publication_date: Some(chrono::Utc.ymd(2021, 8, 30).and_hms(10, 0, 0)),
+                episode_number: None,
+                season_number: None,
+                media_url: "https://example.com/ep1.mp3",
+                block: false,
+                chapters_url: None,
+                transcript: None,
+            }
+        );
+        assert!(EpisodeMetadata::from_json_feed_item(&feed.items[1]).is_none());
+
+        let episode = EpisodeMetadata::from_json_feed_item(&feed.items[3]).unwrap();
+        assert_eq!(episode.publication_date, None);
+        assert_eq!(episode.media_url, "https://example.com/ep4");
+        assert!(EpisodeMetadata::from_json_feed_item(&feed.items[4]).is_none());
+    }
+
     #[test]
     fn time_from_seconds() {
         assert_eq!(
@@ -254,4 +639,37 @@ mod tests {
     fn time_from_invalid_many_components() {
         assert_eq!(super::parse_itunes_duration("10:20:30:40"), None);
     }
+
+    #[test]
+    fn refresh_hints_from_channel() {
+        let channel = rss::Channel::read_from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/"
+                xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+              <channel>
+                <title>Title</title>
+                <ttl>60</ttl>
+                <sy:updatePeriod>daily</sy:updatePeriod>
+                <sy:updateFrequency>4</sy:updateFrequency>
+                <skipHours><hour>0</hour><hour>3</hour><hour>24</hour><hour>30</hour></skipHours>
+                <skipDays><day>Saturday</day><day>Sunday</day></skipDays>
+                <itunes:complete>Yes</itunes:complete>
+              </channel>
+            </rss>"#
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            rss_refresh_hints(&channel),
+            RefreshHints {
+                min_interval: Some(Duration::from_secs(6 * 60 * 60)),
+                skip_hours: 0b1001,
+                skip_days: 0b1100000,
+                complete: true,
+            }
+        );
+
+        let channel = rss::Channel::default();
+        assert_eq!(rss_refresh_hints(&channel), RefreshHints::default());
+    }
 }
@@ -1,8 +1,9 @@
 use crate::{metadata::FeedMetadata, NewFeedMetadata};
 use actix::MessageResponse;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use rusqlite::types::{FromSql, ToSql};
 use std::fmt;
+use std::path::PathBuf;
 use std::time::Duration;
 
 macro_rules! entity_id {
@@ -35,6 +36,8 @@ macro_rules! entity_id {
 entity_id!(FeedId);
 entity_id!(EpisodeId);
 entity_id!(GroupId);
+entity_id!(SavedViewId);
+entity_id!(BookmarkId);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FeedError {
@@ -76,7 +79,7 @@ impl FeedError {
 impl fmt::Display for FeedError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            FeedError::MalformedFeed => f.write_str("The feed is not a valid RSS. Please check the source URL."),
+            FeedError::MalformedFeed => f.write_str("The feed is not a valid RSS, Atom or JSON feed. Please check the source URL."),
             FeedError::NetworkingError => f.write_str("Could not load the source URL. The problem may be with the remote server or with your internet connection."),
             FeedError::HttpError(code) => f.write_fmt(format_args!("The request to the server has failed (status code {}).", code)),
             FeedError::Unknown => f.write_str("An unknown error has occured."),
@@ -128,6 +131,34 @@ impl Identifiable for GroupSummary {
     }
 }
 
+/// The conditions that the episodes of a saved view satisfy. The publication date is limited
+/// relative to the current day so that views like "published this week" stay up to date.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpisodesFilter {
+    pub status: Option<EpisodeSummaryStatus>,
+    pub group_id: Option<GroupId>,
+    pub min_duration: Option<Duration>,
+    pub max_duration: Option<Duration>,
+    pub newer_than_days: Option<u32>,
+    pub older_than_days: Option<u32>,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SavedViewSummary {
+    pub id: SavedViewId,
+    pub name: String,
+    pub filter: EpisodesFilter,
+}
+
+impl Identifiable for SavedViewSummary {
+    type Id = SavedViewId;
+
+    fn id(&self) -> Self::Id {
+        self.id
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct FeedSummary {
     pub id: FeedId,
@@ -174,6 +205,99 @@ impl Identifiable for FeedSummary {
     }
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+// Publisher's hints about how often the feed should be fetched. Hours and days (starting with
+// Monday) listed in `<skipHours>` and `<skipDays>` are stored as bit masks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshHints {
+    pub min_interval: Option<Duration>,
+    pub skip_hours: u32,
+    pub skip_days: u8,
+    pub complete: bool,
+}
+
+// Credentials for private feeds. The password is never stored: it's printed by a user-specified
+// shell command, for example, a password manager invocation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FeedCredentials {
+    pub username: String,
+    pub password_command: Option<String>,
+}
+
+impl fmt::Debug for FeedCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeedCredentials")
+            .field("username", &self.username)
+            .field(
+                "password_command",
+                &self.password_command.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: Option<String>,
+    pub host: String,
+}
+
+impl Credentials {
+    // Credentials are only sent to the host of the feed, never to CDNs or other sites that
+    // episodes or archives are served from.
+    pub fn applies_to(&self, url: &str) -> bool {
+        reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host == self.host))
+            .unwrap_or(false)
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("host", &self.host)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateSource {
+    pub feed_id: FeedId,
+    pub source: String,
+    pub validators: Option<CacheValidators>,
+    pub archive: bool,
+}
+
+impl UpdateSource {
+    pub(crate) fn new(feed_id: FeedId, source: String) -> Self {
+        UpdateSource {
+            feed_id,
+            source,
+            validators: Some(CacheValidators::default()),
+            archive: false,
+        }
+    }
+
+    // An archive page adds older episodes to the feed, it is not the feed's source.
+    pub(crate) fn archive(feed_id: FeedId, source: String) -> Self {
+        UpdateSource {
+            feed_id,
+            source,
+            validators: None,
+            archive: true,
+        }
+    }
+}
+
 pub struct FeedOMPLEntry {
     pub title: Option<String>,
     pub feed_source: String,
@@ -190,6 +314,16 @@ pub struct Feed {
     pub copyright: Option<String>,
     pub source: String,
     pub status: FeedStatus,
+    pub failure_count: usize,
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, cmdparse::Parsable)]
+pub struct FeedPolicy {
+    #[cmd(attr(download))]
+    pub auto_download: usize,
+    #[cmd(attr(keep))]
+    pub retention_days: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, cmdparse::Parsable)]
@@ -282,6 +416,7 @@ pub struct EpisodeSummary {
     pub duration: Option<Duration>,
     pub publication_date: Option<DateTime<Utc>>,
     pub is_hidden: bool,
+    pub download: Option<DownloadProgress>,
 }
 
 impl Identifiable for EpisodeSummary {
@@ -315,6 +450,211 @@ pub struct EpisodePlaybackData {
     pub episode_title: Option<String>,
     pub feed_id: FeedId,
     pub feed_title: Option<String>,
+    pub local_path: Option<PathBuf>,
+    pub credentials: Option<Credentials>,
+    pub rate: f64,
+    pub volume_offset: f64,
+    pub skip_intro: Duration,
+    pub skip_outro: Duration,
+    pub skip_silence: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadState {
+    Queued,
+    InProgress,
+    Completed,
+    Failed,
+}
+
+impl DownloadState {
+    pub(crate) fn from_db(state: usize) -> Self {
+        match state {
+            1 => DownloadState::InProgress,
+            2 => DownloadState::Completed,
+            3 => DownloadState::Failed,
+            _ => DownloadState::Queued,
+        }
+    }
+
+    pub(crate) fn db_view(&self) -> usize {
+        match self {
+            DownloadState::Queued => 0,
+            DownloadState::InProgress => 1,
+            DownloadState::Completed => 2,
+            DownloadState::Failed => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub state: DownloadState,
+    pub bytes: u64,
+    pub total_bytes: Option<u64>,
+}
+
+impl DownloadProgress {
+    pub(crate) fn new(state: DownloadState) -> Self {
+        DownloadProgress {
+            state,
+            bytes: 0,
+            total_bytes: None,
+        }
+    }
+
+    pub fn percentage(&self) -> Option<u64> {
+        match self.total_bytes {
+            Some(total_bytes) if total_bytes > 0 => {
+                Some(self.bytes.min(total_bytes) * 100 / total_bytes)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Download {
+    pub path: PathBuf,
+    pub progress: DownloadProgress,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub start: Duration,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Srt,
+    WebVtt,
+    Json,
+}
+
+impl TranscriptFormat {
+    pub(crate) fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type.trim() {
+            "application/srt" | "application/x-subrip" | "text/srt" => Some(TranscriptFormat::Srt),
+            "text/vtt" => Some(TranscriptFormat::WebVtt),
+            "application/json" => Some(TranscriptFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_db(format: usize) -> Option<Self> {
+        match format {
+            1 => Some(TranscriptFormat::Srt),
+            2 => Some(TranscriptFormat::WebVtt),
+            3 => Some(TranscriptFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn db_view(&self) -> usize {
+        match self {
+            TranscriptFormat::Srt => 1,
+            TranscriptFormat::WebVtt => 2,
+            TranscriptFormat::Json => 3,
+        }
+    }
+
+    pub(crate) fn file_extension(&self) -> &'static str {
+        match self {
+            TranscriptFormat::Srt => "srt",
+            TranscriptFormat::WebVtt => "vtt",
+            TranscriptFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptSource {
+    pub url: String,
+    pub format: TranscriptFormat,
+    pub is_cached: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptCue {
+    pub start: Duration,
+    pub end: Duration,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+/// A span of continuous playback of an episode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListeningSession {
+    pub episode_id: EpisodeId,
+    pub start_position: Duration,
+    pub end_position: Duration,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub rate: f64,
+    /// The silence removed by the player, which is a part of the range between the positions.
+    pub skipped_silence: Duration,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListeningTime {
+    /// The amount of the episodes' audio that was played.
+    pub listened: Duration,
+    /// The time it took to play it at the playback rates used.
+    pub spent: Duration,
+}
+
+impl ListeningTime {
+    pub fn time_saved(&self) -> Duration {
+        self.listened.saturating_sub(self.spent)
+    }
+}
+
+impl std::ops::AddAssign for ListeningTime {
+    fn add_assign(&mut self, other: Self) {
+        self.listened += other.listened;
+        self.spent += other.spent;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedListeningStats {
+    /// Not set for the feeds that were deleted since.
+    pub feed_id: Option<FeedId>,
+    pub title: String,
+    pub time: ListeningTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayListeningStats {
+    pub date: NaiveDate,
+    pub time: ListeningTime,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, MessageResponse)]
+pub struct ListeningStats {
+    pub total: ListeningTime,
+    pub feeds: Vec<FeedListeningStats>,
+    pub days: Vec<DayListeningStats>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub id: BookmarkId,
+    pub episode_id: EpisodeId,
+    pub position: Duration,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub episode_title: Option<String>,
+    pub feed_title: Option<String>,
+}
+
+impl Identifiable for Bookmark {
+    type Id = BookmarkId;
+
+    fn id(&self) -> Self::Id {
+        self.id
+    }
 }
 
 #[derive(Debug, Default, Clone, MessageResponse)]
@@ -327,15 +667,55 @@ pub struct EpisodesListMetadata {
     pub reversed_order: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, cmdparse::Parsable)]
+pub enum EpisodesSortKey {
+    #[default]
+    #[cmd(rename = "date")]
+    PublicationDate,
+    Duration,
+    Title,
+    #[cmd(rename = "number")]
+    EpisodeNumber,
+    #[cmd(rename = "remaining")]
+    RemainingTime,
+    #[cmd(rename = "added")]
+    DateAdded,
+}
+
+impl EpisodesSortKey {
+    pub(crate) fn from_db(sort_key: u32) -> Self {
+        match sort_key {
+            1 => EpisodesSortKey::Duration,
+            2 => EpisodesSortKey::Title,
+            3 => EpisodesSortKey::EpisodeNumber,
+            4 => EpisodesSortKey::RemainingTime,
+            5 => EpisodesSortKey::DateAdded,
+            _ => EpisodesSortKey::PublicationDate,
+        }
+    }
+
+    pub(crate) fn db_view(&self) -> u32 {
+        match self {
+            EpisodesSortKey::PublicationDate => 0,
+            EpisodesSortKey::Duration => 1,
+            EpisodesSortKey::Title => 2,
+            EpisodesSortKey::EpisodeNumber => 3,
+            EpisodesSortKey::RemainingTime => 4,
+            EpisodesSortKey::DateAdded => 5,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum FeedView<F, G> {
+pub enum FeedView<F, G, S> {
     All,
     New,
+    Saved(S),
     Feed(F),
     Group(G),
 }
 
-impl<F, G> FeedView<F, G> {
+impl<F, G, S> FeedView<F, G, S> {
     pub fn as_feed(&self) -> Option<&F> {
         match self {
             FeedView::Feed(feed) => Some(feed),
@@ -364,32 +744,42 @@ impl<F, G> FeedView<F, G> {
         }
     }
 
-    pub fn map_feed<R>(self, f: impl FnOnce(F) -> R) -> FeedView<R, G> {
+    pub fn as_saved(&self) -> Option<&S> {
+        match self {
+            FeedView::Saved(view) => Some(view),
+            _ => None,
+        }
+    }
+
+    pub fn map_feed<R>(self, f: impl FnOnce(F) -> R) -> FeedView<R, G, S> {
         match self {
             FeedView::All => FeedView::All,
             FeedView::New => FeedView::New,
+            FeedView::Saved(view) => FeedView::Saved(view),
             FeedView::Feed(feed) => FeedView::Feed(f(feed)),
             FeedView::Group(group) => FeedView::Group(group),
         }
     }
 
-    pub fn as_ref(&self) -> FeedView<&F, &G> {
+    pub fn as_ref(&self) -> FeedView<&F, &G, &S> {
         match self {
             FeedView::All => FeedView::All,
             FeedView::New => FeedView::New,
+            FeedView::Saved(view) => FeedView::Saved(view),
             FeedView::Feed(feed) => FeedView::Feed(feed),
             FeedView::Group(group) => FeedView::Group(group),
         }
     }
 }
 
-impl<F: Identifiable, G: Identifiable> Identifiable for FeedView<F, G> {
-    type Id = FeedView<F::Id, G::Id>;
+impl<F: Identifiable, G: Identifiable, S: Identifiable> Identifiable for FeedView<F, G, S> {
+    type Id = FeedView<F::Id, G::Id, S::Id>;
 
     fn id(&self) -> Self::Id {
         match self {
             FeedView::All => FeedView::All,
             FeedView::New => FeedView::New,
+            FeedView::Saved(view) => FeedView::Saved(view.id()),
             FeedView::Feed(feed) => FeedView::Feed(feed.id()),
             FeedView::Group(group) => FeedView::Group(group.id()),
         }
@@ -0,0 +1,330 @@
+use crate::credentials::redact_error;
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkConfig {
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+    pub connect_timeout: Option<Duration>,
+    pub read_timeout: Option<Duration>,
+    pub max_fetches: usize,
+    pub max_host_fetches: Option<usize>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            proxy: None,
+            user_agent: None,
+            connect_timeout: None,
+            read_timeout: Some(Duration::from_secs(300)),
+            max_fetches: 8,
+            max_host_fetches: None,
+        }
+    }
+}
+
+pub(crate) struct ClientState {
+    config: NetworkConfig,
+    client: reqwest::Client,
+    no_redirects_client: reqwest::Client,
+    limiter: Arc<FetchLimiter>,
+}
+
+impl ClientState {
+    fn new(config: NetworkConfig, limiter: Arc<FetchLimiter>) -> Result<Self, reqwest::Error> {
+        let build_client = |redirect_policy| {
+            let mut builder = reqwest::Client::builder().redirect(redirect_policy);
+            if let Some(proxy) = &config.proxy {
+                builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+            }
+            if let Some(user_agent) = &config.user_agent {
+                builder = builder.user_agent(user_agent);
+            }
+            if let Some(connect_timeout) = config.connect_timeout {
+                builder = builder.connect_timeout(connect_timeout);
+            }
+            builder.build()
+        };
+        Ok(ClientState {
+            client: build_client(reqwest::redirect::Policy::default())?,
+            no_redirects_client: build_client(reqwest::redirect::Policy::none())?,
+            limiter,
+            config,
+        })
+    }
+
+    pub(crate) fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub(crate) fn get_no_redirects(&self, url: &str) -> reqwest::RequestBuilder {
+        self.no_redirects_client.get(url)
+    }
+
+    // The read timeout limits how long the server may stay silent, be it while responding to
+    // a request or in the middle of a body, so that large downloads are not interrupted.
+    async fn read<F: Future>(&self, future: F) -> Result<F::Output, RequestError> {
+        match self.config.read_timeout {
+            Some(read_timeout) => tokio::time::timeout(read_timeout, future)
+                .await
+                .map_err(|_| RequestError::Timeout),
+            None => Ok(future.await),
+        }
+    }
+
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, RequestError> {
+        Ok(self.read(request.send()).await?.map_err(redact_error)?)
+    }
+
+    pub(crate) async fn read_chunk(
+        &self,
+        response: &mut reqwest::Response,
+    ) -> Result<Option<Bytes>, RequestError> {
+        Ok(self.read(response.chunk()).await?.map_err(redact_error)?)
+    }
+
+    pub(crate) async fn read_body(
+        &self,
+        mut response: reqwest::Response,
+    ) -> Result<Vec<u8>, RequestError> {
+        let mut body = Vec::new();
+        while let Some(chunk) = self.read_chunk(&mut response).await? {
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    // Waits until both the overall limit of concurrent fetches and the limit for the host of
+    // `url` allow another fetch.
+    pub(crate) async fn acquire_fetch(&self, url: &str) -> FetchPermit {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+        self.limiter.acquire(host).await
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RequestError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    #[error("the server did not respond in time")]
+    Timeout,
+}
+
+// Counts fetches in progress rather than using semaphores so that the limits can be changed
+// while fetches are running: fetches started under the old limits keep counting against the new
+// ones.
+struct FetchLimiter {
+    state: Mutex<FetchLimiterState>,
+    released: Notify,
+}
+
+struct FetchLimiterState {
+    max_fetches: usize,
+    max_host_fetches: Option<usize>,
+    fetches: usize,
+    host_fetches: HashMap<String, usize>,
+}
+
+impl FetchLimiter {
+    fn new(config: &NetworkConfig) -> Self {
+        FetchLimiter {
+            state: Mutex::new(FetchLimiterState {
+                max_fetches: config.max_fetches.max(1),
+                max_host_fetches: config.max_host_fetches.map(|max| max.max(1)),
+                fetches: 0,
+                host_fetches: HashMap::new(),
+            }),
+            released: Notify::new(),
+        }
+    }
+
+    fn configure(&self, config: &NetworkConfig) {
+        let mut state = self.state.lock().unwrap();
+        state.max_fetches = config.max_fetches.max(1);
+        state.max_host_fetches = config.max_host_fetches.map(|max| max.max(1));
+        drop(state);
+        self.released.notify_waiters();
+    }
+
+    async fn acquire(self: &Arc<Self>, host: String) -> FetchPermit {
+        loop {
+            let released = self.released.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                let host_fetches = state.host_fetches.get(&host).copied().unwrap_or(0);
+                let host_allowed = state
+                    .max_host_fetches
+                    .map_or(true, |max| host_fetches < max);
+                if state.fetches < state.max_fetches && host_allowed {
+                    state.fetches += 1;
+                    state.host_fetches.insert(host.clone(), host_fetches + 1);
+                    return FetchPermit {
+                        limiter: Arc::clone(self),
+                        host,
+                    };
+                }
+            }
+            released.await;
+        }
+    }
+
+    fn release(&self, host: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.fetches -= 1;
+        if let Some(host_fetches) = state.host_fetches.get_mut(host) {
+            *host_fetches -= 1;
+            if *host_fetches == 0 {
+                state.host_fetches.remove(host);
+            }
+        }
+        drop(state);
+        self.released.notify_waiters();
+    }
+}
+
+pub(crate) struct FetchPermit {
+    limiter: Arc<FetchLimiter>,
+    host: String,
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        self.limiter.release(&self.host);
+    }
+}
+
+/// HTTP client shared by everything that accesses the network. Clones share the configuration,
+/// so reconfiguring any of them affects all the subsequent requests.
+#[derive(Clone)]
+pub struct HttpClient {
+    state: Arc<RwLock<Arc<ClientState>>>,
+}
+
+impl HttpClient {
+    pub fn new(config: NetworkConfig) -> Result<Self, reqwest::Error> {
+        let limiter = Arc::new(FetchLimiter::new(&config));
+        Ok(HttpClient {
+            state: Arc::new(RwLock::new(Arc::new(ClientState::new(config, limiter)?))),
+        })
+    }
+
+    pub fn config(&self) -> NetworkConfig {
+        self.state().config.clone()
+    }
+
+    pub fn configure(&self, config: NetworkConfig) -> Result<(), reqwest::Error> {
+        let current = self.state();
+        if current.config != config {
+            let state = ClientState::new(config, Arc::clone(&current.limiter))?;
+            state.limiter.configure(&state.config);
+            *self.state.write().unwrap() = Arc::new(state);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn state(&self) -> Arc<ClientState> {
+        Arc::clone(&self.state.read().unwrap())
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        HttpClient::new(NetworkConfig::default()).expect("Cannot initialize the HTTP client")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HttpClient, NetworkConfig};
+    use httpmock::prelude::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[actix::test]
+    async fn applies_configuration() {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/feed.xml")
+                .header("user-agent", "Hedgehog/test");
+            then.status(200);
+        });
+
+        let client = HttpClient::default();
+        let shared_client = client.clone();
+        client
+            .configure(NetworkConfig {
+                user_agent: Some("Hedgehog/test".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        let url = mock_server.url("/feed.xml");
+        let response = shared_client.state().get(&url).send().await.unwrap();
+        assert!(response.status().is_success());
+        mock.assert();
+
+        let invalid_proxy = NetworkConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(client.configure(invalid_proxy).is_err());
+        assert_eq!(
+            shared_client.config().user_agent.as_deref(),
+            Some("Hedgehog/test")
+        );
+    }
+
+    #[actix::test]
+    async fn keeps_fetch_limits_across_configuration() {
+        let client = HttpClient::new(NetworkConfig {
+            max_fetches: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let permit = client.state().acquire_fetch("http://a.example.com/").await;
+        client
+            .configure(NetworkConfig {
+                max_fetches: 1,
+                user_agent: Some("Hedgehog/test".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        let state = client.state();
+        let acquire = state.acquire_fetch("http://b.example.com/");
+        assert!(timeout(Duration::from_millis(50), acquire).await.is_err());
+
+        client
+            .configure(NetworkConfig {
+                max_fetches: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        let second_permit = timeout(
+            Duration::from_secs(1),
+            state.acquire_fetch("http://b.example.com/"),
+        )
+        .await
+        .unwrap();
+
+        drop(permit);
+        drop(second_permit);
+        let limiter = client.state().limiter.clone();
+        let limiter_state = limiter.state.lock().unwrap();
+        assert_eq!(limiter_state.fetches, 0);
+        assert!(limiter_state.host_fetches.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::credentials::redact_url;
 use crate::NewFeedMetadata;
 use crate::{datasource::DataProvider, QueryError};
 use quick_xml::escape::escape;
@@ -39,7 +40,8 @@ pub fn build_opml<W: io::Write, D: DataProvider>(write: W, data: &mut D) -> Resu
     } else {
         writer.write_event(Event::Start(BytesStart::borrowed_name(b"body")))?;
         for item in items {
-            let xml_url = escape(item.feed_source.as_bytes());
+            let feed_source = redact_url(&item.feed_source);
+            let xml_url = escape(feed_source.as_bytes());
             let title = item.title.as_ref().map(|title| escape(title.as_bytes()));
             let link = item.link.as_ref().map(|link| escape(link.as_bytes()));
 
@@ -1,19 +1,31 @@
-use crate::metadata::{EpisodeMetadata, FeedMetadata};
-use crate::model::FeedError;
+use crate::chapters::parse_chapters;
+use crate::json_feed::JsonFeed;
+use crate::metadata::{rss_refresh_hints, EpisodeMetadata, FeedMetadata};
+use crate::model::{CacheValidators, Chapter, Credentials, FeedError, RefreshHints};
+use crate::network::{HttpClient, RequestError};
+use quick_xml::events::Event;
+use reqwest::header::{self, HeaderMap};
 use std::io::{BufReader, Cursor};
-use std::time::Duration;
 use thiserror::Error;
 
+const MAX_REDIRECTS: usize = 10;
+
 #[derive(Debug, Error)]
 pub enum FetchError {
     #[error("Networking error: {0}")]
-    HttpError(#[from] reqwest::Error),
+    HttpError(#[from] RequestError),
 
     #[error("Request failed: {0}")]
     FailedStatusCode(reqwest::StatusCode),
 
     #[error("Invalid format: {0}")]
     XmlError(#[from] rss::Error),
+
+    #[error("Invalid format: {0}")]
+    AtomError(#[from] atom_syndication::Error),
+
+    #[error("Invalid format: {0}")]
+    JsonError(#[from] serde_json::Error),
 }
 
 impl FetchError {
@@ -21,27 +33,192 @@ impl FetchError {
         match self {
             FetchError::HttpError(_) => FeedError::NetworkingError,
             FetchError::FailedStatusCode(status_code) => FeedError::HttpError(*status_code),
-            FetchError::XmlError(_) => FeedError::MalformedFeed,
+            FetchError::XmlError(_) | FetchError::AtomError(_) | FetchError::JsonError(_) => {
+                FeedError::MalformedFeed
+            }
         }
     }
 }
 
-pub(crate) async fn fetch_feed(url: &str) -> Result<impl WritableFeed + 'static, FetchError> {
-    let client = reqwest::Client::new();
-    let request = client.get(url).timeout(Duration::from_secs(300));
-    let response = request.send().await?;
+pub(crate) enum FetchedFeed<F> {
+    Modified(F, CacheValidators),
+    NotModified,
+}
+
+pub(crate) struct FeedResponse<F> {
+    pub(crate) feed: FetchedFeed<F>,
+    // Set when the feed was reached only through permanent redirects, so the subscription
+    // should be moved to this URL.
+    pub(crate) moved_to: Option<String>,
+}
+
+pub(crate) async fn fetch_feed(
+    client: &HttpClient,
+    url: &str,
+    validators: Option<&CacheValidators>,
+    credentials: Option<&Credentials>,
+) -> Result<FeedResponse<Box<dyn WritableFeed>>, FetchError> {
+    let client = client.state();
+    let _permit = client.acquire_fetch(url).await;
+    let mut location = url.to_string();
+    let mut moved_to = None;
+    let mut permanently_moved = true;
+    let mut redirects = 0;
+    let response = loop {
+        // Redirects are followed manually to tell permanent ones from temporary.
+        let mut request = client.get_no_redirects(&location);
+        if let Some(credentials) =
+            credentials.filter(|credentials| credentials.applies_to(&location))
+        {
+            request = request.basic_auth(&credentials.username, credentials.password.as_ref());
+        }
+        if let Some(validators) = validators {
+            if let Some(etag) = validators.etag.as_deref() {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = validators.last_modified.as_deref() {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = client.send(request).await?;
+        let status = response.status();
+        if !status.is_redirection() || status == reqwest::StatusCode::NOT_MODIFIED {
+            break response;
+        }
+        let target = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| response.url().join(value).ok());
+        let target = match target {
+            Some(target) if redirects < MAX_REDIRECTS => target,
+            _ => return Err(FetchError::FailedStatusCode(status)),
+        };
+        redirects += 1;
+        permanently_moved &= matches!(
+            status,
+            reqwest::StatusCode::MOVED_PERMANENTLY | reqwest::StatusCode::PERMANENT_REDIRECT
+        );
+        location = target.to_string();
+        if permanently_moved {
+            moved_to = Some(location.clone());
+        }
+    };
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(FeedResponse {
+            feed: FetchedFeed::NotModified,
+            moved_to,
+        });
+    }
     if !response.status().is_success() {
         return Err(FetchError::FailedStatusCode(response.status()));
     }
 
-    let xml_text = response.bytes().await?;
-    let channel = rss::Channel::read_from(BufReader::new(Cursor::new(xml_text)))?;
-    Ok(XmlFeed {
-        channel,
-        item_index: 0,
+    let validators = read_validators(response.headers());
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = client.read_body(response).await?;
+    let feed: Box<dyn WritableFeed> = match detect_format(content_type.as_deref(), &body) {
+        FeedFormat::Rss => {
+            let channel = rss::Channel::read_from(BufReader::new(Cursor::new(body)))?;
+            Box::new(XmlFeed {
+                channel,
+                item_index: 0,
+            })
+        }
+        FeedFormat::Atom => {
+            let feed = atom_syndication::Feed::read_from(BufReader::new(Cursor::new(body)))?;
+            Box::new(AtomFeed {
+                feed,
+                entry_index: 0,
+            })
+        }
+        FeedFormat::Json => {
+            let feed = serde_json::from_slice(&body)?;
+            Box::new(JsonFeedReader {
+                feed,
+                item_index: 0,
+            })
+        }
+    };
+    Ok(FeedResponse {
+        feed: FetchedFeed::Modified(feed, validators),
+        moved_to,
     })
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+fn detect_format(content_type: Option<&str>, document: &[u8]) -> FeedFormat {
+    let mime_type = content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(str::trim);
+    if let Some("application/feed+json" | "application/json") = mime_type {
+        return FeedFormat::Json;
+    }
+    let first_char = document.iter().find(|ch| !ch.is_ascii_whitespace());
+    if first_char == Some(&b'{') {
+        return FeedFormat::Json;
+    }
+
+    let mut reader = quick_xml::Reader::from_reader(document);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf) {
+            Ok(Event::Start(start)) | Ok(Event::Empty(start)) => {
+                return match start.local_name() {
+                    b"feed" => FeedFormat::Atom,
+                    _ => FeedFormat::Rss,
+                };
+            }
+            Ok(Event::Eof) | Err(_) => return FeedFormat::Rss,
+            Ok(_) => {}
+        }
+    }
+}
+
+pub(crate) async fn fetch_chapters(
+    client: &HttpClient,
+    url: &str,
+) -> Result<Vec<Chapter>, FetchError> {
+    let body = fetch_document(client, url).await?;
+    Ok(parse_chapters(&body)?)
+}
+
+pub(crate) async fn fetch_document(client: &HttpClient, url: &str) -> Result<Vec<u8>, FetchError> {
+    let client = client.state();
+    let _permit = client.acquire_fetch(url).await;
+    let response = client.send(client.get(url)).await?;
+    if !response.status().is_success() {
+        return Err(FetchError::FailedStatusCode(response.status()));
+    }
+
+    Ok(client.read_body(response).await?)
+}
+
+fn read_validators(headers: &HeaderMap) -> CacheValidators {
+    let header_value = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    CacheValidators {
+        etag: header_value(header::ETAG),
+        last_modified: header_value(header::LAST_MODIFIED),
+    }
+}
+
 struct XmlFeed {
     channel: rss::Channel,
     item_index: usize,
@@ -50,6 +227,14 @@ struct XmlFeed {
 pub(crate) trait WritableFeed {
     fn feed_metadata(&self) -> FeedMetadata;
     fn next_episode_metadata(&mut self) -> Option<EpisodeMetadata>;
+
+    fn refresh_hints(&self) -> RefreshHints {
+        RefreshHints::default()
+    }
+
+    fn new_feed_url(&self) -> Option<&str> {
+        None
+    }
 }
 
 impl WritableFeed for XmlFeed {
@@ -57,6 +242,19 @@ impl WritableFeed for XmlFeed {
         FeedMetadata::from_rss_channel(&self.channel)
     }
 
+    fn refresh_hints(&self) -> RefreshHints {
+        rss_refresh_hints(&self.channel)
+    }
+
+    fn new_feed_url(&self) -> Option<&str> {
+        self.channel
+            .itunes_ext
+            .as_ref()
+            .and_then(|ext| ext.new_feed_url.as_deref())
+            .map(str::trim)
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+    }
+
     fn next_episode_metadata(&mut self) -> Option<EpisodeMetadata> {
         loop {
             let item = self.channel.items.get(self.item_index)?;
@@ -68,10 +266,57 @@ impl WritableFeed for XmlFeed {
     }
 }
 
+struct AtomFeed {
+    feed: atom_syndication::Feed,
+    entry_index: usize,
+}
+
+impl WritableFeed for AtomFeed {
+    fn feed_metadata(&self) -> FeedMetadata {
+        FeedMetadata::from_atom_feed(&self.feed)
+    }
+
+    fn next_episode_metadata(&mut self) -> Option<EpisodeMetadata> {
+        loop {
+            let entry = self.feed.entries.get(self.entry_index)?;
+            self.entry_index += 1;
+            if let Some(episode) = EpisodeMetadata::from_atom_entry(entry) {
+                return Some(episode);
+            }
+        }
+    }
+}
+
+struct JsonFeedReader {
+    feed: JsonFeed,
+    item_index: usize,
+}
+
+impl WritableFeed for JsonFeedReader {
+    fn feed_metadata(&self) -> FeedMetadata {
+        FeedMetadata::from_json_feed(&self.feed)
+    }
+
+    fn next_episode_metadata(&mut self) -> Option<EpisodeMetadata> {
+        loop {
+            let item = self.feed.items.get(self.item_index)?;
+            self.item_index += 1;
+            if let Some(episode) = EpisodeMetadata::from_json_feed_item(item) {
+                return Some(episode);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{fetch_feed, WritableFeed};
+    use super::{
+        detect_format, fetch_chapters, fetch_feed, FeedFormat, FetchedFeed, WritableFeed, XmlFeed,
+    };
+    use crate::model::{CacheValidators, Chapter, Credentials};
+    use crate::network::{HttpClient, RequestError};
     use httpmock::prelude::*;
+    use std::time::Duration;
 
     #[actix::test]
     async fn fetches_feed() {
@@ -83,9 +328,18 @@ mod tests {
                 .body(include_str!("./test_data/rss/simple-feed.xml"));
         });
 
-        let mut feed = fetch_feed(&mock_server.url("/podcast/feed.rss"))
-            .await
-            .unwrap();
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/feed.rss"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut feed = match fetched.feed {
+            FetchedFeed::Modified(feed, _) => feed,
+            FetchedFeed::NotModified => panic!("feed must be modified"),
+        };
 
         let feed_metadata = feed.feed_metadata();
         assert_eq!(feed_metadata.title, "Feed title");
@@ -99,4 +353,332 @@ mod tests {
         assert!(feed.next_episode_metadata().is_none());
         mock.assert();
     }
+
+    #[actix::test]
+    async fn fetches_atom_feed() {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/podcast/feed.atom");
+            then.status(200)
+                .header("content-type", "application/atom+xml")
+                .body(include_str!("./test_data/rss/simple-atom-feed.xml"));
+        });
+
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/feed.atom"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut feed = match fetched.feed {
+            FetchedFeed::Modified(feed, _) => feed,
+            FetchedFeed::NotModified => panic!("feed must be modified"),
+        };
+
+        let feed_metadata = feed.feed_metadata();
+        assert_eq!(feed_metadata.title, "Feed title");
+
+        let episode_1 = feed.next_episode_metadata().unwrap();
+        assert_eq!(episode_1.guid, "ep1");
+        assert_eq!(episode_1.media_url, "https://example.com/ep1.mp3");
+
+        let episode_2 = feed.next_episode_metadata().unwrap();
+        assert_eq!(episode_2.guid, "ep3");
+
+        assert!(feed.next_episode_metadata().is_none());
+        mock.assert();
+    }
+
+    #[actix::test]
+    async fn fetches_json_feed() {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET).path("/podcast/feed.json");
+            then.status(200)
+                .header("content-type", "application/feed+json")
+                .body(include_str!("./test_data/rss/simple-json-feed.json"));
+        });
+
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/feed.json"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut feed = match fetched.feed {
+            FetchedFeed::Modified(feed, _) => feed,
+            FetchedFeed::NotModified => panic!("feed must be modified"),
+        };
+
+        let feed_metadata = feed.feed_metadata();
+        assert_eq!(feed_metadata.title, "Feed title");
+
+        let episode_1 = feed.next_episode_metadata().unwrap();
+        assert_eq!(episode_1.guid, "ep1");
+        assert_eq!(episode_1.media_url, "https://example.com/ep1.mp3");
+
+        let episode_2 = feed.next_episode_metadata().unwrap();
+        assert_eq!(episode_2.guid, "3");
+
+        let episode_3 = feed.next_episode_metadata().unwrap();
+        assert_eq!(episode_3.guid, "ep4");
+
+        assert!(feed.next_episode_metadata().is_none());
+        mock.assert();
+    }
+
+    #[actix::test]
+    async fn fetches_chapters() {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/podcast/chapters.json");
+            then.status(200)
+                .header("content-type", "application/json+chapters")
+                .body(r#"{"version": "1.2.0", "chapters": [{"startTime": 30, "title": "Intro"}]}"#);
+        });
+
+        let chapters = fetch_chapters(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/chapters.json"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            chapters,
+            vec![Chapter {
+                start: Duration::from_secs(30),
+                title: Some("Intro".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn detects_feed_format() {
+        assert_eq!(
+            detect_format(None, include_bytes!("./test_data/rss/simple-feed.xml")),
+            FeedFormat::Rss
+        );
+        assert_eq!(
+            detect_format(None, include_bytes!("./test_data/rss/simple-atom-feed.xml")),
+            FeedFormat::Atom
+        );
+        assert_eq!(
+            detect_format(
+                None,
+                b"<atom:feed xmlns:atom=\"http://www.w3.org/2005/Atom\"/>"
+            ),
+            FeedFormat::Atom
+        );
+        assert_eq!(
+            detect_format(
+                None,
+                include_bytes!("./test_data/rss/simple-json-feed.json")
+            ),
+            FeedFormat::Json
+        );
+        assert_eq!(
+            detect_format(Some("application/feed+json; charset=utf-8"), b""),
+            FeedFormat::Json
+        );
+        assert_eq!(
+            detect_format(Some("text/xml"), b"not a feed"),
+            FeedFormat::Rss
+        );
+    }
+
+    #[actix::test]
+    async fn reads_cache_validators() {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/podcast/feed.rss");
+            then.status(200)
+                .header("etag", "\"abc\"")
+                .header("last-modified", "Wed, 01 Sep 2021 14:30:00 GMT")
+                .body(include_str!("./test_data/rss/simple-feed.xml"));
+        });
+
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/feed.rss"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let validators = match fetched.feed {
+            FetchedFeed::Modified(_, validators) => validators,
+            FetchedFeed::NotModified => panic!("feed must be modified"),
+        };
+        assert_eq!(
+            validators,
+            CacheValidators {
+                etag: Some("\"abc\"".to_string()),
+                last_modified: Some("Wed, 01 Sep 2021 14:30:00 GMT".to_string()),
+            }
+        );
+    }
+
+    #[actix::test]
+    async fn sends_cache_validators() {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/podcast/feed.rss")
+                .header("if-none-match", "\"abc\"")
+                .header("if-modified-since", "Wed, 01 Sep 2021 14:30:00 GMT");
+            then.status(304);
+        });
+
+        let validators = CacheValidators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: Some("Wed, 01 Sep 2021 14:30:00 GMT".to_string()),
+        };
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/feed.rss"),
+            Some(&validators),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(fetched.feed, FetchedFeed::NotModified));
+        mock.assert();
+    }
+
+    #[actix::test]
+    async fn sends_credentials() {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/podcast/feed.rss")
+                .header("authorization", "Basic dXNlcjpzZWNyZXQ=");
+            then.status(200)
+                .body(include_str!("./test_data/rss/simple-feed.xml"));
+        });
+
+        let credentials = Credentials {
+            username: "user".to_string(),
+            password: Some("secret".to_string()),
+            host: mock_server.host(),
+        };
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/feed.rss"),
+            None,
+            Some(&credentials),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(fetched.feed, FetchedFeed::Modified(_, _)));
+        mock.assert();
+    }
+
+    #[actix::test]
+    async fn keeps_credentials_from_other_hosts() {
+        let mock_server = MockServer::start();
+        let mock = mock_server.mock(|when, then| {
+            when.method(GET)
+                .path("/podcast/archive.rss")
+                .header_missing("authorization");
+            then.status(200)
+                .body(include_str!("./test_data/rss/simple-feed.xml"));
+        });
+
+        let credentials = Credentials {
+            username: "user".to_string(),
+            password: Some("secret".to_string()),
+            host: "feeds.example.com".to_string(),
+        };
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/podcast/archive.rss"),
+            None,
+            Some(&credentials),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(fetched.feed, FetchedFeed::Modified(_, _)));
+        mock.assert();
+    }
+
+    #[actix::test]
+    async fn reports_permanent_redirects() {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/old/feed.rss");
+            then.status(301).header("location", "/moved/feed.rss");
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/moved/feed.rss");
+            then.status(308)
+                .header("location", mock_server.url("/podcast/feed.rss"));
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/podcast/feed.rss");
+            then.status(200)
+                .body(include_str!("./test_data/rss/simple-feed.xml"));
+        });
+
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/old/feed.rss"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(fetched.feed, FetchedFeed::Modified(_, _)));
+        assert_eq!(fetched.moved_to, Some(mock_server.url("/podcast/feed.rss")));
+    }
+
+    #[actix::test]
+    async fn ignores_temporary_redirects() {
+        let mock_server = MockServer::start();
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/old/feed.rss");
+            then.status(301).header("location", "/temporary/feed.rss");
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/temporary/feed.rss");
+            then.status(302).header("location", "/podcast/feed.rss");
+        });
+        mock_server.mock(|when, then| {
+            when.method(GET).path("/podcast/feed.rss");
+            then.status(200)
+                .body(include_str!("./test_data/rss/simple-feed.xml"));
+        });
+
+        let fetched = fetch_feed(
+            &HttpClient::default(),
+            &mock_server.url("/old/feed.rss"),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(fetched.feed, FetchedFeed::Modified(_, _)));
+        assert_eq!(
+            fetched.moved_to,
+            Some(mock_server.url("/temporary/feed.rss"))
+        );
+    }
+
+    #[test]
+    fn reads_new_feed_url() {
+        let feed = XmlFeed {
+            channel: rss::Channel {
+                itunes_ext: Some(rss::extension::itunes::ITunesChannelExtension {
+                    new_feed_url: Some(" https://example.com/new.xml ".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            item_index: 0,
+        };
+        assert_eq!(feed.new_feed_url(), Some("https://example.com/new.xml"));
+    }
 }
@@ -0,0 +1,2 @@
+ALTER TABLE feeds ADD COLUMN auth_username TEXT;
+ALTER TABLE feeds ADD COLUMN auth_password_command TEXT;
@@ -0,0 +1,11 @@
+CREATE VIRTUAL TABLE episodes_search USING fts5(
+    title,
+    description,
+    feed_title,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO episodes_search (rowid, title, description, feed_title)
+SELECT episodes.id, episodes.title, episodes.description, COALESCE(feeds.title_override, feeds.title)
+FROM episodes
+JOIN feeds ON feeds.id = episodes.feed_id;
@@ -0,0 +1,12 @@
+ALTER TABLE episodes ADD COLUMN added_at INTEGER;
+UPDATE episodes SET added_at = COALESCE(
+    CAST(strftime('%s', publication_date) AS INTEGER),
+    CAST(strftime('%s', 'now') AS INTEGER)
+);
+
+CREATE TABLE episodes_sort_keys (
+    "view" INTEGER NOT NULL,
+    "view_id" INTEGER NOT NULL DEFAULT 0,
+    "sort_key" INTEGER NOT NULL,
+    PRIMARY KEY ("view", "view_id")
+);
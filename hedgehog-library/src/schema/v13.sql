@@ -0,0 +1,13 @@
+CREATE TABLE saved_views (
+    "id" INTEGER NOT NULL,
+    "name" TEXT NOT NULL UNIQUE,
+    "status" INTEGER,
+    "group_id" INTEGER,
+    "min_duration" INTEGER,
+    "max_duration" INTEGER,
+    "newer_than_days" INTEGER,
+    "older_than_days" INTEGER,
+    "text" TEXT,
+    PRIMARY KEY("id" AUTOINCREMENT),
+    FOREIGN KEY("group_id") REFERENCES groups("id") ON DELETE SET NULL
+);
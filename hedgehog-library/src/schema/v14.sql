@@ -0,0 +1,5 @@
+CREATE TABLE queue (
+    "episode_id" INTEGER NOT NULL PRIMARY KEY,
+    "position" INTEGER NOT NULL,
+    FOREIGN KEY("episode_id") REFERENCES episodes("id") ON DELETE CASCADE
+);
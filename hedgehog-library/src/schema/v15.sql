@@ -0,0 +1,16 @@
+CREATE TABLE listening_sessions (
+    "id" INTEGER NOT NULL PRIMARY KEY,
+    "episode_id" INTEGER,
+    "feed_id" INTEGER,
+    "episode_title" TEXT,
+    "feed_title" TEXT NOT NULL,
+    "start_position" INTEGER NOT NULL,
+    "end_position" INTEGER NOT NULL,
+    "started_at" INTEGER NOT NULL,
+    "ended_at" INTEGER NOT NULL,
+    "rate" REAL NOT NULL DEFAULT 1,
+    FOREIGN KEY("episode_id") REFERENCES episodes("id") ON DELETE SET NULL,
+    FOREIGN KEY("feed_id") REFERENCES feeds("id") ON DELETE SET NULL
+);
+
+CREATE INDEX listening_sessions_started_at_index ON listening_sessions ("started_at");
@@ -0,0 +1,2 @@
+ALTER TABLE feeds ADD COLUMN playback_rate REAL NOT NULL DEFAULT 1;
+ALTER TABLE feeds ADD COLUMN volume_offset REAL NOT NULL DEFAULT 0;
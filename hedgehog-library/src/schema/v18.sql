@@ -0,0 +1,2 @@
+ALTER TABLE feeds ADD COLUMN skip_silence INTEGER;
+ALTER TABLE listening_sessions ADD COLUMN skipped_silence INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+CREATE TABLE bookmarks (
+    "id" INTEGER NOT NULL PRIMARY KEY,
+    "episode_id" INTEGER NOT NULL,
+    "position" INTEGER NOT NULL,
+    "note" TEXT,
+    "created_at" INTEGER NOT NULL,
+    FOREIGN KEY("episode_id") REFERENCES episodes("id") ON DELETE CASCADE
+);
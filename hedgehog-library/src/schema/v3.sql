@@ -0,0 +1,2 @@
+ALTER TABLE feeds ADD COLUMN http_etag TEXT;
+ALTER TABLE feeds ADD COLUMN http_last_modified TEXT;
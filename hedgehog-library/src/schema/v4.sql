@@ -0,0 +1,12 @@
+ALTER TABLE episodes ADD COLUMN chapters_url TEXT;
+ALTER TABLE episodes ADD COLUMN chapters_loaded INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE chapters (
+    "id" INTEGER NOT NULL PRIMARY KEY,
+    "episode_id" INTEGER NOT NULL,
+    "start_time" INTEGER NOT NULL,
+    "title" TEXT,
+    FOREIGN KEY("episode_id") REFERENCES episodes("id") ON DELETE CASCADE
+);
+
+CREATE INDEX chapters_episode_id_index ON chapters ("episode_id");
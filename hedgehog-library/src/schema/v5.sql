@@ -0,0 +1,3 @@
+ALTER TABLE episodes ADD COLUMN transcript_url TEXT;
+ALTER TABLE episodes ADD COLUMN transcript_format INTEGER;
+ALTER TABLE episodes ADD COLUMN transcript_cached INTEGER NOT NULL DEFAULT 0;
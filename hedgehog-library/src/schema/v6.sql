@@ -0,0 +1,8 @@
+CREATE TABLE downloads (
+    "episode_id" INTEGER NOT NULL PRIMARY KEY,
+    "path" TEXT NOT NULL,
+    "state" INTEGER NOT NULL DEFAULT 0,
+    "bytes" INTEGER NOT NULL DEFAULT 0,
+    "total_bytes" INTEGER,
+    FOREIGN KEY("episode_id") REFERENCES episodes("id") ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+ALTER TABLE feeds ADD COLUMN auto_download INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE feeds ADD COLUMN retention_days INTEGER;
+ALTER TABLE episodes ADD COLUMN finished_at INTEGER;
@@ -0,0 +1,5 @@
+ALTER TABLE feeds ADD COLUMN refresh_interval INTEGER;
+ALTER TABLE feeds ADD COLUMN skip_hours INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE feeds ADD COLUMN skip_days INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE feeds ADD COLUMN complete INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE feeds ADD COLUMN checked_at INTEGER;
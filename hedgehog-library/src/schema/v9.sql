@@ -0,0 +1,4 @@
+ALTER TABLE feeds ADD COLUMN failure_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE feeds ADD COLUMN succeeded_at INTEGER;
+
+UPDATE feeds SET failure_count = 1 WHERE status = 2;
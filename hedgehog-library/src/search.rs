@@ -1,3 +1,4 @@
+use crate::network::{HttpClient, RequestError};
 pub use crate::search_query::SearchQueryParser;
 use serde::Deserialize;
 
@@ -28,7 +29,7 @@ struct SearchResponse {
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
-    Networking(#[from] reqwest::Error),
+    Networking(#[from] RequestError),
 
     #[error("Invalid response: {0}")]
     InvalidResponse(reqwest::StatusCode),
@@ -39,14 +40,14 @@ pub enum Error {
 
 pub struct SearchClient {
     endpoint_url: Option<String>,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 impl SearchClient {
     pub fn new() -> Self {
         SearchClient {
             endpoint_url: None,
-            client: reqwest::Client::new(),
+            client: HttpClient::default(),
         }
     }
 
@@ -55,23 +56,29 @@ impl SearchClient {
         self
     }
 
+    pub fn with_http_client(mut self, client: HttpClient) -> Self {
+        self.client = client;
+        self
+    }
+
     pub async fn perform(&self, terms: &str) -> Result<Vec<SearchResult>, Error> {
-        let response = self
-            .client
-            .get(
-                self.endpoint_url
-                    .as_deref()
-                    .unwrap_or("https://itunes.apple.com/search"),
-            )
-            .query(&[("term", terms), ("entity", "podcast"), ("limit", "50")])
-            .send()
-            .await?;
+        let client = self.client.state();
+        let url = self
+            .endpoint_url
+            .as_deref()
+            .unwrap_or("https://itunes.apple.com/search");
+        let _permit = client.acquire_fetch(url).await;
+        let request =
+            client
+                .get(url)
+                .query(&[("term", terms), ("entity", "podcast"), ("limit", "50")]);
+        let response = client.send(request).await?;
         if !response.status().is_success() {
             return Err(Error::InvalidResponse(response.status()));
         }
 
-        let body = response.text().await?;
-        let response: SearchResponse = serde_json::from_str(&body)?;
+        let body = client.read_body(response).await?;
+        let response: SearchResponse = serde_json::from_slice(&body)?;
         let mut results = response.results;
         results.retain(|entry| !entry.feed_url.is_empty());
         Ok(results)
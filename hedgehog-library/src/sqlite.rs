@@ -1,19 +1,24 @@
 use crate::actor::UpdateQuery;
 use crate::datasource::{
-    DataProvider, DbResult, EpisodeWriter, EpisodesQuery, NewFeedMetadata, QueryError,
+    DataProvider, DbResult, EpisodeWriteResult, EpisodeWriter, EpisodesQuery, NewFeedMetadata,
+    QueryError,
 };
 use crate::metadata::{EpisodeMetadata, FeedMetadata};
 use crate::model::{
-    Episode, EpisodeId, EpisodePlaybackData, EpisodeStatus, EpisodeSummary, EpisodeSummaryStatus,
-    EpisodesListMetadata, Feed, FeedId, FeedOMPLEntry, FeedStatus, FeedSummary, GroupId,
-    GroupSummary,
+    Bookmark, BookmarkId, CacheValidators, Chapter, DayListeningStats, Download, DownloadProgress,
+    DownloadState, Episode, EpisodeId, EpisodePlaybackData, EpisodeStatus, EpisodeSummary,
+    EpisodeSummaryStatus, EpisodesFilter, EpisodesListMetadata, EpisodesSortKey, Feed,
+    FeedCredentials, FeedId, FeedListeningStats, FeedOMPLEntry, FeedPolicy, FeedStatus,
+    FeedSummary, FeedView, GroupId, GroupSummary, ListeningSession, ListeningStats, ListeningTime,
+    RefreshHints, SavedViewId, SavedViewSummary, TranscriptFormat, TranscriptSource, UpdateSource,
 };
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
 use rusqlite::{named_params, Connection};
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use thiserror::Error;
 
@@ -32,10 +37,35 @@ pub enum ConnectionError {
 #[derive(Debug)]
 pub struct SqliteDataProvider {
     connection: Connection,
+    // The full-text search index needs FTS5, which SQLite can be built without.
+    search_index: bool,
 }
 
 impl SqliteDataProvider {
-    const CURRENT_VERSION: u32 = 2;
+    // The migration at index N brings the database from version N to N + 1.
+    const MIGRATIONS: [&'static str; 19] = [
+        include_str!("schema/init.sql"),
+        include_str!("schema/v2.sql"),
+        include_str!("schema/v3.sql"),
+        include_str!("schema/v4.sql"),
+        include_str!("schema/v5.sql"),
+        include_str!("schema/v6.sql"),
+        include_str!("schema/v7.sql"),
+        include_str!("schema/v8.sql"),
+        include_str!("schema/v9.sql"),
+        include_str!("schema/v10.sql"),
+        include_str!("schema/v11.sql"),
+        include_str!("schema/v12.sql"),
+        include_str!("schema/v13.sql"),
+        include_str!("schema/v14.sql"),
+        include_str!("schema/v15.sql"),
+        include_str!("schema/v16.sql"),
+        include_str!("schema/v17.sql"),
+        include_str!("schema/v18.sql"),
+        include_str!("schema/v19.sql"),
+    ];
+    const CURRENT_VERSION: u32 = Self::MIGRATIONS.len() as u32;
+    const SEARCH_INDEX_VERSION: u32 = 11;
 
     pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self, ConnectionError> {
         let connection = Connection::open(path)?;
@@ -48,19 +78,87 @@ impl SqliteDataProvider {
         }
 
         connection.execute("PRAGMA foreign_keys = ON", named_params! {})?;
-        if version < 1 {
-            connection.execute_batch(include_str!("schema/init.sql"))?;
+        Self::migrate(&connection, version, Self::CURRENT_VERSION)?;
+
+        // The search index is created as soon as FTS5 becomes available, until then episodes
+        // are searched with LIKE.
+        let mut search_index: bool = connection.query_row(
+            "SELECT EXISTS (SELECT true FROM sqlite_master WHERE name = 'episodes_search')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !search_index && Self::has_fts5(&connection)? {
+            let migration = Self::MIGRATIONS[Self::SEARCH_INDEX_VERSION as usize - 1];
+            connection.execute_batch(migration)?;
+            search_index = true;
         }
-        if version < 2 {
-            connection.execute_batch(include_str!("schema/v2.sql"))?;
+        Ok(SqliteDataProvider {
+            connection,
+            search_index,
+        })
+    }
+
+    fn migrate(connection: &Connection, version: u32, target: u32) -> rusqlite::Result<()> {
+        let has_fts5 = Self::has_fts5(connection)?;
+        for next_version in version + 1..=target {
+            if next_version == Self::SEARCH_INDEX_VERSION && !has_fts5 {
+                log::warn!(
+                    target: "sql",
+                    "SQLite is built without FTS5, episodes are searched without an index"
+                );
+                continue;
+            }
+            connection.execute_batch(Self::MIGRATIONS[next_version as usize - 1])?;
         }
+        connection.pragma_update(None, "user_version", target)
+    }
 
-        connection.pragma_update(None, "user_version", Self::CURRENT_VERSION)?;
-        Ok(SqliteDataProvider { connection })
+    fn has_fts5(connection: &Connection) -> rusqlite::Result<bool> {
+        connection.query_row(
+            "SELECT sqlite_compileoption_used('ENABLE_FTS5')",
+            [],
+            |row| row.get(0),
+        )
     }
 
-    fn fix_group_oredering(&mut self) -> DbResult<()> {
-        let mut statement = self.connection.prepare(
+    const FEED_SUMMARY_SELECT: &'static str =
+        "SELECT feeds.id, COALESCE(feeds.title_override, feeds.title, feeds.source),
+                feeds.title IS NOT NULL, feeds.status, feeds.error_code, COUNT(episodes.id),
+                feeds.group_id
+        FROM feeds
+        LEFT JOIN episodes ON feeds.id = episodes.feed_id AND episodes.status = 0
+        LEFT JOIN groups ON feeds.group_id = groups.id";
+
+    // Feeds that fail to update are not retried automatically for 15 minutes after the first
+    // failure, this delay doubles after each consecutive failure up to one day.
+    const FEED_RETRY_DELAY: &'static str =
+        "CASE WHEN failure_count > 0 THEN MIN(86400, 900 << MIN(failure_count - 1, 10)) ELSE 0 END";
+
+    fn feed_summary_from_row(row: &rusqlite::Row) -> rusqlite::Result<FeedSummary> {
+        Ok(FeedSummary {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            has_title: row.get(2)?,
+            status: FeedStatus::from_db(row.get(3)?, row.get(4)?),
+            new_count: row.get(5)?,
+            group_id: row.get(6)?,
+        })
+    }
+
+    fn update_source_from_row(row: &rusqlite::Row) -> rusqlite::Result<UpdateSource> {
+        Ok(UpdateSource {
+            feed_id: row.get(0)?,
+            source: row.get(1)?,
+            validators: Some(CacheValidators {
+                etag: row.get(2)?,
+                last_modified: row.get(3)?,
+            }),
+            archive: false,
+        })
+    }
+
+    fn fix_group_oredering(connection: &Connection) -> DbResult<()> {
+        let mut statement = connection.prepare(
             "WITH orders AS (SELECT id, RANK() OVER (ORDER BY ordering) AS new_ordering FROM groups)
             UPDATE groups SET ordering = new_ordering 
             FROM orders WHERE groups.id = orders.id",
@@ -68,13 +166,26 @@ impl SqliteDataProvider {
         statement.execute([])?;
         Ok(())
     }
+
+    // Positions in the queue are not updated when queued episodes are deleted, so they are
+    // renumbered before being used.
+    fn fix_queue_ordering(&mut self) -> DbResult<()> {
+        let mut statement = self.connection.prepare(
+            "WITH orders AS (SELECT episode_id, RANK() OVER (ORDER BY position) AS new_position FROM queue)
+            UPDATE queue SET position = new_position
+            FROM orders WHERE queue.episode_id = orders.episode_id",
+        )?;
+        statement.execute([])?;
+        Ok(())
+    }
 }
 
 impl DataProvider for SqliteDataProvider {
     fn get_feed(&mut self, id: FeedId) -> DbResult<Option<crate::model::Feed>> {
         let mut statement = self.connection.prepare(
             "SELECT id, COALESCE(title_override, title), title_override IS NOT NULL, description, 
-                    link, author, copyright, source, status, error_code 
+                    link, author, copyright, source, status, error_code, failure_count,
+                    succeeded_at
             FROM feeds
             WHERE id = ?1
         ",
@@ -90,6 +201,10 @@ impl DataProvider for SqliteDataProvider {
                 copyright: row.get(6)?,
                 source: row.get(7)?,
                 status: FeedStatus::from_db(row.get(8)?, row.get(9)?),
+                failure_count: row.get(10)?,
+                last_success: row
+                    .get::<_, Option<i64>>(11)?
+                    .map(|timestamp| Utc.timestamp(timestamp, 0)),
             })
         });
         match result {
@@ -100,29 +215,28 @@ impl DataProvider for SqliteDataProvider {
     }
 
     fn get_feed_summaries(&mut self) -> DbResult<Vec<FeedSummary>> {
-        let mut select = self.connection.prepare(
-            "SELECT feeds.id, COALESCE(feeds.title_override, feeds.title, feeds.source), 
-                    feeds.title IS NOT NULL, feeds.status, feeds.error_code, COUNT(episodes.id),
-                    feeds.group_id
-            FROM feeds 
-            LEFT JOIN episodes ON feeds.id = episodes.feed_id AND episodes.status = 0
-            LEFT JOIN groups ON feeds.group_id = groups.id
-            GROUP BY feeds.id
+        let mut select = self.connection.prepare(&format!(
+            "{} GROUP BY feeds.id
             ORDER BY groups.ordering, COALESCE(feeds.title_override, feeds.title), feeds.source",
-        )?;
-        let rows = select.query_map([], |row| {
-            Ok(FeedSummary {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                has_title: row.get(2)?,
-                status: FeedStatus::from_db(row.get(3)?, row.get(4)?),
-                new_count: row.get(5)?,
-                group_id: row.get(6)?,
-            })
-        })?;
+            Self::FEED_SUMMARY_SELECT
+        ))?;
+        let rows = select.query_map([], Self::feed_summary_from_row)?;
         Ok(collect_results(rows)?)
     }
 
+    fn get_feed_summary(&mut self, id: FeedId) -> DbResult<Option<FeedSummary>> {
+        let mut select = self.connection.prepare(&format!(
+            "{} WHERE feeds.id = :id GROUP BY feeds.id",
+            Self::FEED_SUMMARY_SELECT
+        ))?;
+        let result = select.query_row(named_params! {":id": id}, Self::feed_summary_from_row);
+        match result {
+            Ok(summary) => Ok(Some(summary)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
     fn get_feed_opml_entries(&mut self) -> DbResult<Vec<crate::model::FeedOMPLEntry>> {
         let mut select = self
             .connection
@@ -137,36 +251,115 @@ impl DataProvider for SqliteDataProvider {
         Ok(collect_results(rows)?)
     }
 
-    fn get_update_sources(&mut self, query: UpdateQuery) -> DbResult<Vec<(FeedId, String)>> {
+    fn get_update_sources(&mut self, query: UpdateQuery) -> DbResult<Vec<UpdateSource>> {
+        const SELECT: &str = "SELECT id, source, http_etag, http_last_modified FROM feeds";
         match query {
             UpdateQuery::Single(feed_id) => {
                 let mut statement = self
                     .connection
-                    .prepare("SELECT source FROM feeds WHERE id = :id LIMIT 1")?;
-                let source =
-                    statement.query_row(named_params! {":id": feed_id}, |row| row.get(0))?;
-                Ok(vec![(feed_id, source)])
+                    .prepare(&format!("{} WHERE id = :id LIMIT 1", SELECT))?;
+                let source = statement
+                    .query_row(named_params! {":id": feed_id}, Self::update_source_from_row)?;
+                Ok(vec![source])
             }
             UpdateQuery::All => {
+                let mut statement = self.connection.prepare(&format!(
+                    "{} WHERE enabled AND COALESCE(checked_at + {}, 0) <= :now",
+                    SELECT,
+                    Self::FEED_RETRY_DELAY
+                ))?;
+                let rows = statement.query_map(
+                    named_params! {":now": Utc::now().timestamp()},
+                    Self::update_source_from_row,
+                )?;
+                Ok(collect_results(rows)?)
+            }
+            UpdateQuery::Failed => {
                 let mut statement = self
                     .connection
-                    .prepare("SELECT id, source FROM feeds WHERE enabled")?;
-                let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                    .prepare(&format!("{} WHERE enabled AND failure_count > 0", SELECT))?;
+                let rows = statement.query_map([], Self::update_source_from_row)?;
                 Ok(collect_results(rows)?)
             }
             UpdateQuery::Pending => {
                 let mut statement = self
                     .connection
-                    .prepare("SELECT id, source FROM feeds WHERE enabled AND status = :status")?;
+                    .prepare(&format!("{} WHERE enabled AND status = :status", SELECT))?;
                 let rows = statement.query_map(
                     named_params! {":status": FeedStatus::Pending.db_view().0},
-                    |row| Ok((row.get(0)?, row.get(1)?)),
+                    Self::update_source_from_row,
+                )?;
+                Ok(collect_results(rows)?)
+            }
+            UpdateQuery::Due(now, interval) => {
+                let mut statement = self.connection.prepare(&format!(
+                    "{} WHERE enabled AND NOT complete
+                        AND COALESCE(
+                            checked_at + MAX(:interval, COALESCE(refresh_interval, 0), {}), 0
+                        ) <= :now
+                        AND (skip_hours >> :hour) & 1 = 0 AND (skip_days >> :weekday) & 1 = 0",
+                    SELECT,
+                    Self::FEED_RETRY_DELAY
+                ))?;
+                let rows = statement.query_map(
+                    named_params! {
+                        ":interval": interval.as_secs(),
+                        ":now": now.timestamp(),
+                        ":hour": now.hour(),
+                        ":weekday": now.weekday().num_days_from_monday(),
+                    },
+                    Self::update_source_from_row,
                 )?;
                 Ok(collect_results(rows)?)
             }
         }
     }
 
+    fn get_next_update_time(
+        &mut self,
+        now: DateTime<Utc>,
+        interval: Duration,
+    ) -> DbResult<Option<DateTime<Utc>>> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT COALESCE(
+                checked_at + MAX(:interval, COALESCE(refresh_interval, 0), {}), 0
+            ), skip_hours, skip_days
+            FROM feeds WHERE enabled AND NOT complete",
+            Self::FEED_RETRY_DELAY
+        ))?;
+        let rows = statement.query_map(named_params! {":interval": interval.as_secs()}, |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        let next_update = collect_results(rows)?
+            .into_iter()
+            .filter_map(|(timestamp, skip_hours, skip_days)| {
+                let due = Utc.timestamp(timestamp, 0).max(now);
+                first_unskipped_hour(due, skip_hours, skip_days)
+            })
+            .min();
+        Ok(next_update)
+    }
+
+    fn set_feed_checked(
+        &mut self,
+        feed_id: FeedId,
+        checked_at: DateTime<Utc>,
+        succeeded: bool,
+    ) -> DbResult<()> {
+        let mut statement = self.connection.prepare(
+            "UPDATE feeds SET checked_at = :checked_at,
+                succeeded_at = CASE WHEN :succeeded THEN :checked_at ELSE succeeded_at END,
+                failure_count = CASE WHEN :succeeded THEN 0 ELSE failure_count + 1 END
+            WHERE id = :id",
+        )?;
+        statement.execute(named_params! {
+            ":checked_at": checked_at.timestamp(),
+            ":succeeded": succeeded,
+            ":id": feed_id,
+        })?;
+        Ok(())
+    }
+
     fn get_new_episodes_count(
         &mut self,
         feed_ids: HashSet<FeedId>,
@@ -202,6 +395,94 @@ impl DataProvider for SqliteDataProvider {
             .connection
             .prepare("UPDATE feeds SET title_override = :name WHERE id = :feed_id")?;
         statement.execute(named_params! {":name": name, ":feed_id": feed_id})?;
+        if self.search_index {
+            update_search_feed_title(&self.connection, feed_id)?;
+        }
+        Ok(())
+    }
+
+    fn get_feed_credentials(&mut self, feed_id: FeedId) -> DbResult<Option<FeedCredentials>> {
+        let mut statement = self.connection.prepare(
+            "SELECT auth_username, auth_password_command FROM feeds
+            WHERE id = :feed_id AND auth_username IS NOT NULL",
+        )?;
+        let result = statement.query_row(named_params! {":feed_id": feed_id}, |row| {
+            Ok(FeedCredentials {
+                username: row.get(0)?,
+                password_command: row.get(1)?,
+            })
+        });
+        match result {
+            Ok(credentials) => Ok(Some(credentials)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn set_feed_source(&mut self, feed_id: FeedId, source: &str) -> DbResult<()> {
+        // Cache validators issued for the old location are meaningless for the new one.
+        let mut statement = self.connection.prepare(
+            "UPDATE feeds SET source = :source, http_etag = NULL, http_last_modified = NULL
+            WHERE id = :feed_id",
+        )?;
+        statement.execute(named_params! {":source": source, ":feed_id": feed_id})?;
+        Ok(())
+    }
+
+    fn set_feed_policy(&mut self, feed_id: FeedId, policy: &FeedPolicy) -> DbResult<()> {
+        let mut statement = self.connection.prepare(
+            "UPDATE feeds SET auto_download = :auto_download, retention_days = :retention_days
+            WHERE id = :feed_id",
+        )?;
+        statement.execute(named_params! {
+            ":auto_download": policy.auto_download,
+            ":retention_days": policy.retention_days,
+            ":feed_id": feed_id,
+        })?;
+        Ok(())
+    }
+
+    fn set_feed_playback_rate(&mut self, feed_id: FeedId, rate: f64) -> DbResult<()> {
+        let mut statement = self
+            .connection
+            .prepare("UPDATE feeds SET playback_rate = :rate WHERE id = :feed_id")?;
+        statement.execute(named_params! {":rate": rate, ":feed_id": feed_id})?;
+        Ok(())
+    }
+
+    fn set_feed_volume_offset(&mut self, feed_id: FeedId, offset: f64) -> DbResult<()> {
+        let mut statement = self
+            .connection
+            .prepare("UPDATE feeds SET volume_offset = :offset WHERE id = :feed_id")?;
+        statement.execute(named_params! {":offset": offset, ":feed_id": feed_id})?;
+        Ok(())
+    }
+
+    fn set_feed_intro_skip(&mut self, feed_id: FeedId, duration: Duration) -> DbResult<()> {
+        let mut statement = self
+            .connection
+            .prepare("UPDATE feeds SET skip_intro = :duration WHERE id = :feed_id")?;
+        statement.execute(
+            named_params! {":duration": duration.as_nanos() as u64, ":feed_id": feed_id},
+        )?;
+        Ok(())
+    }
+
+    fn set_feed_outro_skip(&mut self, feed_id: FeedId, duration: Duration) -> DbResult<()> {
+        let mut statement = self
+            .connection
+            .prepare("UPDATE feeds SET skip_outro = :duration WHERE id = :feed_id")?;
+        statement.execute(
+            named_params! {":duration": duration.as_nanos() as u64, ":feed_id": feed_id},
+        )?;
+        Ok(())
+    }
+
+    fn set_feed_skip_silence(&mut self, feed_id: FeedId, skip: Option<bool>) -> DbResult<()> {
+        let mut statement = self
+            .connection
+            .prepare("UPDATE feeds SET skip_silence = :skip WHERE id = :feed_id")?;
+        statement.execute(named_params! {":skip": skip, ":feed_id": feed_id})?;
         Ok(())
     }
 
@@ -252,13 +533,19 @@ impl DataProvider for SqliteDataProvider {
     }
 
     fn delete_group(&mut self, group_id: GroupId) -> DbResult<()> {
+        // Saved views limited to the group are kept, they include the episodes of all feeds
+        // from then on.
+        let transaction = self.connection.transaction()?;
         {
-            let mut statement = self
-                .connection
-                .prepare("DELETE FROM groups WHERE id = :group_id")?;
+            let mut statement = transaction.prepare("DELETE FROM groups WHERE id = :group_id")?;
             statement.execute(named_params! { ":group_id": group_id })?;
+            let mut statement = transaction.prepare(
+                "DELETE FROM episodes_sort_keys WHERE view = :view AND view_id = :group_id",
+            )?;
+            statement.execute(named_params! { ":view": GROUP_SORT_VIEW, ":group_id": group_id })?;
         }
-        self.fix_group_oredering()?;
+        Self::fix_group_oredering(&transaction)?;
+        transaction.commit()?;
         Ok(())
     }
 
@@ -289,7 +576,286 @@ impl DataProvider for SqliteDataProvider {
         self.connection.prepare(sql)?.execute(
             named_params! {":position": position, ":current_position": current_position},
         )?;
-        self.fix_group_oredering()?;
+        Self::fix_group_oredering(&self.connection)?;
+        Ok(())
+    }
+
+    fn create_saved_view(
+        &mut self,
+        name: &str,
+        filter: &EpisodesFilter,
+    ) -> DbResult<Option<SavedViewId>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT 1 FROM saved_views WHERE name = :name")?;
+        let already_exist = statement
+            .query(named_params! {":name": name})?
+            .next()?
+            .is_some();
+        if already_exist {
+            return Ok(None);
+        }
+
+        let mut statement = self.connection.prepare(
+            "INSERT INTO saved_views (name, status, group_id, min_duration, max_duration, newer_than_days, older_than_days, text)
+            VALUES (:name, :status, :group_id, :min_duration, :max_duration, :newer_than_days, :older_than_days, :text)",
+        )?;
+        let result = statement.insert(named_params! {
+            ":name": name,
+            ":status": filter.status.map(|status| status.db_view()),
+            ":group_id": filter.group_id,
+            ":min_duration": filter.min_duration.map(|duration| duration.as_nanos() as u64),
+            ":max_duration": filter.max_duration.map(|duration| duration.as_nanos() as u64),
+            ":newer_than_days": filter.newer_than_days,
+            ":older_than_days": filter.older_than_days,
+            ":text": filter.text,
+        })?;
+        Ok(Some(SavedViewId(result)))
+    }
+
+    fn get_saved_views(&mut self) -> DbResult<Vec<SavedViewSummary>> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, name, status, group_id, min_duration, max_duration, newer_than_days, older_than_days, text
+            FROM saved_views ORDER BY id",
+        )?;
+        let items = statement.query_map([], |row| {
+            Ok(SavedViewSummary {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                filter: EpisodesFilter {
+                    status: row
+                        .get::<_, Option<usize>>(2)?
+                        .map(EpisodeSummaryStatus::from_db),
+                    group_id: row.get(3)?,
+                    min_duration: row.get::<_, Option<u64>>(4)?.map(Duration::from_nanos),
+                    max_duration: row.get::<_, Option<u64>>(5)?.map(Duration::from_nanos),
+                    newer_than_days: row.get(6)?,
+                    older_than_days: row.get(7)?,
+                    text: row.get(8)?,
+                },
+            })
+        })?;
+        Ok(collect_results(items)?)
+    }
+
+    fn delete_saved_view(&mut self, view_id: SavedViewId) -> DbResult<()> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement =
+                transaction.prepare("DELETE FROM saved_views WHERE id = :view_id")?;
+            statement.execute(named_params! { ":view_id": view_id })?;
+            let mut statement = transaction.prepare(
+                "DELETE FROM episodes_sort_keys WHERE view = :view AND view_id = :view_id",
+            )?;
+            statement.execute(named_params! { ":view": SAVED_SORT_VIEW, ":view_id": view_id })?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn get_queue(&mut self) -> DbResult<Vec<EpisodeSummary>> {
+        let mut statement = self.connection.prepare(
+            "SELECT ep.id, ep.feed_id, ep.episode_number, ep.season_number, ep.title, ep.status, ep.duration, ep.publication_date, ep.hidden, dl.state, dl.bytes, dl.total_bytes, feeds.title
+            FROM queue
+            JOIN episodes AS ep ON ep.id = queue.episode_id
+            JOIN feeds ON feeds.id == ep.feed_id
+            LEFT JOIN downloads AS dl ON dl.episode_id = ep.id
+            ORDER BY queue.position",
+        )?;
+        let rows = statement.query_map([], |row| read_episode_summary(row, true))?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn add_to_queue(&mut self, episode_id: EpisodeId) -> DbResult<()> {
+        let mut statement = self.connection.prepare(
+            "INSERT OR IGNORE INTO queue (episode_id, position)
+            VALUES (:episode_id, COALESCE((SELECT MAX(position) + 1 FROM queue), 1))",
+        )?;
+        statement.execute(named_params! {":episode_id": episode_id})?;
+        Ok(())
+    }
+
+    fn remove_from_queue(&mut self, episode_id: EpisodeId) -> DbResult<()> {
+        let mut statement = self
+            .connection
+            .prepare("DELETE FROM queue WHERE episode_id = :episode_id")?;
+        statement.execute(named_params! {":episode_id": episode_id})?;
+        Ok(())
+    }
+
+    fn set_queue_position(&mut self, episode_id: EpisodeId, position: usize) -> DbResult<()> {
+        self.fix_queue_ordering()?;
+        let current_position: usize = match self
+            .connection
+            .prepare("SELECT position FROM queue WHERE episode_id = :episode_id")?
+            .query_row(named_params! {":episode_id": episode_id}, |row| row.get(0))
+        {
+            Ok(current_position) => current_position,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        let queue_length: usize = self
+            .connection
+            .prepare("SELECT COUNT(*) FROM queue")?
+            .query_row([], |row| row.get(0))?;
+        let position = position.clamp(1, queue_length);
+
+        let sql = match current_position.cmp(&position) {
+            Ordering::Less => {
+                "UPDATE queue SET position = CASE
+                    WHEN position < :current_position OR position > :position THEN position
+                    WHEN position = :current_position THEN :position
+                    ELSE position - 1
+                END"
+            }
+            Ordering::Greater => {
+                "UPDATE queue SET position = CASE
+                    WHEN position < :position OR position > :current_position THEN position
+                    WHEN position = :current_position THEN :position
+                    ELSE position + 1
+                END"
+            }
+            Ordering::Equal => return Ok(()),
+        };
+        self.connection.prepare(sql)?.execute(
+            named_params! {":position": position, ":current_position": current_position},
+        )?;
+        Ok(())
+    }
+
+    fn clear_queue(&mut self) -> DbResult<()> {
+        let mut statement = self.connection.prepare("DELETE FROM queue")?;
+        statement.execute([])?;
+        Ok(())
+    }
+
+    fn add_listening_session(&mut self, session: &ListeningSession) -> DbResult<()> {
+        // The titles are kept with the session so that it still counts once the episode or its
+        // feed is deleted.
+        let mut statement = self.connection.prepare(
+            "INSERT INTO listening_sessions (episode_id, feed_id, episode_title, feed_title, start_position, end_position, started_at, ended_at, rate, skipped_silence)
+            SELECT ep.id, feeds.id, ep.title, COALESCE(feeds.title_override, feeds.title, feeds.source),
+                :start_position, :end_position, :started_at, :ended_at, :rate, :skipped_silence
+            FROM episodes AS ep
+            JOIN feeds ON feeds.id = ep.feed_id
+            WHERE ep.id = :episode_id",
+        )?;
+        statement.execute(named_params! {
+            ":episode_id": session.episode_id,
+            ":start_position": session.start_position.as_nanos() as u64,
+            ":end_position": session.end_position.as_nanos() as u64,
+            ":started_at": session.started_at.timestamp(),
+            ":ended_at": session.ended_at.timestamp(),
+            ":rate": session.rate,
+            ":skipped_silence": session.skipped_silence.as_nanos() as u64,
+        })?;
+        Ok(())
+    }
+
+    // The time spent listening is derived from the playback rate and the skipped silence rather
+    // than from the wall-clock time of sessions, so that buffering does not count against the
+    // time saved.
+    fn get_listening_stats(&mut self, since: Option<DateTime<Utc>>) -> DbResult<ListeningStats> {
+        let since = since.map_or(0, |since| since.timestamp());
+        let read_time = |row: &rusqlite::Row, index: usize| -> rusqlite::Result<ListeningTime> {
+            Ok(ListeningTime {
+                listened: Duration::from_nanos(row.get::<_, Option<u64>>(index)?.unwrap_or(0)),
+                spent: Duration::from_nanos(
+                    row.get::<_, Option<f64>>(index + 1)?.unwrap_or(0.0) as u64
+                ),
+            })
+        };
+
+        let mut statement = self.connection.prepare(
+            "SELECT s.feed_id,
+                COALESCE(feeds.title_override, feeds.title, feeds.source, s.feed_title),
+                SUM(s.end_position - s.start_position),
+                SUM((s.end_position - s.start_position - s.skipped_silence) / s.rate)
+            FROM listening_sessions AS s
+            LEFT JOIN feeds ON feeds.id = s.feed_id
+            WHERE s.started_at >= :since
+            GROUP BY s.feed_id, CASE WHEN s.feed_id IS NULL THEN s.feed_title END
+            ORDER BY 3 DESC",
+        )?;
+        let rows = statement.query_map(named_params! {":since": since}, |row| {
+            Ok(FeedListeningStats {
+                feed_id: row.get(0)?,
+                title: row.get(1)?,
+                time: read_time(row, 2)?,
+            })
+        })?;
+        let feeds = collect_results(rows)?;
+
+        let mut statement = self.connection.prepare(
+            "SELECT date(s.started_at, 'unixepoch', 'localtime'),
+                SUM(s.end_position - s.start_position),
+                SUM((s.end_position - s.start_position - s.skipped_silence) / s.rate)
+            FROM listening_sessions AS s
+            WHERE s.started_at >= :since
+            GROUP BY 1
+            ORDER BY 1 DESC",
+        )?;
+        let rows = statement.query_map(named_params! {":since": since}, |row| {
+            Ok(DayListeningStats {
+                date: row.get(0)?,
+                time: read_time(row, 1)?,
+            })
+        })?;
+        let days: Vec<DayListeningStats> = collect_results(rows)?;
+
+        let mut total = ListeningTime::default();
+        for day in &days {
+            total += day.time;
+        }
+        Ok(ListeningStats { total, feeds, days })
+    }
+
+    fn add_bookmark(
+        &mut self,
+        episode_id: EpisodeId,
+        position: Duration,
+        note: Option<&str>,
+    ) -> DbResult<()> {
+        let mut statement = self.connection.prepare(
+            "INSERT INTO bookmarks (episode_id, position, note, created_at)
+            VALUES (:episode_id, :position, :note, :created_at)",
+        )?;
+        statement.execute(named_params! {
+            ":episode_id": episode_id,
+            ":position": position.as_nanos() as u64,
+            ":note": note,
+            ":created_at": Utc::now().timestamp(),
+        })?;
+        Ok(())
+    }
+
+    fn get_bookmarks(&mut self) -> DbResult<Vec<Bookmark>> {
+        let mut statement = self.connection.prepare(
+            "SELECT b.id, b.episode_id, b.position, b.note, b.created_at, ep.title, COALESCE(feeds.title_override, feeds.title)
+            FROM bookmarks AS b
+            JOIN episodes AS ep ON ep.id = b.episode_id
+            JOIN feeds ON feeds.id = ep.feed_id
+            ORDER BY b.created_at DESC, b.id DESC",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok(Bookmark {
+                id: row.get(0)?,
+                episode_id: row.get(1)?,
+                position: Duration::from_nanos(row.get(2)?),
+                note: row.get(3)?,
+                created_at: Utc.timestamp(row.get(4)?, 0),
+                episode_title: row.get(5)?,
+                feed_title: row.get(6)?,
+            })
+        })?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn delete_bookmark(&mut self, bookmark_id: BookmarkId) -> DbResult<()> {
+        let mut statement = self
+            .connection
+            .prepare("DELETE FROM bookmarks WHERE id = :bookmark_id")?;
+        statement.execute(named_params! {":bookmark_id": bookmark_id})?;
         Ok(())
     }
 
@@ -325,10 +891,13 @@ impl DataProvider for SqliteDataProvider {
         let mut statement = self
             .connection
             .prepare(
-                "SELECT episodes.media_url, episodes.position, episodes.duration, episodes.title, feeds.id, feeds.title
+                "SELECT episodes.media_url, episodes.position, episodes.duration, episodes.title, feeds.id, feeds.title, downloads.path, feeds.playback_rate, feeds.volume_offset, feeds.skip_intro, feeds.skip_outro, feeds.skip_silence
                 FROM episodes JOIN feeds ON feeds.id = episodes.feed_id
+                LEFT JOIN downloads ON downloads.episode_id = episodes.id AND downloads.state = :completed
                 WHERE episodes.id = :id LIMIT 1")?;
-        let result = statement.query_row(named_params! {":id": episode_id}, |row| {
+        let params =
+            named_params! {":id": episode_id, ":completed": DownloadState::Completed.db_view()};
+        let result = statement.query_row(params, |row| {
             Ok(EpisodePlaybackData {
                 id: episode_id,
                 media_url: row.get(0)?,
@@ -337,6 +906,13 @@ impl DataProvider for SqliteDataProvider {
                 episode_title: row.get(3)?,
                 feed_id: row.get(4)?,
                 feed_title: row.get(5)?,
+                local_path: row.get::<_, Option<String>>(6)?.map(PathBuf::from),
+                credentials: None,
+                rate: row.get(7)?,
+                volume_offset: row.get(8)?,
+                skip_intro: Duration::from_nanos(row.get(9)?),
+                skip_outro: Duration::from_nanos(row.get(10)?),
+                skip_silence: row.get(11)?,
             })
         });
         match result {
@@ -346,31 +922,240 @@ impl DataProvider for SqliteDataProvider {
         }
     }
 
-    fn get_episodes_list_metadata(
-        &mut self,
-        query: EpisodesQuery,
-    ) -> DbResult<EpisodesListMetadata> {
-        let mut sql =
-            "SELECT COUNT(ep.id), MAX(ep.season_number), MAX(ep.episode_number), MAX(ep.duration),
-                    SUM(CASE WHEN ep.publication_date IS NOT NULL THEN 1 ELSE 0 END), feeds.reversed
-            FROM episodes AS ep
-            JOIN feeds ON ep.feed_id = feeds.id
-            LEFT JOIN groups ON feeds.group_id = groups.id
-            "
-            .to_string();
-        query.build_where_clause(&mut sql);
-        let mut statement = self.connection.prepare(&sql)?;
+    fn get_pending_chapters_url(&mut self, episode_id: EpisodeId) -> DbResult<Option<String>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT chapters_url FROM episodes WHERE id = :id AND chapters_loaded = 0")?;
+        let result = statement.query_row(named_params! {":id": episode_id}, |row| row.get(0));
+        match result {
+            Ok(url) => Ok(url),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
 
-        let feed_specific = query.feed_id.is_some();
-        let where_params = EpisodeQueryParams::from_query(query);
-        let params = where_params.as_sql_params();
-        statement
-            .query_row(&*params, |row| {
-                Ok(EpisodesListMetadata {
-                    items_count: row.get(0)?,
-                    max_season_number: row.get(1)?,
-                    max_episode_number: row.get(2)?,
-                    max_duration: row.get::<_, Option<u64>>(3)?.map(Duration::from_nanos),
+    fn get_chapters(&mut self, episode_id: EpisodeId) -> DbResult<Vec<Chapter>> {
+        let mut statement = self.connection.prepare(
+            "SELECT start_time, title FROM chapters WHERE episode_id = :id ORDER BY start_time",
+        )?;
+        let rows = statement.query_map(named_params! {":id": episode_id}, |row| {
+            Ok(Chapter {
+                start: Duration::from_nanos(row.get(0)?),
+                title: row.get(1)?,
+            })
+        })?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn set_chapters(&mut self, episode_id: EpisodeId, chapters: &[Chapter]) -> DbResult<()> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "DELETE FROM chapters WHERE episode_id = :id",
+            named_params! {":id": episode_id},
+        )?;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT INTO chapters (episode_id, start_time, title) VALUES (:id, :start_time, :title)",
+            )?;
+            for chapter in chapters {
+                statement.execute(named_params! {
+                    ":id": episode_id,
+                    ":start_time": chapter.start.as_nanos() as u64,
+                    ":title": chapter.title,
+                })?;
+            }
+        }
+        transaction.execute(
+            "UPDATE episodes SET chapters_loaded = 1 WHERE id = :id",
+            named_params! {":id": episode_id},
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn get_transcript_source(
+        &mut self,
+        episode_id: EpisodeId,
+    ) -> DbResult<Option<TranscriptSource>> {
+        let mut statement = self.connection.prepare(
+            "SELECT transcript_url, transcript_format, transcript_cached FROM episodes
+            WHERE id = :id AND transcript_url IS NOT NULL",
+        )?;
+        let result = statement.query_row(named_params! {":id": episode_id}, |row| {
+            let url = row.get(0)?;
+            let format = row.get::<_, Option<usize>>(1)?;
+            let is_cached = row.get(2)?;
+            Ok(format
+                .and_then(TranscriptFormat::from_db)
+                .map(|format| TranscriptSource {
+                    url,
+                    format,
+                    is_cached,
+                }))
+        });
+        match result {
+            Ok(source) => Ok(source),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn set_transcript_cached(&mut self, episode_id: EpisodeId, url: &str) -> DbResult<()> {
+        let mut statement = self.connection.prepare(
+            "UPDATE episodes SET transcript_cached = 1 WHERE id = :id AND transcript_url = :url",
+        )?;
+        statement.execute(named_params! {":id": episode_id, ":url": url})?;
+        Ok(())
+    }
+
+    fn get_cached_transcripts(&mut self, feed_id: FeedId) -> DbResult<Vec<EpisodeId>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT id FROM episodes WHERE feed_id = :feed_id AND transcript_cached")?;
+        let rows = statement.query_map(named_params! {":feed_id": feed_id}, |row| row.get(0))?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn get_pending_downloads(&mut self) -> DbResult<Vec<EpisodeId>> {
+        let mut statement = self.connection.prepare(
+            "SELECT episode_id FROM downloads WHERE state IN (:queued, :in_progress) ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(
+            named_params! {
+                ":queued": DownloadState::Queued.db_view(),
+                ":in_progress": DownloadState::InProgress.db_view(),
+            },
+            |row| row.get(0),
+        )?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn set_download(&mut self, episode_id: EpisodeId, download: &Download) -> DbResult<()> {
+        let mut statement = self.connection.prepare(
+            "INSERT INTO downloads (episode_id, path, state, bytes, total_bytes)
+            VALUES (:id, :path, :state, :bytes, :total_bytes)
+            ON CONFLICT (episode_id) DO UPDATE SET
+            path = :path, state = :state, bytes = :bytes, total_bytes = :total_bytes",
+        )?;
+        statement.execute(named_params! {
+            ":id": episode_id,
+            ":path": download.path.to_string_lossy(),
+            ":state": download.progress.state.db_view(),
+            ":bytes": download.progress.bytes,
+            ":total_bytes": download.progress.total_bytes,
+        })?;
+        Ok(())
+    }
+
+    fn get_feed_downloads(&mut self, feed_id: FeedId) -> DbResult<Vec<(EpisodeId, Download)>> {
+        let mut statement = self.connection.prepare(
+            "SELECT dl.episode_id, dl.path, dl.state, dl.bytes, dl.total_bytes
+            FROM downloads AS dl JOIN episodes AS ep ON ep.id = dl.episode_id
+            WHERE ep.feed_id = :feed_id",
+        )?;
+        let rows = statement.query_map(named_params! {":feed_id": feed_id}, |row| {
+            Ok((row.get(0)?, read_download(row, 1)?))
+        })?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn delete_downloads(
+        &mut self,
+        episode_ids: &[EpisodeId],
+    ) -> DbResult<Vec<(EpisodeId, Download)>> {
+        let transaction = self.connection.transaction()?;
+        let mut downloads = Vec::new();
+        {
+            let mut select = transaction.prepare(
+                "SELECT path, state, bytes, total_bytes FROM downloads WHERE episode_id = :id",
+            )?;
+            let mut delete = transaction.prepare("DELETE FROM downloads WHERE episode_id = :id")?;
+            for episode_id in episode_ids {
+                let result = select.query_row(named_params! {":id": episode_id}, |row| {
+                    read_download(row, 0)
+                });
+                match result {
+                    Ok(download) => downloads.push((*episode_id, download)),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => continue,
+                    Err(error) => return Err(error.into()),
+                }
+                delete.execute(named_params! {":id": episode_id})?;
+            }
+        }
+        transaction.commit()?;
+        Ok(downloads)
+    }
+
+    fn get_auto_download_episodes(&mut self, feed_id: FeedId) -> DbResult<Vec<EpisodeId>> {
+        let mut statement = self.connection.prepare(
+            "WITH newest AS (
+                SELECT ep.id, ROW_NUMBER() OVER (
+                    ORDER BY ep.publication_date DESC, ep.id DESC
+                ) AS position
+                FROM episodes AS ep
+                WHERE ep.feed_id = :feed_id AND ep.status = :new_status AND NOT ep.hidden
+            )
+            SELECT newest.id FROM newest
+            JOIN feeds ON feeds.id = :feed_id
+            WHERE newest.position <= feeds.auto_download
+                AND newest.id NOT IN (SELECT episode_id FROM downloads WHERE state != :failed)
+            ORDER BY newest.position",
+        )?;
+        let rows = statement.query_map(
+            named_params! {
+                ":feed_id": feed_id,
+                ":new_status": EpisodeSummaryStatus::New.db_view(),
+                ":failed": DownloadState::Failed.db_view(),
+            },
+            |row| row.get(0),
+        )?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn get_expired_downloads(&mut self, now: DateTime<Utc>) -> DbResult<Vec<EpisodeId>> {
+        let mut statement = self.connection.prepare(
+            "SELECT downloads.episode_id FROM downloads
+            JOIN episodes AS ep ON downloads.episode_id = ep.id
+            JOIN feeds ON ep.feed_id = feeds.id
+            WHERE ep.status = :finished_status AND feeds.retention_days IS NOT NULL
+                AND ep.finished_at + feeds.retention_days * 86400 <= :now
+            ORDER BY downloads.rowid",
+        )?;
+        let rows = statement.query_map(
+            named_params! {
+                ":finished_status": EpisodeSummaryStatus::Finished.db_view(),
+                ":now": now.timestamp(),
+            },
+            |row| row.get(0),
+        )?;
+        Ok(collect_results(rows)?)
+    }
+
+    fn get_episodes_list_metadata(
+        &mut self,
+        query: EpisodesQuery,
+    ) -> DbResult<EpisodesListMetadata> {
+        let mut sql =
+            "SELECT COUNT(ep.id), MAX(ep.season_number), MAX(ep.episode_number), MAX(ep.duration),
+                    SUM(CASE WHEN ep.publication_date IS NOT NULL THEN 1 ELSE 0 END), feeds.reversed
+            FROM episodes AS ep
+            JOIN feeds ON ep.feed_id = feeds.id
+            LEFT JOIN groups ON feeds.group_id = groups.id
+            "
+            .to_string();
+        query.build_where_clause(&mut sql, self.search_index);
+        let mut statement = self.connection.prepare(&sql)?;
+
+        let feed_specific = query.feed_id.is_some();
+        let where_params = EpisodeQueryParams::from_query(query, self.search_index);
+        let params = where_params.as_sql_params();
+        statement
+            .query_row(&*params, |row| {
+                Ok(EpisodesListMetadata {
+                    items_count: row.get(0)?,
+                    max_season_number: row.get(1)?,
+                    max_episode_number: row.get(2)?,
+                    max_duration: row.get::<_, Option<u64>>(3)?.map(Duration::from_nanos),
                     has_publication_date: row.get::<_, Option<u64>>(4)?.unwrap_or(0) > 0,
                     reversed_order: if feed_specific {
                         row.get::<_, Option<bool>>(5)?.unwrap_or(false)
@@ -389,63 +1174,79 @@ impl DataProvider for SqliteDataProvider {
     ) -> DbResult<Vec<EpisodeSummary>> {
         let feed_title_required = request.include_feed_title;
         let has_group_filter = request.group_id.is_some();
-        let mut sql = "SELECT ep.id, ep.feed_id, ep.episode_number, ep.season_number, ep.title, ep.status, ep.duration, ep.publication_date, ep.hidden".to_string();
+        let mut sql = "SELECT ep.id, ep.feed_id, ep.episode_number, ep.season_number, ep.title, ep.status, ep.duration, ep.publication_date, ep.hidden, dl.state, dl.bytes, dl.total_bytes".to_string();
         if feed_title_required {
             sql.push_str(", feeds.title");
         }
-        sql.push_str(" FROM episodes AS ep");
+        sql.push_str(" FROM episodes AS ep LEFT JOIN downloads AS dl ON dl.episode_id = ep.id");
         if feed_title_required || has_group_filter {
             sql.push_str(" JOIN feeds ON feeds.id == ep.feed_id");
         }
         if has_group_filter {
             sql.push_str(" LEFT JOIN groups on groups.id = feeds.group_id");
         }
-        request.build_where_clause(&mut sql);
-        sql.push_str(" ORDER BY ep.publication_date ");
-        sql.push_str(match request.reversed_order {
-            true => "ASC",
-            false => "DESC",
-        });
+        request.build_where_clause(&mut sql, self.search_index);
+        request.build_order_clause(&mut sql);
         sql.push_str(" LIMIT :limit OFFSET :offset");
         let mut statement = self.connection.prepare(&sql)?;
 
-        let where_params = EpisodeQueryParams::from_query(request);
+        let sort_key = request.sort_key;
+        let where_params =
+            EpisodeQueryParams::from_query(request, self.search_index).with_order(sort_key);
         let mut params = where_params.as_sql_params();
         let offset = range.start;
         let limit = range.end - range.start;
         params.push((":limit", &limit as &dyn rusqlite::ToSql));
         params.push((":offset", &offset as &dyn rusqlite::ToSql));
         let rows = statement.query_map(&*params, |row| {
-            Ok(EpisodeSummary {
-                id: row.get(0)?,
-                feed_id: row.get(1)?,
-                episode_number: row.get(2)?,
-                season_number: row.get(3)?,
-                title: row.get(4)?,
-                status: EpisodeSummaryStatus::from_db(row.get(5)?),
-                duration: row.get::<_, Option<u64>>(6)?.map(Duration::from_nanos),
-                publication_date: row.get(7)?,
-                feed_title: if feed_title_required {
-                    row.get(9)?
-                } else {
-                    None
-                },
-                is_hidden: row.get(8)?,
-            })
+            read_episode_summary(row, feed_title_required)
         })?;
         Ok(collect_results(rows)?)
     }
 
     fn count_episodes(&mut self, query: EpisodesQuery) -> DbResult<usize> {
         let mut sql = "SELECT COUNT(id) FROM episodes AS ep".to_string();
-        query.build_where_clause(&mut sql);
+        query.build_where_clause(&mut sql, self.search_index);
         let mut statement = self.connection.prepare(&sql)?;
 
-        let where_params = EpisodeQueryParams::from_query(query);
+        let where_params = EpisodeQueryParams::from_query(query, self.search_index);
         let count = statement.query_row(&*where_params.as_sql_params(), |row| row.get(0))?;
         Ok(count)
     }
 
+    fn get_next_episode(
+        &mut self,
+        query: EpisodesQuery,
+        after: EpisodeId,
+    ) -> DbResult<Option<EpisodeId>> {
+        // If the episode is not in the list anymore, the list is played from the beginning.
+        // Otherwise, only the episodes sorted after it are considered.
+        let in_list = self.count_episodes(query.clone().id(after))? > 0;
+        let keyset_condition;
+        let mut sql = "SELECT ep.id FROM episodes AS ep".to_string();
+        let mut clauses = vec!["ep.status != :finished", "ep.id != :after"];
+        if in_list {
+            sql.push_str(" JOIN episodes AS after ON after.id = :after");
+            keyset_condition = query.build_keyset_condition();
+            clauses.push(&keyset_condition);
+        }
+        query.build_where_clause_with(&mut sql, self.search_index, &clauses);
+        query.build_order_clause(&mut sql);
+        sql.push_str(" LIMIT 1");
+        let mut statement = self.connection.prepare(&sql)?;
+
+        let mut where_params = EpisodeQueryParams::from_query(query, self.search_index);
+        where_params.finished = Some(EpisodeSummaryStatus::Finished.db_view());
+        let mut params = where_params.as_sql_params();
+        params.push((":after", &after as &dyn rusqlite::ToSql));
+        let next = match statement.query_row(&*params, |row| row.get(0)) {
+            Ok(episode_id) => Some(episode_id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(error) => return Err(error.into()),
+        };
+        Ok(next)
+    }
+
     fn create_feed_pending(&mut self, data: &NewFeedMetadata) -> DbResult<Option<FeedId>> {
         let mut exists_statement = self
             .connection
@@ -458,22 +1259,44 @@ impl DataProvider for SqliteDataProvider {
             return Ok(None);
         }
 
-        let mut statement = self
-            .connection
-            .prepare("INSERT INTO feeds (source, title, link) VALUES (:source, :title, :link)")?;
+        let mut statement = self.connection.prepare(
+            "INSERT INTO feeds (source, title, link, auth_username, auth_password_command)
+            VALUES (:source, :title, :link, :username, :password_command)",
+        )?;
+        let username = data.credentials.as_ref().map(|creds| &creds.username);
+        let password_command = data
+            .credentials
+            .as_ref()
+            .and_then(|creds| creds.password_command.as_ref());
         statement
-            .insert(
-                named_params! {":source": data.source, ":title": data.title, ":link": data.link},
-            )
+            .insert(named_params! {
+                ":source": data.source,
+                ":title": data.title,
+                ":link": data.link,
+                ":username": username,
+                ":password_command": password_command,
+            })
             .map(|id| Some(FeedId(id)))
             .map_err(Into::into)
     }
 
     fn delete_feed(&mut self, id: FeedId) -> DbResult<()> {
-        let mut statement = self
-            .connection
-            .prepare("DELETE FROM feeds WHERE id = :id")?;
-        statement.execute(named_params! {":id": id})?;
+        let transaction = self.connection.transaction()?;
+        if self.search_index {
+            transaction
+                .prepare(
+                    "DELETE FROM episodes_search
+                    WHERE rowid IN (SELECT id FROM episodes WHERE feed_id = :id)",
+                )?
+                .execute(named_params! {":id": id})?;
+        }
+        transaction
+            .prepare("DELETE FROM episodes_sort_keys WHERE view = :view AND view_id = :id")?
+            .execute(named_params! {":view": FEED_SORT_VIEW, ":id": id})?;
+        transaction
+            .prepare("DELETE FROM feeds WHERE id = :id")?
+            .execute(named_params! {":id": id})?;
+        transaction.commit()?;
         Ok(())
     }
 
@@ -501,14 +1324,59 @@ impl DataProvider for SqliteDataProvider {
         Ok(())
     }
 
+    fn get_episodes_sort_keys(
+        &mut self,
+    ) -> DbResult<HashMap<FeedView<FeedId, GroupId, SavedViewId>, EpisodesSortKey>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT view, view_id, sort_key FROM episodes_sort_keys")?;
+        let rows = statement.query_map([], |row| {
+            let view_id = row.get(1)?;
+            let view = match row.get(0)? {
+                ALL_SORT_VIEW => FeedView::All,
+                NEW_SORT_VIEW => FeedView::New,
+                FEED_SORT_VIEW => FeedView::Feed(FeedId(view_id)),
+                SAVED_SORT_VIEW => FeedView::Saved(SavedViewId(view_id)),
+                _ => FeedView::Group(GroupId(view_id)),
+            };
+            Ok((view, EpisodesSortKey::from_db(row.get(2)?)))
+        })?;
+        Ok(collect_results(rows)?.into_iter().collect())
+    }
+
+    fn set_episodes_sort_key(
+        &mut self,
+        view: FeedView<FeedId, GroupId, SavedViewId>,
+        sort_key: EpisodesSortKey,
+    ) -> DbResult<()> {
+        let (view, view_id) = match view {
+            FeedView::All => (ALL_SORT_VIEW, 0),
+            FeedView::New => (NEW_SORT_VIEW, 0),
+            FeedView::Feed(feed_id) => (FEED_SORT_VIEW, feed_id.as_i64()),
+            FeedView::Group(group_id) => (GROUP_SORT_VIEW, group_id.as_i64()),
+            FeedView::Saved(view_id) => (SAVED_SORT_VIEW, view_id.as_i64()),
+        };
+        let mut statement = self.connection.prepare(
+            "INSERT INTO episodes_sort_keys (view, view_id, sort_key)
+            VALUES (:view, :view_id, :sort_key)
+            ON CONFLICT (view, view_id) DO UPDATE SET sort_key = :sort_key",
+        )?;
+        statement.execute(named_params! {
+            ":view": view,
+            ":view_id": view_id,
+            ":sort_key": sort_key.db_view(),
+        })?;
+        Ok(())
+    }
+
     fn set_episode_status(
         &mut self,
         query: EpisodesQuery,
         status: EpisodeStatus,
     ) -> DbResult<HashSet<FeedId>> {
         let mut sql = "SELECT DISTINCT ep.feed_id FROM episodes AS ep ".to_string();
-        query.build_where_clause(&mut sql);
-        let where_params = EpisodeQueryParams::from_query(query.clone());
+        query.build_where_clause(&mut sql, self.search_index);
+        let where_params = EpisodeQueryParams::from_query(query.clone(), self.search_index);
         let mut statement = self.connection.prepare(&sql)?;
         let feed_ids = statement.query_map(&*where_params.as_sql_params(), |row| row.get(0))?;
         let mut feed_ids_set = HashSet::new();
@@ -516,17 +1384,23 @@ impl DataProvider for SqliteDataProvider {
             feed_ids_set.insert(feed_id?);
         }
 
-        let mut sql =
-            "UPDATE episodes AS ep SET status = :new_status, position = :position ".to_string();
-        query.build_where_clause(&mut sql);
+        let mut sql = "UPDATE episodes AS ep SET status = :new_status, position = :position,
+            finished_at = CASE WHEN ep.status = :new_status THEN ep.finished_at ELSE :finished_at END"
+            .to_string();
+        query.build_where_clause(&mut sql, self.search_index);
         let mut statement = self.connection.prepare(&sql)?;
 
+        let finished_at = match status {
+            EpisodeStatus::Finished => Some(Utc::now().timestamp()),
+            _ => None,
+        };
         let (status, position) = status.db_view();
         let position = position.as_nanos() as u64;
-        let where_params = EpisodeQueryParams::from_query(query);
+        let where_params = EpisodeQueryParams::from_query(query, self.search_index);
         let mut params = where_params.as_sql_params();
         params.push((":new_status", &status as &dyn rusqlite::ToSql));
         params.push((":position", &position as &dyn rusqlite::ToSql));
+        params.push((":finished_at", &finished_at as &dyn rusqlite::ToSql));
         statement.execute(&*params)?;
 
         Ok(feed_ids_set)
@@ -534,10 +1408,10 @@ impl DataProvider for SqliteDataProvider {
 
     fn set_episode_hidden(&mut self, query: EpisodesQuery, hidden: bool) -> DbResult<()> {
         let mut sql = "UPDATE episodes AS ep SET hidden = :hidden".to_string();
-        query.build_where_clause(&mut sql);
+        query.build_where_clause(&mut sql, self.search_index);
         let mut statement = self.connection.prepare(&sql)?;
 
-        let where_params = EpisodeQueryParams::from_query(query);
+        let where_params = EpisodeQueryParams::from_query(query, self.search_index);
         let mut params = where_params.as_sql_params();
         params.push((":hidden", &hidden));
 
@@ -550,12 +1424,22 @@ impl DataProvider for SqliteDataProvider {
         Ok(Box::new(SqliteEpisodeWriter {
             feed_id,
             transaction,
+            search_index: self.search_index,
         }))
     }
 }
 
 impl EpisodesQuery {
-    fn build_where_clause(&self, query: &mut String) {
+    fn build_where_clause(&self, query: &mut String, search_index: bool) {
+        self.build_where_clause_with(query, search_index, &[]);
+    }
+
+    fn build_where_clause_with(
+        &self,
+        query: &mut String,
+        search_index: bool,
+        extra_clauses: &[&str],
+    ) {
         let mut clauses = Vec::new();
         if self.episode_id.is_some() {
             clauses.push("ep.id = :id");
@@ -564,14 +1448,34 @@ impl EpisodesQuery {
             clauses.push("ep.feed_id = :feed_id");
         }
         if self.group_id.is_some() {
-            clauses.push("groups.id = :group_id");
+            clauses.push("ep.feed_id IN (SELECT id FROM feeds WHERE group_id = :group_id)");
         }
         if self.status.is_some() {
             clauses.push("ep.status = :status");
         }
+        if self.text.is_some() && search_index {
+            clauses.push(
+                "ep.id IN (SELECT rowid FROM episodes_search WHERE episodes_search MATCH :text)",
+            );
+        } else if self.text.is_some() {
+            clauses.push(SEARCH_LIKE_CLAUSE);
+        }
+        if self.min_duration.is_some() {
+            clauses.push("ep.duration >= :min_duration");
+        }
+        if self.max_duration.is_some() {
+            clauses.push("ep.duration <= :max_duration");
+        }
+        if self.published_after.is_some() {
+            clauses.push("ep.publication_date >= :published_after");
+        }
+        if self.published_before.is_some() {
+            clauses.push("ep.publication_date < :published_before");
+        }
         if !self.with_hidden {
             clauses.push("NOT ep.hidden");
         }
+        clauses.extend_from_slice(extra_clauses);
         if !clauses.is_empty() {
             query.push_str(" WHERE ");
             for (index, clause) in clauses.into_iter().enumerate() {
@@ -582,24 +1486,143 @@ impl EpisodesQuery {
             }
         }
     }
+
+    // Returns the expressions the episodes of the table aliased as `table` are sorted by and
+    // whether they are sorted in descending order.
+    fn sort_columns(&self, table: &str) -> (Vec<String>, bool) {
+        let (columns, descending) = match self.sort_key {
+            EpisodesSortKey::PublicationDate => (vec![format!("{}.publication_date", table)], true),
+            EpisodesSortKey::Duration => (vec![format!("{}.duration", table)], false),
+            EpisodesSortKey::Title => (vec![format!("{}.title COLLATE NOCASE", table)], false),
+            EpisodesSortKey::EpisodeNumber => (
+                vec![
+                    format!("{}.season_number", table),
+                    format!("{}.episode_number", table),
+                ],
+                true,
+            ),
+            EpisodesSortKey::RemainingTime => (
+                vec![format!(
+                    "CASE WHEN {t}.status = :finished THEN 0 \
+                    ELSE MAX({t}.duration - {t}.position, 0) END",
+                    t = table
+                )],
+                false,
+            ),
+            EpisodesSortKey::DateAdded => (vec![format!("{}.added_at", table)], true),
+        };
+        (columns, descending != self.reversed_order)
+    }
+
+    // Episodes without a value for the sort key are always placed at the end of the list. The
+    // episode id is the last resort to keep the order stable between paginated requests.
+    fn build_order_clause(&self, query: &mut String) {
+        let (columns, descending) = self.sort_columns("ep");
+        let direction = match descending {
+            true => "DESC",
+            false => "ASC",
+        };
+        query.push_str(" ORDER BY ");
+        for column in columns {
+            write!(query, "{} IS NULL, {} {}, ", column, column, direction).unwrap();
+        }
+        write!(query, "ep.id {}", direction).unwrap();
+    }
+
+    // Matches the episodes that `build_order_clause` places after the episode aliased as
+    // `after`: they tie on the first columns and come later on the next one, where a missing
+    // value comes after any other, or tie on every column and come later by id.
+    fn build_keyset_condition(&self) -> String {
+        let (columns, descending) = self.sort_columns("ep");
+        let (after_columns, _) = self.sort_columns("after");
+        let operator = match descending {
+            true => "<",
+            false => ">",
+        };
+        let mut ties = String::new();
+        let mut condition = String::from("(");
+        for (column, after_column) in columns.iter().zip(after_columns.iter()) {
+            write!(
+                condition,
+                "({}{} IS NOT NULL AND ({} IS NULL OR {} {} {})) OR ",
+                ties, after_column, column, column, operator, after_column
+            )
+            .unwrap();
+            write!(ties, "{} IS {} AND ", column, after_column).unwrap();
+        }
+        write!(condition, "({}ep.id {} after.id))", ties, operator).unwrap();
+        condition
+    }
 }
 
+// Searches episodes without the FTS5 index: each of the words in `:text` must be contained in the
+// title, description or feed title, ignoring the case of ASCII letters only.
+const SEARCH_LIKE_CLAUSE: &str = "(:text != '' AND NOT EXISTS (
+    WITH RECURSIVE words (word, rest) AS (
+        SELECT NULL, :text || ' '
+        UNION ALL
+        SELECT substr(rest, 1, instr(rest, ' ') - 1), substr(rest, instr(rest, ' ') + 1)
+        FROM words WHERE rest != ''
+    )
+    SELECT true FROM words
+    WHERE word IS NOT NULL AND NOT (
+        COALESCE(ep.title, '') LIKE '%' || word || '%' ESCAPE '\\'
+        OR COALESCE(ep.description, '') LIKE '%' || word || '%' ESCAPE '\\'
+        OR COALESCE((SELECT COALESCE(title_override, title) FROM feeds WHERE id = ep.feed_id), '')
+            LIKE '%' || word || '%' ESCAPE '\\'
+    )
+))";
+
+// Identifies the kinds of views in the `episodes_sort_keys` table.
+const ALL_SORT_VIEW: u32 = 0;
+const NEW_SORT_VIEW: u32 = 1;
+const FEED_SORT_VIEW: u32 = 2;
+const GROUP_SORT_VIEW: u32 = 3;
+const SAVED_SORT_VIEW: u32 = 4;
+
 #[derive(Default)]
 struct EpisodeQueryParams {
     id: Option<EpisodeId>,
     feed_id: Option<FeedId>,
     group_id: Option<GroupId>,
     status: Option<usize>,
+    text: Option<String>,
+    min_duration: Option<u64>,
+    max_duration: Option<u64>,
+    published_after: Option<DateTime<Utc>>,
+    published_before: Option<DateTime<Utc>>,
+    finished: Option<usize>,
 }
 
 impl EpisodeQueryParams {
-    fn from_query(query: EpisodesQuery) -> Self {
+    fn from_query(query: EpisodesQuery, search_index: bool) -> Self {
         EpisodeQueryParams {
             id: query.episode_id,
             feed_id: query.feed_id,
             group_id: query.group_id,
             status: query.status.map(|status| status.db_view()),
+            text: query.text.as_deref().map(|text| match search_index {
+                true => search_match_expression(text),
+                false => search_like_words(text),
+            }),
+            min_duration: query
+                .min_duration
+                .map(|duration| duration.as_nanos() as u64),
+            max_duration: query
+                .max_duration
+                .map(|duration| duration.as_nanos() as u64),
+            published_after: query.published_after,
+            published_before: query.published_before,
+            finished: None,
+        }
+    }
+
+    // Adds the parameters used by `build_order_clause` to the ones of the filters.
+    fn with_order(mut self, sort_key: EpisodesSortKey) -> Self {
+        if sort_key == EpisodesSortKey::RemainingTime {
+            self.finished = Some(EpisodeSummaryStatus::Finished.db_view());
         }
+        self
     }
 
     fn as_sql_params<'a>(&'a self) -> Vec<(&'static str, &'a dyn rusqlite::ToSql)> {
@@ -616,10 +1639,134 @@ impl EpisodeQueryParams {
         if let Some(status) = self.status.as_ref() {
             params.push((":status", status));
         }
+        if let Some(text) = self.text.as_ref() {
+            params.push((":text", text));
+        }
+        if let Some(min_duration) = self.min_duration.as_ref() {
+            params.push((":min_duration", min_duration));
+        }
+        if let Some(max_duration) = self.max_duration.as_ref() {
+            params.push((":max_duration", max_duration));
+        }
+        if let Some(published_after) = self.published_after.as_ref() {
+            params.push((":published_after", published_after));
+        }
+        if let Some(published_before) = self.published_before.as_ref() {
+            params.push((":published_before", published_before));
+        }
+        if let Some(finished) = self.finished.as_ref() {
+            params.push((":finished", finished));
+        }
         params
     }
 }
 
+// Reads the columns selected for episode summaries: id, feed_id, episode_number, season_number,
+// title, status, duration, publication_date, hidden, download state, bytes and total_bytes,
+// optionally followed by the feed title.
+fn read_episode_summary(
+    row: &rusqlite::Row,
+    with_feed_title: bool,
+) -> rusqlite::Result<EpisodeSummary> {
+    Ok(EpisodeSummary {
+        id: row.get(0)?,
+        feed_id: row.get(1)?,
+        episode_number: row.get(2)?,
+        season_number: row.get(3)?,
+        title: row.get(4)?,
+        status: EpisodeSummaryStatus::from_db(row.get(5)?),
+        duration: row.get::<_, Option<u64>>(6)?.map(Duration::from_nanos),
+        publication_date: row.get(7)?,
+        feed_title: if with_feed_title { row.get(12)? } else { None },
+        is_hidden: row.get(8)?,
+        download: match row.get::<_, Option<usize>>(9)? {
+            Some(state) => Some(DownloadProgress {
+                state: DownloadState::from_db(state),
+                bytes: row.get(10)?,
+                total_bytes: row.get(11)?,
+            }),
+            None => None,
+        },
+    })
+}
+
+// Reads the path, state, bytes and total_bytes columns of a download starting at `offset`.
+fn read_download(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Download> {
+    Ok(Download {
+        path: PathBuf::from(row.get::<_, String>(offset)?),
+        progress: DownloadProgress {
+            state: DownloadState::from_db(row.get(offset + 1)?),
+            bytes: row.get(offset + 2)?,
+            total_bytes: row.get(offset + 3)?,
+        },
+    })
+}
+
+// Every word is quoted so that the user's input is never interpreted as FTS5 query syntax. The
+// words are implicitly joined with AND and match as prefixes.
+fn search_match_expression(text: &str) -> String {
+    let mut expression = String::new();
+    for word in text.split_whitespace() {
+        if !expression.is_empty() {
+            expression.push(' ');
+        }
+        write!(expression, "\"{}\"*", word.replace('"', "\"\"")).unwrap();
+    }
+    if expression.is_empty() {
+        expression.push_str("\"\"");
+    }
+    expression
+}
+
+// Splits `text` into space-separated words and escapes them for SEARCH_LIKE_CLAUSE.
+fn search_like_words(text: &str) -> String {
+    let mut words = String::new();
+    for word in text.split_whitespace() {
+        if !words.is_empty() {
+            words.push(' ');
+        }
+        for c in word.chars() {
+            if matches!(c, '\\' | '%' | '_') {
+                words.push('\\');
+            }
+            words.push(c);
+        }
+    }
+    words
+}
+
+fn update_search_feed_title(connection: &Connection, feed_id: FeedId) -> DbResult<()> {
+    let mut statement = connection.prepare(
+        "UPDATE episodes_search
+        SET feed_title = (SELECT COALESCE(title_override, title) FROM feeds WHERE id = :feed_id)
+        WHERE rowid IN (SELECT id FROM episodes WHERE feed_id = :feed_id)",
+    )?;
+    statement.execute(named_params! {":feed_id": feed_id})?;
+    Ok(())
+}
+
+// Finds the earliest time starting from `time` that is outside of the hours and days of the
+// week (both in UTC) the feed asked to skip.
+fn first_unskipped_hour(
+    mut time: DateTime<Utc>,
+    skip_hours: u32,
+    skip_days: u8,
+) -> Option<DateTime<Utc>> {
+    for _ in 0..24 * 7 {
+        let skipped_hour = (skip_hours >> time.hour()) & 1 != 0;
+        let skipped_day = (skip_days >> time.weekday().num_days_from_monday()) & 1 != 0;
+        if !skipped_hour && !skipped_day {
+            return Some(time);
+        }
+        let next_hour = time + chrono::Duration::hours(1);
+        time = next_hour
+            .with_minute(0)?
+            .with_second(0)?
+            .with_nanosecond(0)?;
+    }
+    None
+}
+
 fn collect_results<T, E>(items: impl IntoIterator<Item = Result<T, E>>) -> Result<Vec<T>, E> {
     let iter = items.into_iter();
     let mut result = Vec::with_capacity(iter.size_hint().0);
@@ -632,6 +1779,7 @@ fn collect_results<T, E>(items: impl IntoIterator<Item = Result<T, E>>) -> Resul
 pub struct SqliteEpisodeWriter<'a> {
     feed_id: FeedId,
     transaction: rusqlite::Transaction<'a>,
+    search_index: bool,
 }
 
 impl<'a> EpisodeWriter for SqliteEpisodeWriter<'a> {
@@ -653,16 +1801,66 @@ impl<'a> EpisodeWriter for SqliteEpisodeWriter<'a> {
             ":error_code": error_code,
             ":id": self.feed_id
         })?;
+        if self.search_index {
+            update_search_feed_title(&self.transaction, self.feed_id)?;
+        }
+        Ok(())
+    }
+
+    fn set_cache_validators(&mut self, validators: &CacheValidators) -> DbResult<()> {
+        let mut statement = self.transaction.prepare(
+            "UPDATE feeds SET http_etag = :etag, http_last_modified = :last_modified WHERE id = :id",
+        )?;
+        statement.execute(named_params! {
+            ":etag": validators.etag,
+            ":last_modified": validators.last_modified,
+            ":id": self.feed_id,
+        })?;
+        Ok(())
+    }
+
+    fn set_refresh_hints(&mut self, hints: &RefreshHints) -> DbResult<()> {
+        let mut statement = self.transaction.prepare(
+            "UPDATE feeds
+            SET refresh_interval = :refresh_interval, skip_hours = :skip_hours,
+                skip_days = :skip_days, complete = :complete
+            WHERE id = :id",
+        )?;
+        statement.execute(named_params! {
+            ":refresh_interval": hints.min_interval.map(|interval| interval.as_secs()),
+            ":skip_hours": hints.skip_hours,
+            ":skip_days": hints.skip_days,
+            ":complete": hints.complete,
+            ":id": self.feed_id,
+        })?;
         Ok(())
     }
 
-    fn set_episode_metadata(&mut self, metadata: &EpisodeMetadata) -> DbResult<EpisodeId> {
+    fn set_episode_metadata(&mut self, metadata: &EpisodeMetadata) -> DbResult<EpisodeWriteResult> {
+        let mut id_statement = self.transaction.prepare(
+            "SELECT ep.id FROM episodes AS ep WHERE feed_id = :feed_id AND guid = :guid",
+        )?;
+        let existing_id = match id_statement.query_row(
+            named_params! {
+                ":feed_id": self.feed_id,
+                ":guid": metadata.guid,
+            },
+            |row| row.get(0),
+        ) {
+            Ok(id) => Some(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(error) => return Err(error.into()),
+        };
+
         let mut statement = self.transaction.prepare(
-            "INSERT INTO episodes (feed_id, guid, title, description, link, duration, publication_date, episode_number, season_number, media_url)
-            VALUES (:feed_id, :guid, :title, :description, :link, :duration, :publication_date, :episode_number, :season_number, :media_url)
+            "INSERT INTO episodes (feed_id, guid, title, description, link, duration, publication_date, episode_number, season_number, media_url, chapters_url, transcript_url, transcript_format, added_at)
+            VALUES (:feed_id, :guid, :title, :description, :link, :duration, :publication_date, :episode_number, :season_number, :media_url, :chapters_url, :transcript_url, :transcript_format, :added_at)
             ON CONFLICT (feed_id, guid) DO UPDATE SET
             title = :title, description = :description, link = :link, duration = :duration, publication_date = :publication_date, 
-            episode_number = :episode_number, season_number = :season_number, media_url = :media_url
+            episode_number = :episode_number, season_number = :season_number, media_url = :media_url,
+            chapters_loaded = CASE WHEN chapters_url IS :chapters_url THEN chapters_loaded ELSE 0 END, chapters_url = :chapters_url,
+            transcript_cached = CASE WHEN transcript_url IS :transcript_url THEN transcript_cached ELSE 0 END,
+            transcript_url = :transcript_url, transcript_format = :transcript_format
             WHERE feed_id = :feed_id AND guid = :guid"
         )?;
         statement.execute(named_params! {
@@ -675,44 +1873,95 @@ impl<'a> EpisodeWriter for SqliteEpisodeWriter<'a> {
             ":publication_date": metadata.publication_date,
             ":episode_number": metadata.episode_number,
             ":season_number": metadata.season_number,
-            ":media_url": metadata.media_url
+            ":media_url": metadata.media_url,
+            ":chapters_url": metadata.chapters_url,
+            ":transcript_url": metadata.transcript.map(|(url, _)| url),
+            ":transcript_format": metadata.transcript.map(|(_, format)| format.db_view()),
+            ":added_at": Utc::now().timestamp(),
         })?;
 
-        let mut id_statement = self.transaction.prepare(
-            "SELECT ep.id FROM episodes AS ep WHERE feed_id = :feed_id AND guid = :guid",
-        )?;
-        id_statement
-            .query_row(
-                named_params! {
-                    ":feed_id": self.feed_id,
-                    ":guid": metadata.guid,
-                },
-                |row| row.get(0),
-            )
-            .map_err(QueryError::from)
+        let result = match existing_id {
+            Some(id) => EpisodeWriteResult::Updated(id),
+            None => EpisodeWriteResult::Inserted(EpisodeId(self.transaction.last_insert_rowid())),
+        };
+
+        if self.search_index {
+            let mut search_statement = self.transaction.prepare(
+                "INSERT OR REPLACE INTO episodes_search (rowid, title, description, feed_title)
+                SELECT :id, :title, :description, COALESCE(title_override, title)
+                FROM feeds WHERE id = :feed_id",
+            )?;
+            search_statement.execute(named_params! {
+                ":id": result.id(),
+                ":title": metadata.title,
+                ":description": metadata.description,
+                ":feed_id": self.feed_id,
+            })?;
+        }
+        Ok(result)
     }
 
     fn close(self: Box<Self>) -> DbResult<()> {
         self.transaction.commit().map_err(QueryError::from)
     }
 
-    fn delete_episode(&mut self, guid: &str) -> DbResult<()> {
+    fn delete_episode(&mut self, guid: &str) -> DbResult<Option<(EpisodeId, Option<Download>)>> {
+        let mut select_statement = self
+            .transaction
+            .prepare("SELECT id FROM episodes WHERE feed_id = :feed_id AND guid = :guid")?;
+        let episode_id = match select_statement.query_row(
+            named_params! { ":feed_id": self.feed_id, ":guid": guid },
+            |row| row.get(0),
+        ) {
+            Ok(episode_id) => episode_id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        // The download is deleted along with the episode, its file is left to the caller.
+        let mut download_statement = self.transaction.prepare(
+            "SELECT path, state, bytes, total_bytes FROM downloads WHERE episode_id = :id",
+        )?;
+        let download = match download_statement
+            .query_row(named_params! { ":id": episode_id }, |row| {
+                read_download(row, 0)
+            }) {
+            Ok(download) => Some(download),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(error) => return Err(error.into()),
+        };
+
+        if self.search_index {
+            let mut search_statement = self
+                .transaction
+                .prepare("DELETE FROM episodes_search WHERE rowid = :id")?;
+            search_statement.execute(named_params! { ":id": episode_id })?;
+        }
+
         let mut statement = self
             .transaction
-            .prepare("DELETE FROM episodes WHERE feed_id = :feed_id AND guid = :guid")?;
-        statement.execute(named_params! { ":feed_id": self.feed_id, ":guid": guid })?;
-        Ok(())
+            .prepare("DELETE FROM episodes WHERE id = :id")?;
+        statement.execute(named_params! { ":id": episode_id })?;
+        Ok(Some((episode_id, download)))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{ConnectionError, SqliteDataProvider};
-    use crate::datasource::{DataProvider, NewFeedMetadata};
+    use crate::datasource::{DataProvider, EpisodeWriteResult, NewFeedMetadata};
     use crate::metadata::{EpisodeMetadata, FeedMetadata};
-    use crate::model::{EpisodeStatus, EpisodeSummary, EpisodeSummaryStatus, FeedStatus};
-    use crate::EpisodesQuery;
+    use crate::model::{
+        CacheValidators, Chapter, DayListeningStats, Download, DownloadProgress, DownloadState,
+        EpisodeStatus, EpisodeSummary, EpisodeSummaryStatus, EpisodesFilter, EpisodesSortKey,
+        FeedCredentials, FeedListeningStats, FeedPolicy, FeedStatus, FeedView, ListeningSession,
+        ListeningStats, ListeningTime, RefreshHints, SavedViewSummary, TranscriptFormat,
+        TranscriptSource,
+    };
+    use crate::{EpisodesQuery, UpdateQuery};
+    use chrono::{DateTime, Local, TimeZone, Utc};
     use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
     use std::time::Duration;
 
     #[test]
@@ -747,11 +1996,44 @@ mod tests {
             error,
             ConnectionError::VersionUnknown {
                 version: 20,
-                current: 2
+                current: 19
             }
         ));
     }
 
+    #[test]
+    fn backfills_date_added() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut path = dir.path().to_path_buf();
+        path.push("db.sqlite");
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        SqliteDataProvider::migrate(&connection, 0, 11).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO feeds (source) VALUES ('http://example.com/feed.xml');
+                INSERT INTO episodes (feed_id, guid, media_url, publication_date)
+                VALUES (1, 'dated', 'http://example.com/1.mp3', '2021-10-01 12:00:00+00:00'),
+                       (1, 'undated', 'http://example.com/2.mp3', NULL);",
+            )
+            .unwrap();
+        drop(connection);
+
+        let provider = SqliteDataProvider::connect(&path).unwrap();
+        let added_at = |guid: &str| -> Option<i64> {
+            provider
+                .connection
+                .query_row(
+                    "SELECT added_at FROM episodes WHERE guid = ?",
+                    [guid],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(added_at("dated"), Some(1633089600));
+        assert!(added_at("undated").unwrap() >= Utc::now().timestamp() - 60);
+    }
+
     #[test]
     fn feed_update() {
         let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
@@ -792,15 +2074,74 @@ mod tests {
     }
 
     #[test]
-    fn does_not_create_duplicate() {
+    fn feed_source_update() {
         let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
-        let id1 = provider
+        let id = provider
             .create_feed_pending(&NewFeedMetadata::new(
                 "http://example.com/feed.xml".to_string(),
             ))
+            .unwrap()
             .unwrap();
-        let id2 = provider
-            .create_feed_pending(&NewFeedMetadata::new(
+        let mut writer = provider.writer(id).unwrap();
+        writer
+            .set_cache_validators(&CacheValidators {
+                etag: Some("\"abc\"".to_string()),
+                last_modified: None,
+            })
+            .unwrap();
+        writer.close().unwrap();
+
+        provider
+            .set_feed_source(id, "https://example.com/new-feed.xml")
+            .unwrap();
+        let feed = provider.get_feed(id).unwrap().unwrap();
+        assert_eq!(&feed.source, "https://example.com/new-feed.xml");
+        let sources = provider
+            .get_update_sources(UpdateQuery::Single(id))
+            .unwrap();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].source, "https://example.com/new-feed.xml");
+        assert_eq!(sources[0].validators, Some(CacheValidators::default()));
+    }
+
+    #[test]
+    fn feed_credentials() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let public_feed = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+        let credentials = FeedCredentials {
+            username: "user".to_string(),
+            password_command: Some("pass show example.com".to_string()),
+        };
+        let private_feed = provider
+            .create_feed_pending(
+                &NewFeedMetadata::new("http://example.com/private.xml".to_string())
+                    .with_credentials(credentials.clone()),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(provider.get_feed_credentials(public_feed).unwrap(), None);
+        assert_eq!(
+            provider.get_feed_credentials(private_feed).unwrap(),
+            Some(credentials)
+        );
+    }
+
+    #[test]
+    fn does_not_create_duplicate() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let id1 = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap();
+        let id2 = provider
+            .create_feed_pending(&NewFeedMetadata::new(
                 "http://example.com/feed.xml".to_string(),
             ))
             .unwrap();
@@ -832,9 +2173,13 @@ mod tests {
                 season_number: Some(4),
                 media_url: "http://example.com/feed.xml",
                 block: false,
+                chapters_url: None,
+                transcript: None,
             })
             .unwrap();
         writer.close().unwrap();
+        assert!(matches!(episode_id, EpisodeWriteResult::Inserted(_)));
+        let episode_id = episode_id.id();
 
         let retrieved = provider.get_episode(episode_id).unwrap().unwrap();
         assert_eq!(retrieved.id, episode_id);
@@ -862,9 +2207,12 @@ mod tests {
                 season_number: None,
                 media_url: "http://example.com/feed2.xml",
                 block: false,
+                chapters_url: None,
+                transcript: None,
             })
             .unwrap();
-        assert_eq!(episode_id, episode_id_1);
+        assert_eq!(episode_id_1, EpisodeWriteResult::Updated(episode_id));
+        let episode_id_1 = episode_id_1.id();
         writer.close().unwrap();
 
         let retrieved = provider.get_episode(episode_id).unwrap().unwrap();
@@ -893,9 +2241,13 @@ mod tests {
                 season_number: None,
                 media_url: "http://example.com/feed3.xml",
                 block: false,
+                chapters_url: None,
+                transcript: None,
             })
             .unwrap();
         writer.close().unwrap();
+        assert!(matches!(episode_id_2, EpisodeWriteResult::Inserted(_)));
+        let episode_id_2 = episode_id_2.id();
 
         let mut episodes = provider
             .get_episode_summaries(EpisodesQuery::default().feed_id(feed_id), 0..100)
@@ -914,6 +2266,7 @@ mod tests {
                 duration: Some(Duration::from_secs(300)),
                 publication_date: None,
                 is_hidden: false,
+                download: None,
             }
         );
         assert_eq!(
@@ -929,7 +2282,1219 @@ mod tests {
                 duration: None,
                 publication_date: None,
                 is_hidden: false,
+                download: None,
+            }
+        );
+    }
+
+    #[test]
+    fn episode_text_search() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer
+            .set_feed_metadata(&FeedMetadata {
+                title: "Rustacean Station",
+                description: "",
+                link: "http://example.com",
+                author: None,
+                copyright: None,
+            })
+            .unwrap();
+        let episode = |guid, title, description| EpisodeMetadata {
+            title: Some(title),
+            description: Some(description),
+            link: None,
+            guid,
+            duration: None,
+            publication_date: None,
+            episode_number: None,
+            season_number: None,
+            media_url: "http://example.com/episode.mp3",
+            block: false,
+            chapters_url: None,
+            transcript: None,
+        };
+        let async_episode = writer
+            .set_episode_metadata(&episode("guid-1", "Async Rust", "All about futures"))
+            .unwrap()
+            .id();
+        let cafe_episode = writer
+            .set_episode_metadata(&episode("guid-2", "Café", "A \"quoted\" chat"))
+            .unwrap()
+            .id();
+        writer.close().unwrap();
+
+        let mut search = |text: &str| {
+            let query = EpisodesQuery::default().text(text.to_string());
+            let mut ids: Vec<_> = provider
+                .get_episode_summaries(query, 0..100)
+                .unwrap()
+                .into_iter()
+                .map(|episode| episode.id)
+                .collect();
+            ids.sort_by_key(|id| id.0);
+            ids
+        };
+        assert_eq!(search("rust fut"), vec![async_episode]);
+        assert_eq!(search("cafe"), vec![cafe_episode]);
+        assert_eq!(search("\"quoted"), vec![cafe_episode]);
+        assert_eq!(search("rustacean"), vec![async_episode, cafe_episode]);
+        assert!(search("python").is_empty());
+        assert!(search(" ").is_empty());
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer
+            .set_episode_metadata(&episode("guid-1", "Async Python", "All about asyncio"))
+            .unwrap();
+        writer.delete_episode("guid-2").unwrap();
+        writer.close().unwrap();
+        provider
+            .rename_feed(feed_id, "Station".to_string())
+            .unwrap();
+
+        let mut search = |text: &str| {
+            let query = EpisodesQuery::default().text(text.to_string());
+            provider.count_episodes(query).unwrap()
+        };
+        assert_eq!(search("rust"), 0);
+        assert_eq!(search("python"), 1);
+        assert_eq!(search("cafe"), 0);
+        assert_eq!(search("station"), 1);
+
+        provider.delete_feed(feed_id).unwrap();
+        let count: usize = provider
+            .connection
+            .query_row("SELECT COUNT(*) FROM episodes_search", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn text_search_without_index() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        provider.search_index = false;
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+        provider
+            .rename_feed(feed_id, "Rustacean Station".to_string())
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let episode = |guid, title, description| EpisodeMetadata {
+            title: Some(title),
+            description: Some(description),
+            link: None,
+            guid,
+            duration: None,
+            publication_date: None,
+            episode_number: None,
+            season_number: None,
+            media_url: "http://example.com/episode.mp3",
+            block: false,
+            chapters_url: None,
+            transcript: None,
+        };
+        let async_episode = writer
+            .set_episode_metadata(&episode("guid-1", "Async Rust", "All about futures"))
+            .unwrap()
+            .id();
+        let percent_episode = writer
+            .set_episode_metadata(&episode("guid-2", "100% Rust", "No description"))
+            .unwrap()
+            .id();
+        writer.close().unwrap();
+
+        let mut search = |text: &str| {
+            let query = EpisodesQuery::default().text(text.to_string());
+            let mut ids: Vec<_> = provider
+                .get_episode_summaries(query, 0..100)
+                .unwrap()
+                .into_iter()
+                .map(|episode| episode.id)
+                .collect();
+            ids.sort_by_key(|id| id.0);
+            ids
+        };
+        assert_eq!(search("FUTURES  rust"), vec![async_episode]);
+        assert_eq!(search("station"), vec![async_episode, percent_episode]);
+        assert_eq!(search("0%"), vec![percent_episode]);
+        assert!(search("1_0").is_empty());
+        assert!(search(" ").is_empty());
+    }
+
+    #[test]
+    fn episodes_sorting() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let mut episode = |guid, title, duration: Option<u64>, day| {
+            writer
+                .set_episode_metadata(&EpisodeMetadata {
+                    title: Some(title),
+                    description: None,
+                    link: None,
+                    guid,
+                    duration: duration.map(Duration::from_secs),
+                    publication_date: Some(Utc.ymd(2021, 9, day).and_hms(0, 0, 0)),
+                    episode_number: Some(day as i64),
+                    season_number: None,
+                    media_url: "http://example.com/episode.mp3",
+                    block: false,
+                    chapters_url: None,
+                    transcript: None,
+                })
+                .unwrap()
+                .id()
+        };
+        let first = episode("guid-1", "b", Some(600), 1);
+        let second = episode("guid-2", "C", None, 2);
+        let third = episode("guid-3", "a", Some(300), 3);
+        writer.close().unwrap();
+        provider
+            .set_episode_status(
+                EpisodesQuery::default().id(first),
+                EpisodeStatus::Started(Duration::from_secs(500)),
+            )
+            .unwrap();
+
+        let mut sorted = |sort_key, reversed| {
+            let query = EpisodesQuery::default()
+                .sort_key(sort_key)
+                .reversed_order(reversed);
+            (provider.get_episode_summaries(query, 0..100).unwrap())
+                .into_iter()
+                .map(|episode| episode.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sorted(EpisodesSortKey::PublicationDate, false),
+            vec![third, second, first]
+        );
+        assert_eq!(
+            sorted(EpisodesSortKey::PublicationDate, true),
+            vec![first, second, third]
+        );
+        assert_eq!(
+            sorted(EpisodesSortKey::Duration, false),
+            vec![third, first, second]
+        );
+        assert_eq!(
+            sorted(EpisodesSortKey::Duration, true),
+            vec![first, third, second]
+        );
+        assert_eq!(
+            sorted(EpisodesSortKey::Title, false),
+            vec![third, first, second]
+        );
+        assert_eq!(
+            sorted(EpisodesSortKey::EpisodeNumber, false),
+            vec![third, second, first]
+        );
+        assert_eq!(
+            sorted(EpisodesSortKey::RemainingTime, false),
+            vec![first, third, second]
+        );
+        assert_eq!(
+            sorted(EpisodesSortKey::DateAdded, false),
+            vec![third, second, first]
+        );
+
+        assert!(provider.get_episodes_sort_keys().unwrap().is_empty());
+        provider
+            .set_episodes_sort_key(FeedView::New, EpisodesSortKey::Duration)
+            .unwrap();
+        provider
+            .set_episodes_sort_key(FeedView::Feed(feed_id), EpisodesSortKey::Title)
+            .unwrap();
+        provider
+            .set_episodes_sort_key(FeedView::Feed(feed_id), EpisodesSortKey::RemainingTime)
+            .unwrap();
+        let sort_keys = provider.get_episodes_sort_keys().unwrap();
+        assert_eq!(sort_keys.len(), 2);
+        assert_eq!(sort_keys[&FeedView::New], EpisodesSortKey::Duration);
+        assert_eq!(
+            sort_keys[&FeedView::Feed(feed_id)],
+            EpisodesSortKey::RemainingTime
+        );
+
+        provider.delete_feed(feed_id).unwrap();
+        let sort_keys = provider.get_episodes_sort_keys().unwrap();
+        assert_eq!(sort_keys.len(), 1);
+    }
+
+    #[test]
+    fn queue() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let mut episode = |guid| {
+            writer
+                .set_episode_metadata(&EpisodeMetadata {
+                    title: Some(guid),
+                    description: None,
+                    link: None,
+                    guid,
+                    duration: None,
+                    publication_date: None,
+                    episode_number: None,
+                    season_number: None,
+                    media_url: "http://example.com/episode.mp3",
+                    block: false,
+                    chapters_url: None,
+                    transcript: None,
+                })
+                .unwrap()
+                .id()
+        };
+        let ids: Vec<_> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|guid| episode(guid))
+            .collect();
+        writer.close().unwrap();
+
+        let queued = |provider: &mut SqliteDataProvider| {
+            (provider.get_queue().unwrap())
+                .into_iter()
+                .map(|episode| episode.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(queued(&mut provider), vec![]);
+
+        for id in &ids {
+            provider.add_to_queue(*id).unwrap();
+        }
+        provider.add_to_queue(ids[0]).unwrap();
+        assert_eq!(queued(&mut provider), ids);
+
+        provider.set_queue_position(ids[3], 1).unwrap();
+        assert_eq!(queued(&mut provider), vec![ids[3], ids[0], ids[1], ids[2]]);
+        provider.set_queue_position(ids[3], 10).unwrap();
+        assert_eq!(queued(&mut provider), ids);
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer.delete_episode("b").unwrap();
+        writer.close().unwrap();
+        provider.remove_from_queue(ids[0]).unwrap();
+        assert_eq!(queued(&mut provider), vec![ids[2], ids[3]]);
+        provider.set_queue_position(ids[3], 1).unwrap();
+        assert_eq!(queued(&mut provider), vec![ids[3], ids[2]]);
+
+        provider.clear_queue().unwrap();
+        assert_eq!(queued(&mut provider), vec![]);
+    }
+
+    #[test]
+    fn next_episode() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let mut episode = |guid, day| {
+            writer
+                .set_episode_metadata(&EpisodeMetadata {
+                    title: Some(guid),
+                    description: None,
+                    link: None,
+                    guid,
+                    duration: None,
+                    publication_date: Some(Utc.ymd(2021, 10, day).and_hms(12, 0, 0)),
+                    episode_number: None,
+                    season_number: None,
+                    media_url: "http://example.com/episode.mp3",
+                    block: false,
+                    chapters_url: None,
+                    transcript: None,
+                })
+                .unwrap()
+                .id()
+        };
+        let first = episode("a", 1);
+        let second = episode("b", 2);
+        let third = episode("c", 3);
+        let fourth = episode("d", 4);
+        writer.close().unwrap();
+        provider
+            .set_episode_status(EpisodesQuery::default().id(third), EpisodeStatus::Finished)
+            .unwrap();
+
+        let chronological = EpisodesQuery::default()
+            .feed_id(feed_id)
+            .reversed_order(true);
+        let mut next =
+            |query: &EpisodesQuery, after| provider.get_next_episode(query.clone(), after).unwrap();
+        assert_eq!(next(&chronological, first), Some(second));
+        assert_eq!(next(&chronological, second), Some(fourth));
+        assert_eq!(next(&chronological, fourth), None);
+        assert_eq!(next(&EpisodesQuery::default(), second), Some(first));
+
+        let new_episodes = EpisodesQuery::default().status(EpisodeSummaryStatus::New);
+        assert_eq!(next(&new_episodes, third), Some(fourth));
+        assert_eq!(next(&new_episodes, fourth), Some(second));
+
+        let by_title = EpisodesQuery::default().sort_key(EpisodesSortKey::Title);
+        assert_eq!(next(&by_title, first), Some(second));
+        assert_eq!(next(&by_title, second), Some(fourth));
+        let by_duration = EpisodesQuery::default()
+            .sort_key(EpisodesSortKey::Duration)
+            .reversed_order(true);
+        assert_eq!(next(&by_duration, fourth), Some(second));
+        assert_eq!(next(&by_duration, first), None);
+    }
+
+    #[test]
+    fn listening_stats() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let mut episode = |source: &str, title: Option<&str>| {
+            let feed_id = provider
+                .create_feed_pending(&NewFeedMetadata::new(source.to_string()))
+                .unwrap()
+                .unwrap();
+            let mut writer = provider.writer(feed_id).unwrap();
+            if let Some(title) = title {
+                writer
+                    .set_feed_metadata(&FeedMetadata {
+                        title,
+                        description: "",
+                        link: "",
+                        author: None,
+                        copyright: None,
+                    })
+                    .unwrap();
+            }
+            let episode_id = writer
+                .set_episode_metadata(&EpisodeMetadata {
+                    title: None,
+                    description: None,
+                    link: None,
+                    guid: "episode",
+                    duration: None,
+                    publication_date: None,
+                    episode_number: None,
+                    season_number: None,
+                    media_url: "http://example.com/episode.mp3",
+                    block: false,
+                    chapters_url: None,
+                    transcript: None,
+                })
+                .unwrap()
+                .id();
+            writer.close().unwrap();
+            (feed_id, episode_id)
+        };
+        let (first_feed, first) = episode("http://example.com/first.xml", Some("First"));
+        let (second_feed, second) = episode("http://example.com/second.xml", None);
+
+        let first_day = Utc.ymd(2021, 10, 1).and_hms(12, 0, 0);
+        let second_day = Utc.ymd(2021, 10, 3).and_hms(12, 0, 0);
+        let sessions = [
+            (first, 0, 60, first_day, 1.5),
+            (second, 0, 30, second_day, 1.0),
+            (first, 60, 90, second_day, 2.0),
+        ];
+        for (episode_id, start, end, started_at, rate) in sessions.iter() {
+            provider
+                .add_listening_session(&ListeningSession {
+                    episode_id: *episode_id,
+                    start_position: Duration::from_secs(*start),
+                    end_position: Duration::from_secs(*end),
+                    started_at: *started_at,
+                    ended_at: *started_at + chrono::Duration::seconds((*end - *start) as i64),
+                    rate: *rate,
+                    skipped_silence: Duration::ZERO,
+                })
+                .unwrap();
+        }
+
+        let time = |listened, spent| ListeningTime {
+            listened: Duration::from_secs(listened),
+            spent: Duration::from_secs(spent),
+        };
+        let local_date = |date: DateTime<Utc>| date.with_timezone(&Local).date().naive_local();
+        assert_eq!(
+            provider.get_listening_stats(None).unwrap(),
+            ListeningStats {
+                total: time(120, 85),
+                feeds: vec![
+                    FeedListeningStats {
+                        feed_id: Some(first_feed),
+                        title: "First".to_string(),
+                        time: time(90, 55),
+                    },
+                    FeedListeningStats {
+                        feed_id: Some(second_feed),
+                        title: "http://example.com/second.xml".to_string(),
+                        time: time(30, 30),
+                    },
+                ],
+                days: vec![
+                    DayListeningStats {
+                        date: local_date(second_day),
+                        time: time(60, 45),
+                    },
+                    DayListeningStats {
+                        date: local_date(first_day),
+                        time: time(60, 40),
+                    },
+                ],
             }
         );
+        assert_eq!(
+            provider
+                .get_listening_stats(None)
+                .unwrap()
+                .total
+                .time_saved(),
+            Duration::from_secs(35)
+        );
+
+        let recent = provider.get_listening_stats(Some(second_day)).unwrap();
+        assert_eq!(recent.total, time(60, 45));
+        assert_eq!(recent.feeds.len(), 2);
+        assert_eq!(recent.days.len(), 1);
+
+        let third_day = Utc.ymd(2021, 10, 5).and_hms(12, 0, 0);
+        provider
+            .add_listening_session(&ListeningSession {
+                episode_id: second,
+                start_position: Duration::from_secs(30),
+                end_position: Duration::from_secs(90),
+                started_at: third_day,
+                ended_at: third_day + chrono::Duration::seconds(20),
+                rate: 2.0,
+                skipped_silence: Duration::from_secs(20),
+            })
+            .unwrap();
+        let recent = provider.get_listening_stats(Some(third_day)).unwrap();
+        assert_eq!(recent.total, time(60, 20));
+
+        provider.delete_feed(first_feed).unwrap();
+        let stats = provider.get_listening_stats(None).unwrap();
+        assert_eq!(stats.total, time(180, 105));
+        assert!(stats.feeds.contains(&FeedListeningStats {
+            feed_id: None,
+            title: "First".to_string(),
+            time: time(90, 55),
+        }));
+    }
+
+    #[test]
+    fn bookmarks() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer
+            .set_feed_metadata(&FeedMetadata {
+                title: "Podcast",
+                description: "",
+                link: "",
+                author: None,
+                copyright: None,
+            })
+            .unwrap();
+        let episode_id = writer
+            .set_episode_metadata(&EpisodeMetadata {
+                title: Some("Interview"),
+                description: None,
+                link: None,
+                guid: "guid-1",
+                duration: None,
+                publication_date: None,
+                episode_number: None,
+                season_number: None,
+                media_url: "http://example.com/episode.mp3",
+                block: false,
+                chapters_url: None,
+                transcript: None,
+            })
+            .unwrap()
+            .id();
+        writer.close().unwrap();
+
+        provider
+            .add_bookmark(episode_id, Duration::from_secs(60), None)
+            .unwrap();
+        provider
+            .add_bookmark(episode_id, Duration::from_secs(1800), Some("quote"))
+            .unwrap();
+
+        let bookmarks = provider.get_bookmarks().unwrap();
+        let summary: Vec<_> = (bookmarks.iter())
+            .map(|bookmark| (bookmark.position, bookmark.note.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Duration::from_secs(1800), Some("quote")),
+                (Duration::from_secs(60), None),
+            ]
+        );
+        assert_eq!(bookmarks[0].episode_id, episode_id);
+        assert_eq!(bookmarks[0].episode_title.as_deref(), Some("Interview"));
+        assert_eq!(bookmarks[0].feed_title.as_deref(), Some("Podcast"));
+
+        provider.delete_bookmark(bookmarks[0].id).unwrap();
+        let bookmarks = provider.get_bookmarks().unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].position, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn saved_views() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let group_id = provider.create_group("Tech").unwrap().unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+        provider
+            .set_feed_for_group(Some(group_id), feed_id)
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let mut episode = |guid, duration: u64| {
+            writer
+                .set_episode_metadata(&EpisodeMetadata {
+                    title: Some("Episode"),
+                    description: None,
+                    link: None,
+                    guid,
+                    duration: Some(Duration::from_secs(duration)),
+                    publication_date: Some(Utc.ymd(2021, 9, 1).and_hms(0, 0, 0)),
+                    episode_number: None,
+                    season_number: None,
+                    media_url: "http://example.com/episode.mp3",
+                    block: false,
+                    chapters_url: None,
+                    transcript: None,
+                })
+                .unwrap()
+                .id()
+        };
+        let short = episode("guid-1", 600);
+        let long = episode("guid-2", 3600);
+        let finished = episode("guid-3", 3600);
+        writer.close().unwrap();
+        provider
+            .set_episode_status(
+                EpisodesQuery::default().id(finished),
+                EpisodeStatus::Finished,
+            )
+            .unwrap();
+
+        let filter = EpisodesFilter {
+            status: Some(EpisodeSummaryStatus::New),
+            group_id: Some(group_id),
+            min_duration: Some(Duration::from_secs(1800)),
+            older_than_days: Some(30),
+            ..Default::default()
+        };
+        let view_id = provider
+            .create_saved_view("Long", &filter)
+            .unwrap()
+            .unwrap();
+        assert_eq!(provider.create_saved_view("Long", &filter).unwrap(), None);
+        assert_eq!(
+            provider.get_saved_views().unwrap(),
+            vec![SavedViewSummary {
+                id: view_id,
+                name: "Long".to_string(),
+                filter: filter.clone(),
+            }]
+        );
+
+        let mut matching = |filter: &EpisodesFilter| {
+            let query = EpisodesQuery::from_feed_view(FeedView::Saved(filter));
+            (provider.get_episode_summaries(query, 0..100).unwrap())
+                .into_iter()
+                .map(|episode| episode.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(matching(&filter), vec![long]);
+        let shorter = EpisodesFilter {
+            max_duration: Some(Duration::from_secs(1800)),
+            ..Default::default()
+        };
+        assert_eq!(matching(&shorter), vec![short]);
+        let recent = EpisodesFilter {
+            newer_than_days: Some(30),
+            ..Default::default()
+        };
+        assert_eq!(matching(&recent), vec![]);
+
+        provider
+            .set_episodes_sort_key(FeedView::Saved(view_id), EpisodesSortKey::Duration)
+            .unwrap();
+        provider.delete_group(group_id).unwrap();
+        assert_eq!(
+            provider.get_saved_views().unwrap(),
+            vec![SavedViewSummary {
+                id: view_id,
+                name: "Long".to_string(),
+                filter: EpisodesFilter {
+                    group_id: None,
+                    ..filter
+                },
+            }]
+        );
+        assert_eq!(
+            provider
+                .get_episodes_sort_keys()
+                .unwrap()
+                .get(&FeedView::Saved(view_id)),
+            Some(&EpisodesSortKey::Duration)
+        );
+    }
+
+    #[test]
+    fn chapters_update() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut metadata = EpisodeMetadata {
+            title: Some("title"),
+            description: None,
+            link: None,
+            guid: "guid-1",
+            duration: None,
+            publication_date: None,
+            episode_number: None,
+            season_number: None,
+            media_url: "http://example.com/episode.mp3",
+            block: false,
+            chapters_url: Some("http://example.com/chapters.json"),
+            transcript: None,
+        };
+        let mut writer = provider.writer(feed_id).unwrap();
+        let episode_id = writer.set_episode_metadata(&metadata).unwrap().id();
+        writer.close().unwrap();
+
+        assert_eq!(
+            provider.get_pending_chapters_url(episode_id).unwrap(),
+            Some("http://example.com/chapters.json".to_string())
+        );
+        let chapters = vec![
+            Chapter {
+                start: Duration::ZERO,
+                title: Some("Intro".to_string()),
+            },
+            Chapter {
+                start: Duration::from_secs(90),
+                title: None,
+            },
+        ];
+        provider.set_chapters(episode_id, &chapters).unwrap();
+        assert_eq!(provider.get_pending_chapters_url(episode_id).unwrap(), None);
+        assert_eq!(provider.get_chapters(episode_id).unwrap(), chapters);
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer.set_episode_metadata(&metadata).unwrap();
+        writer.close().unwrap();
+        assert_eq!(provider.get_pending_chapters_url(episode_id).unwrap(), None);
+
+        metadata.chapters_url = Some("http://example.com/chapters-v2.json");
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer.set_episode_metadata(&metadata).unwrap();
+        writer.close().unwrap();
+        assert_eq!(
+            provider.get_pending_chapters_url(episode_id).unwrap(),
+            Some("http://example.com/chapters-v2.json".to_string())
+        );
+    }
+
+    #[test]
+    fn transcript_source_update() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut metadata = EpisodeMetadata {
+            title: Some("title"),
+            description: None,
+            link: None,
+            guid: "guid-1",
+            duration: None,
+            publication_date: None,
+            episode_number: None,
+            season_number: None,
+            media_url: "http://example.com/episode.mp3",
+            block: false,
+            chapters_url: None,
+            transcript: Some((
+                "http://example.com/transcript.vtt",
+                TranscriptFormat::WebVtt,
+            )),
+        };
+        let mut writer = provider.writer(feed_id).unwrap();
+        let episode_id = writer.set_episode_metadata(&metadata).unwrap().id();
+        writer.close().unwrap();
+
+        let mut source = TranscriptSource {
+            url: "http://example.com/transcript.vtt".to_string(),
+            format: TranscriptFormat::WebVtt,
+            is_cached: false,
+        };
+        assert_eq!(
+            provider.get_transcript_source(episode_id).unwrap(),
+            Some(source.clone())
+        );
+
+        provider
+            .set_transcript_cached(episode_id, "http://example.com/transcript.vtt")
+            .unwrap();
+        source.is_cached = true;
+        assert_eq!(
+            provider.get_transcript_source(episode_id).unwrap(),
+            Some(source)
+        );
+        assert_eq!(
+            provider.get_cached_transcripts(feed_id).unwrap(),
+            vec![episode_id]
+        );
+
+        metadata.transcript = Some(("http://example.com/transcript.json", TranscriptFormat::Json));
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer.set_episode_metadata(&metadata).unwrap();
+        writer.close().unwrap();
+        assert_eq!(
+            provider.get_transcript_source(episode_id).unwrap(),
+            Some(TranscriptSource {
+                url: "http://example.com/transcript.json".to_string(),
+                format: TranscriptFormat::Json,
+                is_cached: false,
+            })
+        );
+
+        metadata.transcript = None;
+        let mut writer = provider.writer(feed_id).unwrap();
+        writer.set_episode_metadata(&metadata).unwrap();
+        writer.close().unwrap();
+        assert_eq!(provider.get_transcript_source(episode_id).unwrap(), None);
+    }
+
+    #[test]
+    fn downloads_update() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let episode_id = writer
+            .set_episode_metadata(&EpisodeMetadata {
+                title: Some("title"),
+                description: None,
+                link: None,
+                guid: "guid-1",
+                duration: None,
+                publication_date: None,
+                episode_number: None,
+                season_number: None,
+                media_url: "http://example.com/episode.mp3",
+                block: false,
+                chapters_url: None,
+                transcript: None,
+            })
+            .unwrap()
+            .id();
+        writer.close().unwrap();
+
+        let mut download = Download {
+            path: PathBuf::from("/downloads/1.mp3"),
+            progress: DownloadProgress {
+                state: DownloadState::Queued,
+                bytes: 0,
+                total_bytes: None,
+            },
+        };
+        provider.set_download(episode_id, &download).unwrap();
+        assert_eq!(provider.get_pending_downloads().unwrap(), vec![episode_id]);
+        let playback_data = provider.get_episode_playback_data(episode_id).unwrap();
+        assert_eq!(playback_data.unwrap().local_path, None);
+
+        download.progress = DownloadProgress {
+            state: DownloadState::Completed,
+            bytes: 1024,
+            total_bytes: Some(1024),
+        };
+        provider.set_download(episode_id, &download).unwrap();
+        assert!(provider.get_pending_downloads().unwrap().is_empty());
+        let playback_data = provider.get_episode_playback_data(episode_id).unwrap();
+        assert_eq!(
+            playback_data.unwrap().local_path,
+            Some(PathBuf::from("/downloads/1.mp3"))
+        );
+        let episodes = provider
+            .get_episode_summaries(EpisodesQuery::default().feed_id(feed_id), 0..100)
+            .unwrap();
+        assert_eq!(episodes[0].download, Some(download.progress));
+        assert_eq!(
+            provider.get_feed_downloads(feed_id).unwrap(),
+            vec![(episode_id, download.clone())]
+        );
+
+        assert_eq!(
+            provider.delete_downloads(&[episode_id]).unwrap(),
+            vec![(episode_id, download)]
+        );
+        assert!(provider.delete_downloads(&[episode_id]).unwrap().is_empty());
+        let episodes = provider
+            .get_episode_summaries(EpisodesQuery::default().feed_id(feed_id), 0..100)
+            .unwrap();
+        assert_eq!(episodes[0].download, None);
+    }
+
+    #[test]
+    fn feed_playback_settings() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let episode_id = writer
+            .set_episode_metadata(&EpisodeMetadata {
+                title: Some("title"),
+                description: None,
+                link: None,
+                guid: "guid-1",
+                duration: None,
+                publication_date: None,
+                episode_number: None,
+                season_number: None,
+                media_url: "http://example.com/episode.mp3",
+                block: false,
+                chapters_url: None,
+                transcript: None,
+            })
+            .unwrap()
+            .id();
+        writer.close().unwrap();
+
+        let playback_data = provider.get_episode_playback_data(episode_id).unwrap();
+        let playback_data = playback_data.unwrap();
+        assert_eq!(playback_data.rate, 1.0);
+        assert_eq!(playback_data.volume_offset, 0.0);
+
+        provider.set_feed_playback_rate(feed_id, 1.5).unwrap();
+        provider.set_feed_volume_offset(feed_id, -0.1).unwrap();
+        let playback_data = provider.get_episode_playback_data(episode_id).unwrap();
+        let playback_data = playback_data.unwrap();
+        assert_eq!(playback_data.rate, 1.5);
+        assert_eq!(playback_data.volume_offset, -0.1);
+        assert_eq!(playback_data.skip_intro, Duration::ZERO);
+        assert_eq!(playback_data.skip_outro, Duration::ZERO);
+
+        provider
+            .set_feed_intro_skip(feed_id, Duration::from_secs(45))
+            .unwrap();
+        provider
+            .set_feed_outro_skip(feed_id, Duration::from_secs(120))
+            .unwrap();
+        let playback_data = provider.get_episode_playback_data(episode_id).unwrap();
+        let playback_data = playback_data.unwrap();
+        assert_eq!(playback_data.skip_intro, Duration::from_secs(45));
+        assert_eq!(playback_data.skip_outro, Duration::from_secs(120));
+        assert_eq!(playback_data.skip_silence, None);
+
+        provider.set_feed_skip_silence(feed_id, Some(true)).unwrap();
+        let playback_data = provider.get_episode_playback_data(episode_id).unwrap();
+        assert_eq!(playback_data.unwrap().skip_silence, Some(true));
+    }
+
+    #[test]
+    fn download_policies() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let feed_id = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let mut writer = provider.writer(feed_id).unwrap();
+        let episode_ids: Vec<_> = ["guid-1", "guid-2", "guid-3"]
+            .iter()
+            .enumerate()
+            .map(|(index, guid)| {
+                writer
+                    .set_episode_metadata(&EpisodeMetadata {
+                        title: None,
+                        description: None,
+                        link: None,
+                        guid,
+                        duration: None,
+                        publication_date: Some(Utc.ymd(2021, 9, index as u32 + 1).and_hms(0, 0, 0)),
+                        episode_number: None,
+                        season_number: None,
+                        media_url: "http://example.com/episode.mp3",
+                        block: false,
+                        chapters_url: None,
+                        transcript: None,
+                    })
+                    .unwrap()
+                    .id()
+            })
+            .collect();
+        writer.close().unwrap();
+        assert!(provider
+            .get_auto_download_episodes(feed_id)
+            .unwrap()
+            .is_empty());
+
+        let policy = FeedPolicy {
+            auto_download: 2,
+            retention_days: Some(7),
+        };
+        provider.set_feed_policy(feed_id, &policy).unwrap();
+        assert_eq!(
+            provider.get_auto_download_episodes(feed_id).unwrap(),
+            vec![episode_ids[2], episode_ids[1]]
+        );
+
+        let download = Download {
+            path: PathBuf::from("/downloads/3.mp3"),
+            progress: DownloadProgress {
+                state: DownloadState::Completed,
+                bytes: 1024,
+                total_bytes: Some(1024),
+            },
+        };
+        provider.set_download(episode_ids[2], &download).unwrap();
+        assert_eq!(
+            provider.get_auto_download_episodes(feed_id).unwrap(),
+            vec![episode_ids[1]]
+        );
+
+        let failed_download = Download {
+            path: PathBuf::from("/downloads/2.mp3"),
+            progress: DownloadProgress::new(DownloadState::Failed),
+        };
+        provider
+            .set_download(episode_ids[1], &failed_download)
+            .unwrap();
+        assert_eq!(
+            provider.get_auto_download_episodes(feed_id).unwrap(),
+            vec![episode_ids[1]]
+        );
+
+        let now = Utc::now();
+        provider
+            .set_episode_status(
+                EpisodesQuery::default().id(episode_ids[2]),
+                EpisodeStatus::Finished,
+            )
+            .unwrap();
+        assert!(provider.get_expired_downloads(now).unwrap().is_empty());
+        assert_eq!(
+            provider
+                .get_expired_downloads(now + chrono::Duration::days(8))
+                .unwrap(),
+            vec![episode_ids[2]]
+        );
+    }
+
+    #[test]
+    fn scheduled_updates() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let mut create_feed = |source: &str| {
+            provider
+                .create_feed_pending(&NewFeedMetadata::new(source.to_string()))
+                .unwrap()
+                .unwrap()
+        };
+        let hourly_feed = create_feed("http://example.com/hourly.xml");
+        let daily_feed = create_feed("http://example.com/daily.xml");
+        let complete_feed = create_feed("http://example.com/complete.xml");
+
+        let hints = [
+            (hourly_feed, RefreshHints::default()),
+            (
+                daily_feed,
+                RefreshHints {
+                    min_interval: Some(Duration::from_secs(24 * 60 * 60)),
+                    skip_hours: 1 << 3,
+                    ..Default::default()
+                },
+            ),
+            (
+                complete_feed,
+                RefreshHints {
+                    complete: true,
+                    ..Default::default()
+                },
+            ),
+        ];
+        for (feed_id, hints) in &hints {
+            let mut writer = provider.writer(*feed_id).unwrap();
+            writer.set_refresh_hints(hints).unwrap();
+            writer.close().unwrap();
+        }
+
+        let interval = Duration::from_secs(60 * 60);
+        let checked_at = Utc.ymd(2021, 9, 1).and_hms(12, 0, 0);
+        let due_feeds = |provider: &mut SqliteDataProvider, now| {
+            let sources = provider
+                .get_update_sources(UpdateQuery::Due(now, interval))
+                .unwrap();
+            sources
+                .into_iter()
+                .map(|source| source.feed_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            due_feeds(&mut provider, checked_at),
+            vec![hourly_feed, daily_feed]
+        );
+        assert_eq!(
+            provider.get_next_update_time(checked_at, interval).unwrap(),
+            Some(checked_at)
+        );
+
+        provider
+            .set_feed_checked(hourly_feed, checked_at, true)
+            .unwrap();
+        provider
+            .set_feed_checked(daily_feed, checked_at, true)
+            .unwrap();
+        assert_eq!(
+            provider.get_next_update_time(checked_at, interval).unwrap(),
+            Some(checked_at + chrono::Duration::hours(1))
+        );
+        assert!(due_feeds(&mut provider, checked_at).is_empty());
+        assert_eq!(
+            due_feeds(&mut provider, checked_at + chrono::Duration::hours(2)),
+            vec![hourly_feed]
+        );
+        assert_eq!(
+            due_feeds(&mut provider, checked_at + chrono::Duration::hours(24)),
+            vec![hourly_feed, daily_feed]
+        );
+        assert_eq!(
+            due_feeds(&mut provider, checked_at + chrono::Duration::hours(39)),
+            vec![hourly_feed]
+        );
+
+        // The daily feed is due at 3:00 which it asks to skip.
+        provider
+            .set_feed_checked(daily_feed, Utc.ymd(2021, 8, 31).and_hms(3, 0, 0), true)
+            .unwrap();
+        assert_eq!(
+            provider
+                .get_next_update_time(Utc.ymd(2021, 9, 1).and_hms(0, 0, 0), interval)
+                .unwrap(),
+            Some(Utc.ymd(2021, 9, 1).and_hms(4, 0, 0))
+        );
+    }
+
+    #[test]
+    fn failure_backoff() {
+        let mut provider = SqliteDataProvider::connect(":memory:").unwrap();
+        let working_feed = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/feed.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+        let broken_feed = provider
+            .create_feed_pending(&NewFeedMetadata::new(
+                "http://example.com/broken.xml".to_string(),
+            ))
+            .unwrap()
+            .unwrap();
+
+        let update_ids = |provider: &mut SqliteDataProvider, query| {
+            let sources = provider.get_update_sources(query).unwrap();
+            sources
+                .into_iter()
+                .map(|source| source.feed_id)
+                .collect::<Vec<_>>()
+        };
+
+        let now = Utc::now();
+        provider.set_feed_checked(working_feed, now, true).unwrap();
+        provider.set_feed_checked(broken_feed, now, true).unwrap();
+        provider.set_feed_checked(broken_feed, now, false).unwrap();
+        provider.set_feed_checked(broken_feed, now, false).unwrap();
+
+        let feed = provider.get_feed(broken_feed).unwrap().unwrap();
+        assert_eq!(feed.failure_count, 2);
+        assert_eq!(feed.last_success, Some(Utc.timestamp(now.timestamp(), 0)));
+        assert_eq!(
+            update_ids(&mut provider, UpdateQuery::All),
+            vec![working_feed]
+        );
+        assert_eq!(
+            update_ids(&mut provider, UpdateQuery::Failed),
+            vec![broken_feed]
+        );
+
+        let half_an_hour_ago = now - chrono::Duration::minutes(30);
+        provider
+            .set_feed_checked(broken_feed, half_an_hour_ago, false)
+            .unwrap();
+        assert_eq!(
+            update_ids(&mut provider, UpdateQuery::All),
+            vec![working_feed]
+        );
+        provider
+            .set_feed_checked(broken_feed, half_an_hour_ago, true)
+            .unwrap();
+        assert_eq!(
+            update_ids(&mut provider, UpdateQuery::All),
+            vec![working_feed, broken_feed]
+        );
+        assert!(update_ids(&mut provider, UpdateQuery::Failed).is_empty());
+        let feed = provider.get_feed(broken_feed).unwrap().unwrap();
+        assert_eq!(feed.failure_count, 0);
     }
 }
@@ -2,7 +2,10 @@
 
 mod data;
 
-use crate::model::{EpisodeSummary, FeedError, FeedId, FeedStatus};
+use crate::model::{
+    Download, DownloadProgress, DownloadState, EpisodeId, EpisodeSummary, FeedError, FeedId,
+    FeedStatus,
+};
 use crate::sqlite::SqliteDataProvider;
 use crate::{
     EpisodeSummariesRequest, EpisodesListMetadataRequest, EpisodesQuery, FeedSummariesRequest,
@@ -12,6 +15,8 @@ use crate::{
 use actix::prelude::*;
 use reqwest::StatusCode;
 use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 struct NotificationListener {
@@ -211,6 +216,20 @@ async fn get_episode_summaries(
     episodes
 }
 
+async fn seed_download(library: &Addr<Library>, episode_id: EpisodeId, path: &Path) {
+    fs::write(path, b"audio").unwrap();
+    let download = Download {
+        path: path.to_path_buf(),
+        progress: DownloadProgress {
+            state: DownloadState::Completed,
+            bytes: 5,
+            total_bytes: Some(5),
+        },
+    };
+    let msg = FeedUpdateRequest::SetDownload(episode_id, download);
+    library.send(msg).await.unwrap();
+}
+
 #[actix::test]
 async fn creates_episodes() {
     let (library, mut reciever) = create_library().await;
@@ -255,6 +274,11 @@ async fn updates_episodes_on_update() {
     let_assert!(let FeedUpdateNotification::UpdateStarted(ids) = update_started);
     assert_eq!(ids, vec![feed_id]);
 
+    let episodes_added = reciever.recv().await.unwrap();
+    let_assert!(let FeedUpdateNotification::EpisodesAdded(id, added_ids) = episodes_added);
+    assert_eq!(id, feed_id);
+    assert_eq!(added_ids.len(), 1);
+
     let update_finished = reciever.recv().await.unwrap();
     let_assert!(let FeedUpdateNotification::UpdateFinished(id, update) = update_finished);
     assert_eq!(id, feed_id);
@@ -287,6 +311,15 @@ async fn removes_blocked_episodes() {
     let feed = include_str!("../test_data/rss/feed1.xml");
     let mock_server = httpmock::MockServer::start();
     let feed_id = seed_feed(&mock_server, library.clone(), &mut reciever, feed).await;
+    let query = EpisodesQuery::default().feed_id(feed_id);
+    let episodes = get_episode_summaries(library.clone(), query).await;
+    let blocked_episode = episodes
+        .iter()
+        .find(|ep| ep.title.as_deref() == Some("Episode #2"))
+        .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("2.mp3");
+    seed_download(&library, blocked_episode.id, &path).await;
     mock_server.mock(|when, then| {
         when.method(httpmock::Method::GET).path("/feed.xml");
         then.status(200)
@@ -317,6 +350,29 @@ async fn removes_blocked_episodes() {
     for (expected, actual) in expected.iter().zip(episodes.iter()) {
         expected.assert_equals(actual);
     }
+    assert!(!path.exists());
+}
+
+#[actix::test]
+async fn deleting_feed_removes_downloads() {
+    let (library, mut reciever) = create_library().await;
+    let feed = include_str!("../test_data/rss/feed1.xml");
+    let mock_server = httpmock::MockServer::start();
+    let feed_id = seed_feed(&mock_server, library.clone(), &mut reciever, feed).await;
+    let query = EpisodesQuery::default().feed_id(feed_id);
+    let episodes = get_episode_summaries(library.clone(), query).await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("1.mp3");
+    seed_download(&library, episodes[0].id, &path).await;
+
+    library
+        .send(FeedUpdateRequest::DeleteFeed(feed_id))
+        .await
+        .unwrap();
+    let feed_deleted = reciever.recv().await.unwrap();
+    let_assert!(let FeedUpdateNotification::FeedDeleted(id) = feed_deleted);
+    assert_eq!(id, feed_id);
+    assert!(!path.exists());
 }
 
 #[actix::test]
@@ -361,6 +417,56 @@ async fn update_failure() {
     }
 }
 
+#[actix::test]
+async fn update_not_modified() {
+    let (library, mut reciever) = create_library().await;
+    let mock_server = httpmock::MockServer::start();
+    let mut mock = mock_server.mock(|when, then| {
+        when.method(httpmock::Method::GET).path("/feed.xml");
+        then.status(200)
+            .header("etag", "\"feed1\"")
+            .body(include_str!("../test_data/rss/feed1.xml"));
+    });
+
+    let source_url = format!("{}/feed.xml", mock_server.base_url());
+    let msg = FeedUpdateRequest::AddFeed(NewFeedMetadata::new(source_url));
+    library.send(msg).await.unwrap();
+    let feed_id = loop {
+        let msg = reciever.recv().await.unwrap();
+        if let FeedUpdateNotification::UpdateFinished(feed_id, _) = msg {
+            break feed_id;
+        }
+    };
+    mock.delete();
+
+    let not_modified = mock_server.mock(|when, then| {
+        when.method(httpmock::Method::GET)
+            .path("/feed.xml")
+            .header("if-none-match", "\"feed1\"");
+        then.status(304);
+    });
+
+    let msg = FeedUpdateRequest::Update(UpdateQuery::Single(feed_id));
+    library.send(msg).await.unwrap();
+
+    let _update_started = reciever.recv().await.unwrap();
+    let update_finished = reciever.recv().await.unwrap();
+    let_assert!(let FeedUpdateNotification::UpdateFinished(id, update) = update_finished);
+    assert_eq!(id, feed_id);
+    let_assert!(let FeedUpdateResult::Updated(summary) = update);
+    assert_eq!(summary.id, feed_id);
+    assert_eq!(&summary.title, "Sample Podcast");
+    assert_eq!(summary.status, FeedStatus::Loaded);
+    assert_eq!(summary.new_count, 5);
+    not_modified.assert();
+
+    let query = EpisodesQuery::default()
+        .feed_id(feed_id)
+        .include_feed_title();
+    let episodes = get_episode_summaries(library, query).await;
+    assert_eq!(episodes.len(), 5);
+}
+
 #[actix::test]
 async fn update_all() {
     let (library, mut reciever) = create_library().await;
@@ -0,0 +1,255 @@
+use crate::model::{TranscriptCue, TranscriptFormat};
+use serde::Deserialize;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub(crate) enum TranscriptError {
+    #[error("transcript is not a valid UTF-8 text")]
+    Encoding(#[from] std::str::Utf8Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonTranscript {
+    #[serde(default)]
+    segments: Vec<JsonTranscriptSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTranscriptSegment {
+    speaker: Option<String>,
+    start_time: f64,
+    end_time: Option<f64>,
+    body: String,
+}
+
+pub(crate) fn parse_transcript(
+    format: TranscriptFormat,
+    data: &[u8],
+) -> Result<Vec<TranscriptCue>, TranscriptError> {
+    let mut cues = match format {
+        TranscriptFormat::Json => parse_json(data)?,
+        TranscriptFormat::Srt | TranscriptFormat::WebVtt => {
+            parse_timed_text(std::str::from_utf8(data)?)
+        }
+    };
+    cues.sort_by_key(|cue| cue.start);
+    Ok(cues)
+}
+
+fn parse_json(data: &[u8]) -> Result<Vec<TranscriptCue>, serde_json::Error> {
+    let transcript: JsonTranscript = serde_json::from_slice(data)?;
+    let cues = transcript
+        .segments
+        .into_iter()
+        .filter(|segment| segment.start_time.is_finite() && segment.start_time >= 0.0)
+        .map(|segment| {
+            let start = Duration::from_secs_f64(segment.start_time);
+            let end = segment
+                .end_time
+                .filter(|end_time| end_time.is_finite() && *end_time >= segment.start_time)
+                .map(Duration::from_secs_f64)
+                .unwrap_or(start);
+            TranscriptCue {
+                start,
+                end,
+                speaker: segment.speaker.filter(|speaker| !speaker.is_empty()),
+                text: segment.body.trim().to_string(),
+            }
+        })
+        .filter(|cue| !cue.text.is_empty())
+        .collect();
+    Ok(cues)
+}
+
+// SRT and WebVTT only differ in details that do not matter for displaying the cues: both
+// consist of blank line separated blocks with a "start --> end" line followed by the text.
+fn parse_timed_text(text: &str) -> Vec<TranscriptCue> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut cues = Vec::new();
+    for block in text.split("\n\n") {
+        let mut lines = block.lines().skip_while(|line| !line.contains("-->"));
+        let (start, end) = match lines.next().and_then(parse_cue_timing) {
+            Some(timing) => timing,
+            None => continue,
+        };
+
+        let mut speaker = None;
+        let mut text = String::new();
+        for line in lines {
+            let (line_speaker, line) = strip_tags(line);
+            if speaker.is_none() {
+                speaker = line_speaker;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&line);
+        }
+        if !text.is_empty() {
+            cues.push(TranscriptCue {
+                start,
+                end,
+                speaker,
+                text,
+            });
+        }
+    }
+    cues
+}
+
+fn parse_cue_timing(line: &str) -> Option<(Duration, Duration)> {
+    let (start, rest) = line.split_once("-->")?;
+    let end = rest.split_whitespace().next()?;
+    Some((parse_timestamp(start.trim())?, parse_timestamp(end)?))
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<Duration> {
+    let (time, fraction) = match timestamp.rsplit_once(|ch| ch == ',' || ch == '.') {
+        Some((time, fraction)) => (time, fraction),
+        None => (timestamp, "0"),
+    };
+    let mut seconds = 0;
+    for part in time.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    let millis = format!("{:0<3}", fraction).get(..3)?.parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds) + Duration::from_millis(millis))
+}
+
+// Removes markup from the cue text. WebVTT voice spans (`<v Speaker>`) are used to determine
+// the speaker.
+fn strip_tags(line: &str) -> (Option<String>, String) {
+    let mut speaker = None;
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(tag_start) = rest.find('<') {
+        result.push_str(&rest[..tag_start]);
+        let tag_end = match rest[tag_start..].find('>') {
+            Some(tag_end) => tag_start + tag_end,
+            None => {
+                rest = &rest[tag_start..];
+                break;
+            }
+        };
+        let tag = &rest[tag_start + 1..tag_end];
+        if let Some(voice) = tag
+            .strip_prefix('v')
+            .filter(|voice| voice.starts_with(|ch: char| ch.is_whitespace() || ch == '.'))
+        {
+            let name = voice.split_once(char::is_whitespace).map(|(_, name)| name);
+            if let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) {
+                speaker = Some(name.to_string());
+            }
+        }
+        rest = &rest[tag_end + 1..];
+    }
+    result.push_str(rest);
+    (speaker, decode_entities(&result).trim().to_string())
+}
+
+// Entities are decoded in a single pass, so an escaped entity such as `&amp;lt;` is kept as
+// `&lt;`.
+fn decode_entities(text: &str) -> String {
+    const ENTITIES: [(&str, &str); 4] = [
+        ("&amp;", "&"),
+        ("&lt;", "<"),
+        ("&gt;", ">"),
+        ("&nbsp;", " "),
+    ];
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match ENTITIES.iter().find(|(entity, _)| rest.starts_with(entity)) {
+            Some((entity, value)) => {
+                result.push_str(value);
+                rest = &rest[entity.len()..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_transcript;
+    use crate::model::{TranscriptCue, TranscriptFormat};
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn cue(start: u64, end: u64, speaker: Option<&str>, text: &str) -> TranscriptCue {
+        TranscriptCue {
+            start: Duration::from_millis(start),
+            end: Duration::from_millis(end),
+            speaker: speaker.map(str::to_string),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_srt() {
+        let data = concat!(
+            "1\r\n00:00:00,000 --> 00:00:02,500\r\nHello and welcome\r\nto the show.\r\n\r\n",
+            "2\r\n00:01:02,040 --> 00:01:04,000\r\n<i>Music</i>\r\n",
+        );
+        let cues = parse_transcript(TranscriptFormat::Srt, data.as_bytes()).unwrap();
+        assert_eq!(
+            cues,
+            vec![
+                cue(0, 2500, None, "Hello and welcome to the show."),
+                cue(62040, 64000, None, "Music"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_webvtt() {
+        let data = concat!(
+            "WEBVTT\n\nNOTE This is a comment\n\n",
+            "intro\n00:05.000 --> 00:07.250 align:start\n<v Alice>Hi, I'm Alice.</v>\n\n",
+            "01:00:00.000 --> 01:00:01.000\n<v.loud Bob>Bye &amp; thanks! &amp;lt;3\n",
+        );
+        let cues = parse_transcript(TranscriptFormat::WebVtt, data.as_bytes()).unwrap();
+        assert_eq!(
+            cues,
+            vec![
+                cue(5000, 7250, Some("Alice"), "Hi, I'm Alice."),
+                cue(3600000, 3601000, Some("Bob"), "Bye & thanks! &lt;3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_json() {
+        let data = r#"{
+            "version": "1.0.0",
+            "segments": [
+                {"speaker": "Alice", "startTime": 1.5, "endTime": 3.0, "body": "Second"},
+                {"startTime": 0, "endTime": 1.5, "body": "First"},
+                {"startTime": 4, "body": " "}
+            ]
+        }"#;
+        let cues = parse_transcript(TranscriptFormat::Json, data.as_bytes()).unwrap();
+        assert_eq!(
+            cues,
+            vec![
+                cue(0, 1500, None, "First"),
+                cue(1500, 3000, Some("Alice"), "Second"),
+            ]
+        );
+    }
+}
@@ -3,6 +3,7 @@ use gstreamer_base::{glib, gst};
 use std::borrow::Cow;
 use std::error::Error;
 use std::fmt;
+use std::path::Path;
 
 #[derive(Debug)]
 pub enum GstError {
@@ -54,6 +55,12 @@ where
         .map_err(GstError::from_err)
 }
 
+pub fn file_uri(path: &Path) -> Result<String, GstError> {
+    glib::filename_to_uri(path, None)
+        .map(|uri| uri.to_string())
+        .map_err(GstError::from_err)
+}
+
 pub(crate) fn build_flags<'a>(
     type_name: &str,
     flags: impl IntoIterator<Item = &'a str>,
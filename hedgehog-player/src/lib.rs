@@ -1,5 +1,6 @@
 mod gst_utils;
 pub mod mpris;
+pub mod sleep;
 pub mod state;
 pub mod volume;
 
@@ -9,12 +10,23 @@ use cmdparse::{
     tokens::{Token, TokenStream},
     CompletionResult, Parsable, ParseResult, Parser,
 };
-pub use gst_utils::GstError;
 use gst_utils::{build_flags, get_property, set_property};
+pub use gst_utils::{file_uri, GstError};
 use gstreamer_base::{gst, gst::prelude::*, BaseParse};
+use sleep::SleepTimer;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use std::{str::FromStr, time::Duration};
 use volume::{Volume, VolumeCommand};
 
+// Loudness normalization: a soft-knee compressor followed by a limiter preventing clipping.
+const NORMALIZE_FILTER: &str =
+    "audiodynamic characteristics=soft-knee mode=compressor threshold=0.125 ratio=0.5 ! rglimiter";
+
+const FADE_DURATION: Duration = Duration::from_secs(5);
+const FADE_STEPS: u32 = 25;
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct State {
     pub(crate) is_started: bool,
@@ -23,11 +35,21 @@ pub struct State {
 }
 
 impl State {
+    pub fn is_started(&self) -> bool {
+        self.is_started
+    }
+
     fn is_playing(&self) -> bool {
         self.is_started && !self.is_paused && !self.is_buffering
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SleepDeadline {
+    At(Instant),
+    EndOfEpisode,
+}
+
 pub struct Player {
     element: Option<gst::Element>,
     subscribers: Vec<Recipient<PlayerNotification>>,
@@ -36,6 +58,18 @@ pub struct Player {
     required_seek: Option<Duration>,
     should_pause: bool,
     seek_position: Option<Duration>,
+    rate: f64,
+    outro: Duration,
+    audio_filter: String,
+    skip_silence: bool,
+    silence_start: Option<u64>,
+    silence_skipped: Duration,
+    gain_filter: Option<gst::Element>,
+    volume_offset: f64,
+    fade_filter: Option<gst::Element>,
+    fade_step: Option<u32>,
+    sleep_deadline: Option<SleepDeadline>,
+    credentials: Arc<Mutex<Option<SourceCredentials>>>,
 }
 
 impl Player {
@@ -49,7 +83,7 @@ impl Player {
         let flags = build_flags("GstPlayFlags", ["audio", "download"])?;
         set_property(&mut element, "flags", flags)?;
 
-        Ok(Player {
+        let mut player = Player {
             element: Some(element),
             reported_volume: None,
             subscribers: Vec::new(),
@@ -57,7 +91,23 @@ impl Player {
             required_seek: None,
             should_pause: false,
             seek_position: None,
-        })
+            rate: 1.0,
+            outro: Duration::ZERO,
+            audio_filter: String::new(),
+            skip_silence: false,
+            silence_start: None,
+            silence_skipped: Duration::ZERO,
+            gain_filter: None,
+            volume_offset: 0.0,
+            fade_filter: None,
+            fade_step: None,
+            sleep_deadline: None,
+            credentials: Arc::new(Mutex::new(None)),
+        };
+        if let Err(error) = player.configure_audio_filter("") {
+            log::error!(target: "player", "{}", error);
+        }
+        Ok(player)
     }
 
     pub fn init_uninitialized() -> Self {
@@ -69,6 +119,18 @@ impl Player {
             required_seek: None,
             should_pause: false,
             seek_position: None,
+            rate: 1.0,
+            outro: Duration::ZERO,
+            audio_filter: String::new(),
+            skip_silence: false,
+            silence_start: None,
+            silence_skipped: Duration::ZERO,
+            gain_filter: None,
+            volume_offset: 0.0,
+            fade_filter: None,
+            fade_step: None,
+            sleep_deadline: None,
+            credentials: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -77,6 +139,158 @@ impl Player {
         self.notify_subscribers(PlayerNotification::StateChanged(self.state));
     }
 
+    // removesilence only supports mono audio, so it is only inserted into the audio filter
+    // while silence is skipped.
+    fn set_skip_silence(&mut self, skip: bool) -> Result<(), GstError> {
+        if skip != self.skip_silence {
+            check_silence_removal(skip)?;
+            self.skip_silence = skip;
+            let chain = self.audio_filter.clone();
+            self.rebuild_pipeline(&chain)?;
+        }
+        Ok(())
+    }
+
+    // The volume offset is applied by a volume element of its own rather than by changing the
+    // playbin's volume, so that it does not interfere with the volume set by the user.
+    fn set_volume_offset(&mut self, offset: f64) -> Result<(), GstError> {
+        self.volume_offset = offset;
+        match self.gain_filter {
+            Some(ref mut filter) => {
+                set_property(filter, "volume", Volume::from_cubic(1.0 + offset))
+            }
+            None => Ok(()),
+        }
+    }
+
+    // The audio filter is a bin containing the silence removal element, if silence is skipped,
+    // followed by the chain configured by the user and the volume elements used for the volume
+    // offset and for fading out. playbin only accepts a new filter when it is stopped.
+    fn configure_audio_filter(&mut self, chain: &str) -> Result<(), GstError> {
+        let description = audio_filter_description(chain, self.skip_silence);
+        let bin =
+            gst::parse_bin_from_description(&description, true).map_err(GstError::from_err)?;
+        let gain_filter = bin.by_name("gain");
+        let fade_filter = bin.by_name("fade");
+        if let Some(ref mut element) = self.element {
+            set_property(element, "audio-filter", bin.upcast::<gst::Element>())?;
+        }
+        self.audio_filter = chain.to_string();
+        self.gain_filter = gain_filter;
+        self.fade_filter = fade_filter;
+        self.set_volume_offset(self.volume_offset)
+    }
+
+    // Replacing the audio filter requires restarting the stream, which then continues from the
+    // current position in the same state and at the same rate.
+    fn rebuild_pipeline(&mut self, chain: &str) -> Result<(), GstError> {
+        let (element, state) = match (&self.element, self.state) {
+            (Some(element), Some(state)) => (element, state),
+            _ => return self.configure_audio_filter(chain),
+        };
+        let position = if state.is_started {
+            element
+                .query_position::<gst::ClockTime>()
+                .map(|position| Duration::from_nanos(position.nseconds()))
+                .or(self.seek_position)
+        } else {
+            self.required_seek
+        };
+        element
+            .set_state(gst::State::Null)
+            .map_err(GstError::from_err)?;
+        let result = self.configure_audio_filter(chain);
+
+        self.set_state(Some(State {
+            is_paused: state.is_paused,
+            ..Default::default()
+        }));
+        let element = self.element.as_ref().unwrap();
+        element
+            .set_state(gst::State::Playing)
+            .map_err(GstError::from_err)?;
+        self.required_seek = position.filter(|position| !position.is_zero());
+        self.should_pause = state.is_paused;
+        self.seek_position = None;
+        self.silence_start = None;
+        self.cancel_fade();
+        result
+    }
+
+    fn pause(&mut self) -> Result<(), GstError> {
+        if let Some(state) = self.state {
+            if !state.is_paused {
+                self.set_state(Some(State {
+                    is_paused: true,
+                    ..state
+                }));
+                let element = self.element.as_ref().unwrap();
+                element
+                    .set_state(gst::State::Paused)
+                    .map_err(GstError::from_err)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn end_of_stream(&mut self) {
+        self.notify_subscribers(PlayerNotification::Eos);
+        self.set_state(None);
+        if self.sleep_deadline == Some(SleepDeadline::EndOfEpisode) {
+            self.set_sleep_timer(SleepTimer::Off);
+        }
+    }
+
+    fn sleep_timer(&self) -> SleepTimer {
+        match self.sleep_deadline {
+            Some(SleepDeadline::At(deadline)) => {
+                SleepTimer::After(deadline.saturating_duration_since(Instant::now()))
+            }
+            Some(SleepDeadline::EndOfEpisode) => SleepTimer::EndOfEpisode,
+            None => SleepTimer::Off,
+        }
+    }
+
+    fn set_sleep_timer(&mut self, timer: SleepTimer) {
+        self.sleep_deadline = match timer {
+            SleepTimer::After(duration) => Some(SleepDeadline::At(Instant::now() + duration)),
+            SleepTimer::EndOfEpisode => Some(SleepDeadline::EndOfEpisode),
+            SleepTimer::Off => None,
+        };
+        self.notify_subscribers(PlayerNotification::SleepTimer(timer));
+    }
+
+    // The volume of the fade element is restored whenever the fading is interrupted or
+    // finished, so that it does not affect the volume controlled by the user.
+    fn cancel_fade(&mut self) {
+        if self.fade_step.take().is_some() {
+            if let Some(ref mut fade_filter) = self.fade_filter {
+                if let Err(error) = set_property(fade_filter, "volume", Volume::FULL) {
+                    log::error!(target: "player", "{}", error);
+                }
+            }
+        }
+    }
+
+    // The outro is skipped by ending the playback as if the stream ended once the position
+    // reaches it.
+    fn is_outro(&self, position: Duration) -> bool {
+        if self.outro.is_zero() {
+            return false;
+        }
+        let duration = self
+            .element
+            .as_ref()
+            .and_then(|element| element.query_duration::<gst::ClockTime>());
+        match duration {
+            Some(duration) => {
+                let duration = Duration::from_nanos(duration.nseconds());
+                duration > self.outro && position + self.outro >= duration
+            }
+            None => false,
+        }
+    }
+
     fn notify_subscribers(&mut self, notification: PlayerNotification) {
         for subscriber in &self.subscribers {
             if let Err(error) = subscriber.do_send(notification.clone()) {
@@ -119,6 +333,25 @@ impl Actor for Player {
             handle_volume_changed(element, &addr);
         });
 
+        // The source element is created by playbin for each URI, so credentials have to be set
+        // whenever it is being configured.
+        let credentials = Arc::clone(&self.credentials);
+        let result = element.connect("source-setup", false, move |values| {
+            let source = values
+                .get(1)
+                .and_then(|value| value.get::<gst::Element>().ok());
+            let credentials = credentials.lock().unwrap();
+            if let (Some(source), Some(credentials)) = (source, credentials.as_ref()) {
+                if let Err(error) = set_source_credentials(&source, credentials) {
+                    log::error!(target: "player", "{}", error);
+                }
+            }
+            None
+        });
+        if let Err(error) = result {
+            log::error!(target: "player", "{}", error);
+        }
+
         if let Some(bus) = element.bus() {
             ctx.add_stream(bus.stream_filtered(&[
                 gst::MessageType::Eos,
@@ -126,6 +359,7 @@ impl Actor for Player {
                 gst::MessageType::Buffering,
                 gst::MessageType::StateChanged,
                 gst::MessageType::DurationChanged,
+                gst::MessageType::Element,
             ]));
         } else {
             log::error!(target: "player", "Element does not have a bus");
@@ -250,6 +484,96 @@ pub struct PlaybackMetadata {
     pub feed_title: Option<String>,
 }
 
+// removesilence is a part of gst-plugins-bad, so the player works without it and only reports an
+// error when skipping silence is requested.
+fn check_silence_removal(skip: bool) -> Result<(), GstError> {
+    if skip && gst::ElementFactory::find("removesilence").is_none() {
+        return Err(GstError::from_str(
+            "Silence cannot be skipped as the removesilence element is not available",
+        ));
+    }
+    Ok(())
+}
+
+fn audio_filter_description(chain: &str, skip_silence: bool) -> String {
+    let mut elements = Vec::new();
+    if skip_silence {
+        elements.push("removesilence remove=true silent=false");
+    }
+    match chain.trim() {
+        "" => {}
+        "normalize" => elements.push(NORMALIZE_FILTER),
+        chain => elements.push(chain),
+    }
+    elements.push("volume name=gain");
+    elements.push("volume name=fade");
+    format!(
+        "audioconvert ! {} ! audioconvert",
+        elements.join(" ! audioconvert ! ")
+    )
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SkippedParts {
+    pub intro: Duration,
+    pub outro: Duration,
+    pub silence: bool,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct SourceCredentials {
+    pub username: String,
+    pub password: Option<String>,
+    pub host: String,
+}
+
+impl fmt::Debug for SourceCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourceCredentials")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("host", &self.host)
+            .finish()
+    }
+}
+
+// Returns the host of a URI like "https://user@example.com:8080/path".
+fn uri_host(uri: &str) -> Option<&str> {
+    let (_, rest) = uri.split_once("://")?;
+    let authority = rest.split(|ch| matches!(ch, '/' | '?' | '#')).next()?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next(),
+        None => host.split(':').next(),
+    }
+}
+
+fn set_source_credentials(
+    source: &gst::Element,
+    credentials: &SourceCredentials,
+) -> Result<(), GstError> {
+    if source.find_property("user-id").is_none() {
+        return Ok(());
+    }
+    // Episodes are often served from CDNs which must not receive the feed's credentials.
+    let location: Option<String> = get_property(source, "location")?;
+    let host = location.as_deref().and_then(uri_host);
+    if !host.map_or(false, |host| host.eq_ignore_ascii_case(&credentials.host)) {
+        return Ok(());
+    }
+    source
+        .set_property("user-id", &credentials.username)
+        .map_err(GstError::from_err)?;
+    if let Some(password) = &credentials.password {
+        source
+            .set_property("user-pw", password)
+            .map_err(GstError::from_err)?;
+    }
+    Ok(())
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum InitialPlaybackState {
     Playing,
@@ -265,6 +589,8 @@ pub enum PlaybackCommand {
         #[cmd(parser = "DurationParser")] Duration,
         Option<PlaybackMetadata>,
         InitialPlaybackState,
+        Option<SourceCredentials>,
+        SkippedParts,
     ),
     Stop,
     Pause,
@@ -276,6 +602,13 @@ pub enum PlaybackCommand {
     SeekRelative(SeekOffset),
     #[cmd(rename = "rate")]
     SetRate(f64),
+    #[cmd(ignore)]
+    SetSkipSilence(bool),
+    #[cmd(ignore)]
+    SetVolumeOffset(f64),
+    #[cmd(ignore)]
+    SetAudioFilter(String),
+    Sleep(SleepTimer),
 }
 
 impl Handler<PlaybackCommand> for Player {
@@ -287,7 +620,14 @@ impl Handler<PlaybackCommand> for Player {
         }
         let result: Result<(), GstError> = (|| {
             match msg {
-                PlaybackCommand::Play(url, position, metadata, initial_state) => {
+                PlaybackCommand::Play(
+                    url,
+                    position,
+                    metadata,
+                    initial_state,
+                    credentials,
+                    skipped_parts,
+                ) => {
                     let state = State {
                         is_paused: matches!(initial_state, InitialPlaybackState::Paused),
                         ..Default::default()
@@ -298,24 +638,48 @@ impl Handler<PlaybackCommand> for Player {
                     element
                         .set_state(gst::State::Null)
                         .map_err(GstError::from_err)?;
+                    *self.credentials.lock().unwrap() = credentials;
                     element
                         .set_property("uri", url)
                         .map_err(GstError::from_err)?;
+                    // The stream is stopped, so the audio filter is replaced without restarting it.
+                    if skipped_parts.silence != self.skip_silence {
+                        let result = check_silence_removal(skipped_parts.silence).and_then(|_| {
+                            self.skip_silence = skipped_parts.silence;
+                            let chain = self.audio_filter.clone();
+                            self.configure_audio_filter(&chain)
+                        });
+                        if let Err(error) = result {
+                            log::error!(target: "player", "{}", error);
+                        }
+                    }
+                    let element = self.element.as_ref().unwrap();
                     element
                         .set_state(gst::State::Playing)
                         .map_err(GstError::from_err)?;
+                    // The intro is only skipped if the playback would start within it.
+                    let position = position.max(skipped_parts.intro);
                     self.required_seek = if position.is_zero() {
                         None
                     } else {
                         Some(position)
                     };
+                    self.outro = skipped_parts.outro;
+                    self.silence_start = None;
+                    self.silence_skipped = Duration::ZERO;
+                    self.cancel_fade();
                     self.should_pause = matches!(initial_state, InitialPlaybackState::Paused);
                     self.seek_position = None;
+                    if self.rate != 1.0 {
+                        self.rate = 1.0;
+                        self.notify_subscribers(PlayerNotification::RateChanged(self.rate));
+                    }
                     if let Some(metadata) = metadata {
                         self.notify_subscribers(PlayerNotification::MetadataChanged(metadata));
                     }
                 }
                 PlaybackCommand::Stop => {
+                    self.cancel_fade();
                     if self.state.is_some() {
                         self.set_state(None);
                         let element = self.element.as_ref().unwrap();
@@ -325,18 +689,8 @@ impl Handler<PlaybackCommand> for Player {
                     }
                 }
                 PlaybackCommand::Pause => {
-                    if let Some(state) = self.state {
-                        if !state.is_paused {
-                            self.set_state(Some(State {
-                                is_paused: true,
-                                ..state
-                            }));
-                            let element = self.element.as_ref().unwrap();
-                            element
-                                .set_state(gst::State::Paused)
-                                .map_err(GstError::from_err)?;
-                        }
-                    }
+                    self.cancel_fade();
+                    self.pause()?;
                 }
                 PlaybackCommand::Resume => {
                     if let Some(state) = self.state {
@@ -434,9 +788,22 @@ impl Handler<PlaybackCommand> for Player {
                                     gst::ClockTime::NONE,
                                 )
                                 .map_err(GstError::from_err)?;
+                            self.rate = speed;
+                            self.notify_subscribers(PlayerNotification::RateChanged(speed));
                         }
                     }
                 }
+                PlaybackCommand::SetSkipSilence(skip) => self.set_skip_silence(skip)?,
+                PlaybackCommand::SetVolumeOffset(offset) => self.set_volume_offset(offset)?,
+                PlaybackCommand::Sleep(timer) => {
+                    self.cancel_fade();
+                    self.set_sleep_timer(timer);
+                }
+                PlaybackCommand::SetAudioFilter(chain) => {
+                    if chain != self.audio_filter {
+                        self.rebuild_pipeline(&chain)?;
+                    }
+                }
             }
             Ok(())
         })();
@@ -530,7 +897,14 @@ pub enum PlayerNotification {
     VolumeChanged(Option<Volume>),
     StateChanged(Option<State>),
     DurationSet(Duration),
-    PositionSet { position: Duration, seeked: bool },
+    PositionSet {
+        position: Duration,
+        seeked: bool,
+    },
+    RateChanged(f64),
+    /// The total duration of silence removed from the current episode.
+    SilenceSkipped(Duration),
+    SleepTimer(SleepTimer),
     Eos,
     Failure,
 }
@@ -539,10 +913,7 @@ impl StreamHandler<gst::Message> for Player {
     fn handle(&mut self, item: gst::Message, ctx: &mut Self::Context) {
         if let Some(state) = self.state {
             match item.view() {
-                gst::MessageView::Eos(_) => {
-                    self.notify_subscribers(PlayerNotification::Eos);
-                    self.set_state(None);
-                }
+                gst::MessageView::Eos(_) => self.end_of_stream(),
                 gst::MessageView::Error(error) => {
                     log::error!(target: "playback", "{}", error.error());
                     if let Some(ref element) = self.element {
@@ -579,6 +950,11 @@ impl StreamHandler<gst::Message> for Player {
                             if let Some(seek) = self.required_seek.take() {
                                 ctx.address().do_send(PlaybackCommand::Seek(seek));
                             }
+                            // The rate is only kept when the pipeline is rebuilt, new episodes
+                            // start at the normal rate.
+                            if self.rate != 1.0 {
+                                ctx.address().do_send(PlaybackCommand::SetRate(self.rate));
+                            }
                             if self.should_pause {
                                 if let Some(ref element) = self.element {
                                     if let Err(error) = element.set_state(gst::State::Paused) {
@@ -590,6 +966,26 @@ impl StreamHandler<gst::Message> for Player {
                         }
                     }
                 }
+                gst::MessageView::Element(element) => {
+                    let structure = match element.structure() {
+                        Some(structure)
+                            if self.skip_silence && structure.name() == "removesilence" =>
+                        {
+                            structure
+                        }
+                        _ => return,
+                    };
+                    if let Ok(start) = structure.get::<u64>("silence_detected") {
+                        self.silence_start = Some(start);
+                    } else if let Ok(end) = structure.get::<u64>("silence_finished") {
+                        if let Some(start) = self.silence_start.take() {
+                            self.silence_skipped += Duration::from_nanos(end.saturating_sub(start));
+                            self.notify_subscribers(PlayerNotification::SilenceSkipped(
+                                self.silence_skipped,
+                            ));
+                        }
+                    }
+                }
                 gst::MessageView::DurationChanged(duration_changed) => {
                     let clock_time = duration_changed.src().and_then(|src| {
                         src.downcast_ref::<BaseParse>()?
@@ -625,7 +1021,25 @@ impl Handler<TimerTick> for Player {
                     position,
                     seeked: false,
                 });
+                if self.is_outro(position) {
+                    if let Some(ref element) = self.element {
+                        if let Err(error) = element.set_state(gst::State::Null) {
+                            log::error!(target: "player", "{}", error);
+                        }
+                    }
+                    self.end_of_stream();
+                }
+            }
+        }
+        if let Some(SleepDeadline::At(deadline)) = self.sleep_deadline {
+            if deadline <= Instant::now() {
+                self.sleep_deadline = None;
+                if let Some(true) = self.state.as_ref().map(State::is_playing) {
+                    self.fade_step = Some(0);
+                    ctx.address().do_send(FadeTick(0));
+                }
             }
+            self.notify_subscribers(PlayerNotification::SleepTimer(self.sleep_timer()));
         }
         ctx.spawn(
             actix::clock::sleep(Duration::from_secs(1))
@@ -634,3 +1048,47 @@ impl Handler<TimerTick> for Player {
         );
     }
 }
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct FadeTick(u32);
+
+// When the sleep timer expires, the playback fades out and pauses. The position is reported
+// before pausing so that the listener can continue from where the playback ended.
+impl Handler<FadeTick> for Player {
+    type Result = ();
+
+    fn handle(&mut self, FadeTick(step): FadeTick, ctx: &mut Self::Context) -> Self::Result {
+        if self.fade_step != Some(step) {
+            return;
+        }
+        if step < FADE_STEPS {
+            let volume = Volume::from_cubic(1.0 - (step + 1) as f64 / FADE_STEPS as f64);
+            if let Some(ref mut fade_filter) = self.fade_filter {
+                if let Err(error) = set_property(fade_filter, "volume", volume) {
+                    log::error!(target: "player", "{}", error);
+                }
+            }
+            self.fade_step = Some(step + 1);
+            ctx.run_later(FADE_DURATION / FADE_STEPS, move |_, ctx| {
+                ctx.address().do_send(FadeTick(step + 1))
+            });
+            return;
+        }
+
+        let position = self
+            .element
+            .as_ref()
+            .and_then(|element| element.query_position::<gst::ClockTime>());
+        if let Some(position) = position {
+            self.notify_subscribers(PlayerNotification::PositionSet {
+                position: Duration::from_nanos(position.nseconds()),
+                seeked: false,
+            });
+        }
+        if let Err(error) = self.pause() {
+            log::error!(target: "player", "{}", error);
+        }
+        self.cancel_fade();
+    }
+}
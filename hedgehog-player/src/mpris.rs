@@ -1,5 +1,6 @@
 #![cfg(feature = "mpris")]
 
+use crate::sleep::SleepTimer;
 use crate::state::PlaybackState;
 use crate::volume::Volume;
 use crate::{
@@ -28,6 +29,7 @@ type PropChangeCallback =
 struct PlayerState {
     state: PlaybackState,
     metadata: Option<PlaybackMetadata>,
+    sleep_timer: SleepTimer,
 }
 
 impl PlayerState {
@@ -70,6 +72,23 @@ impl PlayerState {
                 )),
             );
         }
+        // MPRIS has no notion of a sleep timer, so it is published under custom keys in the
+        // `hedgehog:` namespace, as described in the README.
+        match self.sleep_timer {
+            SleepTimer::After(remaining) => {
+                metadata.insert(
+                    "hedgehog:sleepTimer".to_string(),
+                    Variant(Box::new(remaining.as_micros() as i64)),
+                );
+            }
+            SleepTimer::EndOfEpisode => {
+                metadata.insert(
+                    "hedgehog:sleepAtEndOfEpisode".to_string(),
+                    Variant(Box::new(true)),
+                );
+            }
+            SleepTimer::Off => {}
+        }
         metadata
     }
 }
@@ -440,7 +459,33 @@ impl Handler<PlayerNotification> for MprisPlayer {
                         }
                     }
                 }
-                PlayerNotification::Eos | PlayerNotification::Failure => {}
+                PlayerNotification::SleepTimer(sleep_timer) => {
+                    // The player reports the timer on every tick, while the metadata only changes
+                    // when the remaining time does in whole minutes.
+                    let sleep_timer = match sleep_timer {
+                        SleepTimer::After(remaining) => SleepTimer::After(Duration::from_secs(
+                            (remaining.as_secs() + 59) / 60 * 60,
+                        )),
+                        sleep_timer => sleep_timer,
+                    };
+                    if let Ok(mut guard) = self.playback_state.write() {
+                        if guard.sleep_timer != sleep_timer {
+                            guard.sleep_timer = sleep_timer;
+
+                            let message = (callbacks.metadata_changed)(
+                                &dbus::Path::from("/org/mpris/MediaPlayer2").into_static(),
+                                &guard.construct_mpris_metadata(),
+                            );
+                            if let Some(message) = message {
+                                let _ = connection.send(message);
+                            }
+                        }
+                    }
+                }
+                PlayerNotification::RateChanged(_)
+                | PlayerNotification::SilenceSkipped(_)
+                | PlayerNotification::Eos
+                | PlayerNotification::Failure => {}
             }
         }
     }
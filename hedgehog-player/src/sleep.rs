@@ -0,0 +1,11 @@
+use crate::DurationParser;
+use std::time::Duration;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, cmdparse::Parsable)]
+pub enum SleepTimer {
+    #[cmd(transparent_no_error)]
+    After(#[cmd(parser = "DurationParser")] Duration),
+    EndOfEpisode,
+    #[default]
+    Off,
+}
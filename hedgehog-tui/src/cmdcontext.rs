@@ -1,9 +1,9 @@
 use cmdparse::{tokens::Token, CompletionResult, Parsable, Parser};
-use hedgehog_library::model::{FeedSummary, FeedView, GroupSummary};
+use hedgehog_library::model::{FeedSummary, FeedView, GroupSummary, SavedViewSummary};
 
 #[derive(Clone)]
 pub(crate) struct CommandContext<'a> {
-    pub(crate) feeds: &'a [FeedView<FeedSummary, GroupSummary>],
+    pub(crate) feeds: &'a [FeedView<FeedSummary, GroupSummary, SavedViewSummary>],
 }
 
 #[derive(Default)]
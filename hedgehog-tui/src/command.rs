@@ -5,7 +5,7 @@ use crate::options::OptionsUpdate;
 use crate::scrolling::ScrollAction;
 use crate::theming::ThemeCommand;
 use cmdparse::Parsable;
-use hedgehog_library::model::{EpisodeStatus, EpisodeSummaryStatus};
+use hedgehog_library::model::{EpisodeStatus, EpisodeSummaryStatus, EpisodesSortKey, FeedPolicy};
 use hedgehog_player::volume::VolumeCommand;
 use hedgehog_player::PlaybackCommand;
 use std::path::PathBuf;
@@ -23,8 +23,10 @@ pub(crate) enum Command {
     #[cmd(transparent)]
     Volume(VolumeCommand),
     PlayCurrent,
+    Next,
     #[cmd(transparent)]
     Playback(PlaybackCommand),
+    Chapter(ChapterCommand),
     Finish,
     #[cmd(alias = "enable", alias = "disable")]
     SetFeedEnabled(
@@ -41,19 +43,29 @@ pub(crate) enum Command {
     #[cmd(rename = "set")]
     SetOption(OptionsUpdate),
     #[cmd(rename = "add")]
-    AddFeed(String),
+    AddFeed {
+        source: String,
+        #[cmd(attr(user))]
+        username: Option<String>,
+        #[cmd(attr(password_cmd))]
+        password_command: Option<String>,
+    },
     AddGroup(#[cmd(parser = "crate::cmdcontext::GroupNameParser")] String),
     SetGroup(#[cmd(parser = "crate::cmdcontext::GroupNameParser")] String),
     UnsetGroup,
     PlaceGroup(usize),
+    View(ViewCommand),
     #[cmd(alias = "delete-feed")]
     Delete,
     Reverse,
+    Sort(EpisodesSortKey),
     Rename(#[cmd(parser = "hedgehog_library::search::SearchQueryParser")] String),
     #[cmd(alias = "u")]
     Update {
         #[cmd(attr(this = "true"))]
         current_only: bool,
+        #[cmd(attr(failed = "true"))]
+        failed_only: bool,
     },
     AddArchive(String),
     Mark {
@@ -71,9 +83,34 @@ pub(crate) enum Command {
         )]
         bool,
     ),
+    Download,
+    CancelDownload,
+    QueueAdd,
+    QueueRemove,
+    QueueMove(usize),
+    QueueClear,
+    Stats {
+        #[cmd(attr(days))]
+        days: Option<u32>,
+    },
+    SetFeedPolicy(FeedPolicy),
+    SetFeedRate(f64),
+    SetFeedVolume(f64),
+    SetFeedSkipSilence(Option<bool>),
+    MarkIntro {
+        #[cmd(attr(clear = "true"))]
+        clear: bool,
+    },
+    MarkOutro {
+        #[cmd(attr(clear = "true"))]
+        clear: bool,
+    },
+    Bookmark(Option<String>),
+    BookmarkDelete,
     #[cmd(alias = "s")]
     Search(#[cmd(parser = "hedgehog_library::search::SearchQueryParser")] String),
     SearchAdd,
+    Find(#[cmd(parser = "hedgehog_library::search::SearchQueryParser")] String),
     OpenLink(LinkType),
 
     RepeatCommand,
@@ -102,6 +139,36 @@ pub(crate) enum Command {
     },
 }
 
+#[derive(Debug, Clone, PartialEq, Parsable)]
+pub(crate) enum ViewCommand {
+    Add {
+        name: String,
+        #[cmd(attr(status))]
+        status: Option<EpisodeSummaryStatus>,
+        #[cmd(attr(group))]
+        group: Option<String>,
+        #[cmd(attr(longer))]
+        min_minutes: Option<u64>,
+        #[cmd(attr(shorter))]
+        max_minutes: Option<u64>,
+        #[cmd(attr(newer))]
+        newer_than_days: Option<u32>,
+        #[cmd(attr(older))]
+        older_than_days: Option<u32>,
+        #[cmd(attr(text))]
+        text: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Parsable)]
+pub(crate) enum ChapterCommand {
+    Next,
+    #[cmd(alias = "previous")]
+    Prev,
+    #[cmd(transparent_no_error)]
+    Go(usize),
+}
+
 #[derive(Debug, Clone, PartialEq, Parsable)]
 pub(crate) enum LinkType {
     Feed,
@@ -126,6 +193,10 @@ pub(crate) enum FocusedPane {
     Search,
     #[cmd(rename = "log")]
     ErrorsLog,
+    Transcript,
+    Queue,
+    Stats,
+    Bookmarks,
 }
 
 #[derive(Debug, Clone, Copy, Parsable, PartialEq, Eq)]
@@ -133,9 +204,12 @@ pub(crate) enum SelectedItem {
     SpecialFeed,
     Feed,
     Group,
+    View,
     Episode,
     LogEntry,
     SearchResult,
+    TranscriptCue,
+    Bookmark,
     Nothing,
 }
 
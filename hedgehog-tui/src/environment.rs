@@ -21,6 +21,18 @@ impl AppEnvironment {
         path
     }
 
+    pub(crate) fn transcripts_path(&self) -> PathBuf {
+        let mut path = self.data_path.to_path_buf();
+        path.push("transcripts");
+        path
+    }
+
+    pub(crate) fn downloads_path(&self) -> PathBuf {
+        let mut path = self.data_path.to_path_buf();
+        path.push("downloads");
+        path
+    }
+
     pub(crate) fn resolve_config<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
         if path.is_absolute() || path.exists() {
             return path.into();
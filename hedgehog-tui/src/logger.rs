@@ -62,6 +62,7 @@ enum LogTarget {
     Io,
     Networking,
     Browser,
+    Update,
     InternalLogControlMessage,
 }
 
@@ -80,6 +81,7 @@ impl LogTarget {
             "io" => LogTarget::Io,
             "networking" => LogTarget::Networking,
             "browser" => LogTarget::Browser,
+            "update" => LogTarget::Update,
             "__logger_ctl" => LogTarget::InternalLogControlMessage,
             _ => LogTarget::Default,
         }
@@ -106,7 +108,7 @@ impl LogEntry {
 
     pub(crate) fn display_ttl(&self) -> Option<Duration> {
         match self.target {
-            LogTarget::Playback => Some(TTL_LONG),
+            LogTarget::Playback | LogTarget::Update => Some(TTL_LONG),
             LogTarget::Browser => Some(TTL_MEDIUM),
             LogTarget::KeyMapping | LogTarget::Volume => Some(TTL_SHORT),
             _ => None,
@@ -132,6 +134,7 @@ impl LogEntry {
             LogTarget::Io => Some("I/O error"),
             LogTarget::Networking => Some("Network error"),
             LogTarget::Browser => None,
+            LogTarget::Update => None,
             LogTarget::InternalLogControlMessage => unreachable!(),
         }
     }
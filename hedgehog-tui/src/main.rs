@@ -26,11 +26,14 @@ use crossterm::terminal::{
 use directories::BaseDirs;
 use environment::AppEnvironment;
 use hedgehog_library::datasource::DataProvider;
+use hedgehog_library::downloads::DownloadManager;
+use hedgehog_library::network::HttpClient;
 use hedgehog_library::opml::LineEndingTransformer;
 use hedgehog_library::status_writer::StatusWriter;
 use hedgehog_library::{opml, InMemoryCache, Library, SqliteDataProvider};
 use hedgehog_player::Player;
 use logger::ActorLogger;
+use options::Options;
 use screen::UI;
 use std::env;
 use std::fmt;
@@ -261,6 +264,7 @@ fn run_player(
 
     let system = System::new();
     Player::initialize()?;
+    let http_client = HttpClient::new(Options::default().network_config())?;
 
     enable_raw_mode()?;
     execute!(io::stdout(), EnterAlternateScreen, DisableLineWrap)?;
@@ -275,14 +279,28 @@ fn run_player(
 
     system.block_on(async {
         let library_arbiter = Arbiter::new();
+        let transcripts_path = env.transcripts_path();
+        let library_http_client = http_client.clone();
         let library = Library::start_in_arbiter(&library_arbiter.handle(), |_| {
             Library::new(InMemoryCache::new(data_provider))
+                .set_transcripts_path(transcripts_path)
+                .set_http_client(library_http_client)
         });
 
         let status_writer = StatusWriter::new(library.clone())
             .set_playing_path(env.playing_id_path())
             .start();
 
+        let downloads_arbiter = Arbiter::new();
+        let downloads_path = env.downloads_path();
+        let downloads_library = library.clone();
+        let downloads_http_client = http_client.clone();
+        let download_manager =
+            DownloadManager::start_in_arbiter(&downloads_arbiter.handle(), |_| {
+                DownloadManager::new(downloads_library, downloads_path)
+                    .set_http_client(downloads_http_client)
+            });
+
         let player_arbiter = Arbiter::new();
         let player = Player::start_in_arbiter(&player_arbiter.handle(), |_| match Player::init() {
             Ok(player) => player,
@@ -295,6 +313,8 @@ fn run_player(
             library,
             player.clone(),
             status_writer,
+            download_manager,
+            http_client,
             env,
         )
         .start();
@@ -1,3 +1,6 @@
+use hedgehog_library::network::NetworkConfig;
+use std::time::Duration;
+
 macro_rules! gen_options {
     ($($(#$attr:tt)* $command:ident($(#$arg_attr:tt)* $name:ident: $value:ty = $default:expr)),*$(,)?) => {
         pub(crate) struct Options {
@@ -39,6 +42,9 @@ gen_options! {
     LabelEpisodeStarted(label_episode_started: String = " started ".to_string()),
     LabelEpisodeFinished(label_episode_finished: String = " finished ".to_string()),
     LabelEpisodeError(label_episode_error: String = " error ".to_string()),
+    LabelDownloadQueued(label_download_queued: String = " queued ".to_string()),
+    LabelDownloadCompleted(label_download_completed: String = " offline ".to_string()),
+    LabelDownloadFailed(label_download_failed: String = " failed ".to_string()),
     LabelFeedError(label_feed_error: String = "E".to_string()),
     FeedUpdatingChars(
         #[cmd(parser = "cmdparse::parsers::TransformParser<cmdparse::parsers::StringParser, CharVecTransformation, Vec<char>>")]
@@ -46,6 +52,16 @@ gen_options! {
     ),
     AnimationTickDuration(animation_tick_duration: u64 = 150),
     UpdateOnStart(update_on_start: bool = true),
+    UpdateInterval(update_interval: u64 = 0),
+    Proxy(proxy: String = "".to_string()),
+    UserAgent(user_agent: String = concat!("Hedgehog/", env!("CARGO_PKG_VERSION")).to_string()),
+    ConnectTimeout(connect_timeout: u64 = 30),
+    ReadTimeout(read_timeout: u64 = 300),
+    MaxFetches(max_fetches: usize = 8),
+    MaxHostFetches(max_host_fetches: usize = 4),
+    OnFinish(on_finish: OnFinishPolicy = OnFinishPolicy::Stop),
+    SkipSilence(skip_silence: bool = false),
+    AudioFilter(audio_filter: String = "".to_string()),
     ShowEpisodeNumber(show_episode_number: bool = true),
     Hidden(hidden: bool = false),
     ProgressBarWidth(progress_bar_width: u16 = 32),
@@ -55,10 +71,46 @@ gen_options! {
     ),
 }
 
+// What to play when an episode finishes and the queue is empty.
+#[derive(Debug, Clone, Copy, PartialEq, cmdparse::Parsable)]
+pub(crate) enum OnFinishPolicy {
+    Stop,
+    NextInFeed,
+    NextInView,
+    NextNew,
+}
+
 impl OptionsUpdate {
     pub(crate) fn affects_episodes_list(&self) -> bool {
         matches!(self, OptionsUpdate::Hidden(_))
     }
+
+    pub(crate) fn affects_network(&self) -> bool {
+        matches!(
+            self,
+            OptionsUpdate::Proxy(_)
+                | OptionsUpdate::UserAgent(_)
+                | OptionsUpdate::ConnectTimeout(_)
+                | OptionsUpdate::ReadTimeout(_)
+                | OptionsUpdate::MaxFetches(_)
+                | OptionsUpdate::MaxHostFetches(_)
+        )
+    }
+}
+
+impl Options {
+    // Empty strings and zeros stand for the defaults of the HTTP client.
+    pub(crate) fn network_config(&self) -> NetworkConfig {
+        let seconds = |seconds| Some(Duration::from_secs(seconds)).filter(|d| !d.is_zero());
+        NetworkConfig {
+            proxy: Some(self.proxy.clone()).filter(|proxy| !proxy.is_empty()),
+            user_agent: Some(self.user_agent.clone()).filter(|agent| !agent.is_empty()),
+            connect_timeout: seconds(self.connect_timeout),
+            read_timeout: seconds(self.read_timeout),
+            max_fetches: self.max_fetches,
+            max_host_fetches: Some(self.max_host_fetches).filter(|max| *max > 0),
+        }
+    }
 }
 
 struct CharVecTransformation;
@@ -1,69 +1,115 @@
 use crate::cmdcontext::CommandContext;
 use crate::cmdreader::CommandReader;
 use crate::command::{
-    Command, CommandConfirmation, FocusedPane, LinkType, Predicate, SelectedItem,
+    ChapterCommand, Command, CommandConfirmation, FocusedPane, LinkType, Predicate, SelectedItem,
+    ViewCommand,
 };
 use crate::events::key;
 use crate::history::CommandsHistory;
 use crate::keymap::KeyMapping;
 use crate::logger::{log_set_level, LogEntry, LogHistory, Severity};
 use crate::mouse::{MouseEventKind, MouseHitResult, MouseState, WidgetPositions};
-use crate::options::Options;
+use crate::options::{OnFinishPolicy, Options, OptionsUpdate};
 use crate::scrolling::pagination::{DataProvider, PaginatedData};
 use crate::scrolling::{selection, DataView, ScrollAction, ScrollableList};
 use crate::theming::Theme;
 use crate::widgets::animation::AnimationController;
+use crate::widgets::bookmarks::BookmarksWidget;
 use crate::widgets::command::{CommandActionResult, CommandEditor, CommandState};
 use crate::widgets::confirmation::ConfirmationView;
 use crate::widgets::errors_log::ErrorsLogWidget;
 use crate::widgets::library::LibraryWidget;
 use crate::widgets::player_state::PlayerState;
+use crate::widgets::queue::QueueWidget;
 use crate::widgets::search_results::SearchResults;
 use crate::widgets::split_bottom;
+use crate::widgets::stats::StatsWidget;
+use crate::widgets::stats_row::StatsRow;
 use crate::widgets::status::LogEntryView;
+use crate::widgets::transcript::TranscriptWidget;
 use actix::clock::sleep;
 use actix::fut::wrap_future;
 use actix::prelude::*;
+use chrono::{DateTime, Local, Utc};
 use crossterm::event::{self, Event};
 use crossterm::QueueableCommand;
+use hedgehog_library::downloads::{DownloadManager, DownloadNotification, DownloadRequest};
 use hedgehog_library::model::{
-    Episode, EpisodeId, EpisodePlaybackData, EpisodeSummary, EpisodeSummaryStatus,
-    EpisodesListMetadata, Feed, FeedId, FeedSummary, FeedView, GroupId, GroupSummary, Identifiable,
+    Bookmark, Chapter, Episode, EpisodeId, EpisodePlaybackData, EpisodeSummary,
+    EpisodeSummaryStatus, EpisodesFilter, EpisodesListMetadata, EpisodesSortKey, Feed,
+    FeedCredentials, FeedId, FeedSummary, FeedView, GroupId, GroupSummary, Identifiable,
+    ListeningSession, ListeningStats, SavedViewId, SavedViewSummary, TranscriptCue,
 };
+use hedgehog_library::network::HttpClient;
 use hedgehog_library::search::{self, SearchClient, SearchResult};
 use hedgehog_library::status_writer::{self, StatusWriter, StatusWriterCommand};
 use hedgehog_library::{
-    EpisodePlaybackDataRequest, EpisodeSummariesRequest, EpisodesListMetadataRequest,
-    EpisodesQuery, FeedSummariesRequest, FeedSummariesResponse, FeedUpdateNotification,
-    FeedUpdateRequest, FeedUpdateResult, Library, NewFeedMetadata, UpdateQuery,
+    BookmarksRequest, EpisodeChaptersRequest, EpisodePlaybackDataRequest, EpisodeSummariesRequest,
+    EpisodeTranscriptRequest, EpisodesListMetadataRequest, EpisodesQuery, FeedSummariesRequest,
+    FeedSummariesResponse, FeedUpdateNotification, FeedUpdateRequest, FeedUpdateResult, Library,
+    ListeningStatsRequest, NewFeedMetadata, NextEpisodeRequest, QueueRequest, UpdateQuery,
 };
-use hedgehog_player::state::PlaybackState;
+use hedgehog_player::sleep::SleepTimer;
+use hedgehog_player::state::{PlaybackState, PlaybackStatus};
 use hedgehog_player::{
     InitialPlaybackState, PlaybackCommand, PlaybackMetadata, Player, PlayerNotification,
-    SeekDirection, SeekOffset,
+    SeekDirection, SeekOffset, SkippedParts, SourceCredentials,
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::{stdout, Write};
 use std::iter::once;
 use std::ops::Range;
 use std::time::Duration;
 use tui::backend::CrosstermBackend;
 
+// "chapter prev" rewinds to the start of the current chapter unless the playback
+// has just entered it.
+const CHAPTER_RESTART_GAP: Duration = Duration::from_secs(3);
+
 pub(crate) enum SearchState {
     Loading,
     Loaded(ScrollableList<Vec<SearchResult>>),
     Error(search::Error),
 }
 
+pub(crate) enum TranscriptState {
+    None,
+    Loading(EpisodeId),
+    Unavailable(EpisodeId),
+    Loaded(EpisodeId, ScrollableList<Vec<TranscriptCue>>),
+}
+
+impl TranscriptState {
+    fn episode_id(&self) -> Option<EpisodeId> {
+        match self {
+            TranscriptState::None => None,
+            TranscriptState::Loading(episode_id)
+            | TranscriptState::Unavailable(episode_id)
+            | TranscriptState::Loaded(episode_id, _) => Some(*episode_id),
+        }
+    }
+}
+
 pub(crate) struct LibraryViewModel {
-    pub(crate) feeds: ScrollableList<Vec<FeedView<FeedSummary, GroupSummary>>>,
+    pub(crate) feeds: ScrollableList<Vec<FeedView<FeedSummary, GroupSummary, SavedViewSummary>>>,
     pub(crate) feeds_loaded: bool,
     pub(crate) episodes: ScrollableList<PaginatedData<EpisodeSummary>>,
     pub(crate) episodes_list_metadata: Option<EpisodesListMetadata>,
+    pub(crate) queue: ScrollableList<Vec<EpisodeSummary>>,
+    pub(crate) find_query: Option<String>,
+    pub(crate) sort_keys: HashMap<FeedView<FeedId, GroupId, SavedViewId>, EpisodesSortKey>,
     pub(crate) search: SearchState,
     pub(crate) focus: FocusedPane,
     pub(crate) updating_feeds: HashSet<FeedId>,
+    pub(crate) added_episodes: HashMap<FeedId, usize>,
+    pub(crate) next_update: Option<DateTime<Utc>>,
     pub(crate) playing_episode: Option<EpisodePlaybackData>,
+    pub(crate) chapters: Vec<Chapter>,
+    pub(crate) transcript: TranscriptState,
+    pub(crate) playing_cue: Option<usize>,
+    pub(crate) stats: Option<ScrollableList<Vec<StatsRow>>>,
+    pub(crate) stats_days: Option<u32>,
+    pub(crate) bookmarks: ScrollableList<Vec<Bookmark>>,
 }
 
 impl LibraryViewModel {
@@ -73,16 +119,59 @@ impl LibraryViewModel {
             feeds_loaded: false,
             episodes: ScrollableList::new(PaginatedData::new(), window_size, 3),
             episodes_list_metadata: None,
+            queue: ScrollableList::new(Vec::new(), window_size, 3),
+            find_query: None,
+            sort_keys: HashMap::new(),
             search: SearchState::Loading,
             focus: FocusedPane::FeedsList,
             playing_episode: None,
+            chapters: Vec::new(),
+            transcript: TranscriptState::None,
+            playing_cue: None,
+            stats: None,
+            stats_days: None,
+            bookmarks: ScrollableList::new(Vec::new(), window_size, 3),
             updating_feeds: HashSet::new(),
+            added_episodes: HashMap::new(),
+            next_update: None,
+        }
+    }
+
+    fn current_chapter_index(&self, position: Duration) -> Option<usize> {
+        self.chapters
+            .iter()
+            .rposition(|chapter| chapter.start <= position)
+    }
+
+    fn update_playing_cue(&mut self, position: Duration) {
+        let list = match &mut self.transcript {
+            TranscriptState::Loaded(_, list) => list,
+            _ => return,
+        };
+        let playing_cue = list.data().iter().rposition(|cue| cue.start <= position);
+        if playing_cue != self.playing_cue {
+            // The selection only follows the playback while it stays on the playing cue, so it
+            // does not move under the user scrolling through the transcript.
+            let following = self.playing_cue.unwrap_or(0) == list.selected_index();
+            self.playing_cue = playing_cue;
+            if let Some(index) = playing_cue.filter(|_| following) {
+                let offset = index as isize - list.selected_index() as isize;
+                list.scroll(ScrollAction::MoveBy(offset));
+            }
         }
     }
 
     fn set_window_size(&mut self, window_size: usize) {
         self.episodes.set_window_size(window_size);
         self.feeds.set_window_size(window_size);
+        self.queue.set_window_size(window_size);
+        if let TranscriptState::Loaded(_, list) = &mut self.transcript {
+            list.set_window_size(window_size);
+        }
+        if let Some(stats) = &mut self.stats {
+            stats.set_window_size(window_size);
+        }
+        self.bookmarks.set_window_size(window_size);
     }
 }
 
@@ -103,13 +192,21 @@ pub(crate) struct UI {
     library_actor: Addr<Library>,
     player_actor: Addr<Player>,
     status_writer_actor: Addr<StatusWriter>,
+    download_manager_actor: Addr<DownloadManager>,
+    http_client: HttpClient,
 
     options: Options,
     theme: Theme,
     key_mapping: KeyMapping<Command>,
     library: LibraryViewModel,
-    selected_feed: Option<FeedView<FeedId, GroupId>>,
+    selected_feed: Option<FeedView<FeedId, GroupId, SavedViewId>>,
     playback_state: PlaybackState,
+    playback_rate: f64,
+    playback_settings_pending: bool,
+    volume_offset: f64,
+    silence_skipped: Duration,
+    sleep_timer: SleepTimer,
+    listening_session: Option<ListeningSession>,
 
     previous_command: Option<Command>,
     log_history: ScrollableList<LogHistory>,
@@ -128,6 +225,8 @@ impl UI {
         library_actor: Addr<Library>,
         player_actor: Addr<Player>,
         status_writer_actor: Addr<StatusWriter>,
+        download_manager_actor: Addr<DownloadManager>,
+        http_client: HttpClient,
         app_env: super::AppEnvironment,
     ) -> Self {
         UI {
@@ -140,6 +239,8 @@ impl UI {
             library_actor,
             player_actor,
             status_writer_actor,
+            download_manager_actor,
+            http_client,
 
             options: Options::default(),
             theme: Theme::default(),
@@ -147,6 +248,12 @@ impl UI {
             library: LibraryViewModel::new(size.1.saturating_sub(2) as usize),
             selected_feed: None,
             playback_state: PlaybackState::default(),
+            playback_rate: 1.0,
+            playback_settings_pending: false,
+            volume_offset: 0.0,
+            silence_skipped: Duration::ZERO,
+            sleep_timer: SleepTimer::Off,
+            listening_session: None,
 
             previous_command: None,
             rendering_suspended: false,
@@ -195,14 +302,52 @@ impl UI {
                     let widget = ErrorsLogWidget::new(&self.log_history, &self.theme);
                     f.render_widget(widget, area);
                 }
+                FocusedPane::Transcript => {
+                    let widget = TranscriptWidget::new(
+                        &self.library.transcript,
+                        &self.theme,
+                        self.library.playing_cue,
+                    );
+                    f.render_widget(widget, area);
+                }
+                FocusedPane::Queue => {
+                    let widget = QueueWidget::new(
+                        &self.library.queue,
+                        &self.theme,
+                        &self.options,
+                        self.library
+                            .playing_episode
+                            .as_ref()
+                            .map(|episode| episode.id),
+                    );
+                    f.render_widget(widget, area);
+                }
+                FocusedPane::Stats => {
+                    let widget =
+                        StatsWidget::new(self.library.stats.as_ref(), &self.theme, &self.options);
+                    f.render_widget(widget, area);
+                }
+                FocusedPane::Bookmarks => {
+                    let widget =
+                        BookmarksWidget::new(&self.library.bookmarks, &self.theme, &self.options);
+                    f.render_widget(widget, area);
+                }
             }
 
+            let current_chapter = self.playback_state.timing().and_then(|timing| {
+                let index = self.library.current_chapter_index(timing.position)?;
+                self.library.chapters.get(index)
+            });
             let player_widget = PlayerState::new(
                 &self.playback_state,
                 &self.theme,
                 &self.options,
                 self.library.playing_episode.as_ref(),
-            );
+            )
+            .chapter(current_chapter)
+            .rate(self.playback_rate)
+            .silence_skipped(self.silence_skipped)
+            .sleep_timer(self.sleep_timer);
             f.render_widget(player_widget, player_area);
 
             if let Some(ref mut command_state) = self.command {
@@ -215,7 +360,8 @@ impl UI {
                 f.render_widget(confirmation, status_area);
             } else {
                 let status =
-                    LogEntryView::new(self.log_history.data().display_entry(), &self.theme);
+                    LogEntryView::new(self.log_history.data().display_entry(), &self.theme)
+                        .next_update(self.library.next_update);
                 f.render_widget(status, status_area);
             }
         };
@@ -290,6 +436,7 @@ impl UI {
     fn start_playback(
         &mut self,
         episode_id: EpisodeId,
+        position: Option<Duration>,
         initial_state: InitialPlaybackState,
         ctx: &mut <Self as Actor>::Context,
     ) {
@@ -300,19 +447,58 @@ impl UI {
         .map(move |result, actor: &mut UI, ctx| match result {
             Ok(Some(playback_data)) => {
                 actor.library.playing_episode = Some(playback_data.clone());
-                actor.playback_state =
-                    PlaybackState::new_started(playback_data.position, playback_data.duration);
+                actor.library.chapters.clear();
+                actor.load_chapters(episode_id, ctx);
+                if actor.library.focus == FocusedPane::Transcript {
+                    actor.load_transcript(ctx);
+                }
+                // Explicit positions, such as bookmarks, are played as they are even if they are
+                // within the intro.
+                let skip_intro = match position {
+                    Some(_) => Duration::ZERO,
+                    None => playback_data.skip_intro,
+                };
+                let position = position.unwrap_or(playback_data.position);
+                actor.playback_state = PlaybackState::new_started(position, playback_data.duration);
+                actor.playback_settings_pending = true;
+                actor.silence_skipped = Duration::ZERO;
+                let skip_silence =
+                    (playback_data.skip_silence).unwrap_or(actor.options.skip_silence);
+                let local_path = playback_data
+                    .local_path
+                    .as_deref()
+                    .filter(|path| path.is_file());
+                let media_url = match local_path.map(hedgehog_player::file_uri) {
+                    Some(Ok(uri)) => uri,
+                    Some(Err(error)) => {
+                        log::error!(target: "player", "{}", error);
+                        playback_data.media_url
+                    }
+                    None => playback_data.media_url,
+                };
                 actor
                     .player_actor
                     .do_send(hedgehog_player::PlaybackCommand::Play(
-                        playback_data.media_url,
-                        playback_data.position,
+                        media_url,
+                        position,
                         Some(PlaybackMetadata {
                             episode_id: playback_data.id.as_i64(),
                             episode_title: playback_data.episode_title,
                             feed_title: playback_data.feed_title,
                         }),
                         initial_state,
+                        playback_data
+                            .credentials
+                            .map(|credentials| SourceCredentials {
+                                username: credentials.username,
+                                password: credentials.password,
+                                host: credentials.host,
+                            }),
+                        SkippedParts {
+                            intro: skip_intro,
+                            outro: playback_data.skip_outro,
+                            silence: skip_silence,
+                        },
                     ));
                 actor
                     .library
@@ -335,6 +521,308 @@ impl UI {
         ctx.spawn(future);
     }
 
+    // The player resets the rate for every episode, so the feed's rate is applied once the
+    // playback starts. The volume offset is kept between episodes, so it is only sent when the
+    // feed's offset differs from the previous one.
+    fn apply_playback_settings(&mut self) {
+        let (rate, volume_offset) = match &self.library.playing_episode {
+            Some(playing_episode) => (playing_episode.rate, playing_episode.volume_offset),
+            None => return,
+        };
+        if rate != self.playback_rate {
+            self.player_actor.do_send(PlaybackCommand::SetRate(rate));
+        }
+        if volume_offset != self.volume_offset {
+            self.player_actor
+                .do_send(PlaybackCommand::SetVolumeOffset(volume_offset));
+            self.volume_offset = volume_offset;
+        }
+    }
+
+    // Episodes leave the queue once they start playing.
+    fn play_queued(&mut self, episode_id: EpisodeId, ctx: &mut <Self as Actor>::Context) {
+        self.library_actor
+            .do_send(FeedUpdateRequest::RemoveFromQueue(episode_id));
+        self.load_queue(ctx);
+        self.log_history
+            .update_data::<selection::DoNotUpdate, _>(|data, _| {
+                data.clear_playback_display_error();
+            });
+        self.start_playback(episode_id, None, InitialPlaybackState::Playing, ctx);
+    }
+
+    // Resolves the episode to continue with according to the `on-finish` option.
+    fn play_next(
+        &mut self,
+        finished_id: EpisodeId,
+        feed_id: FeedId,
+        ctx: &mut <Self as Actor>::Context,
+    ) {
+        let query = match self.options.on_finish {
+            OnFinishPolicy::Stop => return,
+            OnFinishPolicy::NextInFeed => EpisodesQuery::default()
+                .feed_id(feed_id)
+                .sort_key(EpisodesSortKey::PublicationDate)
+                .reversed_order(true),
+            OnFinishPolicy::NextInView => match self.episodes_query() {
+                Some(query) => {
+                    let reversed = (self.library.episodes_list_metadata.as_ref())
+                        .map_or(false, |metadata| metadata.reversed_order);
+                    query
+                        .with_hidden(self.options.hidden)
+                        .reversed_order(reversed)
+                }
+                None => return,
+            },
+            OnFinishPolicy::NextNew => EpisodesQuery::from_feed_view(FeedView::New).sort_key(
+                (self.library.sort_keys.get(&FeedView::New).cloned()).unwrap_or_default(),
+            ),
+        };
+        let request = NextEpisodeRequest {
+            query,
+            after: finished_id,
+        };
+        let future = wrap_future(self.library_actor.send(request)).map(
+            move |result, actor: &mut UI, ctx| match result {
+                Ok(Some(episode_id)) => {
+                    actor.start_playback(episode_id, None, InitialPlaybackState::Playing, ctx)
+                }
+                Ok(None) => (),
+                Err(error) => log::error!(target: "actix", "{}", error),
+            },
+        );
+        ctx.spawn(future);
+    }
+
+    // Listening sessions cover uninterrupted playback: they end when the playback is paused,
+    // stopped, or seeked and when the playback rate changes. They are also split at local
+    // midnight so that the daily statistics count the time on the day it was spent.
+    fn track_listening_session(&mut self, position: Duration) {
+        let episode_id = match &self.library.playing_episode {
+            Some(playing_episode) => playing_episode.id,
+            None => return,
+        };
+        let now = Utc::now();
+        let start_position = match &mut self.listening_session {
+            Some(session) if session.episode_id == episode_id => {
+                let local_date = |time: DateTime<Utc>| time.with_timezone(&Local).date();
+                if local_date(session.started_at) == local_date(now) {
+                    session.end_position = position;
+                    session.ended_at = now;
+                    return;
+                }
+                session.end_position
+            }
+            _ => position,
+        };
+        self.finish_listening_session();
+        self.listening_session = Some(ListeningSession {
+            episode_id,
+            start_position,
+            end_position: position,
+            started_at: now,
+            ended_at: now,
+            rate: self.playback_rate,
+            skipped_silence: Duration::ZERO,
+        });
+    }
+
+    fn take_listening_session(&mut self) -> Option<ListeningSession> {
+        (self.listening_session.take())
+            .filter(|session| session.end_position > session.start_position)
+    }
+
+    fn finish_listening_session(&mut self) {
+        if let Some(session) = self.take_listening_session() {
+            self.library_actor
+                .do_send(FeedUpdateRequest::AddListeningSession(session));
+        }
+    }
+
+    // The library's arbiter stops along with the system, so the current listening session has
+    // to be stored before that.
+    fn quit(&mut self, ctx: &mut <Self as Actor>::Context) {
+        match self.take_listening_session() {
+            Some(session) => {
+                let request = FeedUpdateRequest::AddListeningSession(session);
+                let future = wrap_future(self.library_actor.send(request))
+                    .map(|_, _: &mut UI, _| System::current().stop());
+                ctx.spawn(future);
+            }
+            None => System::current().stop(),
+        }
+    }
+
+    fn load_queue(&mut self, ctx: &mut <Self as Actor>::Context) {
+        let future = wrap_future(self.library_actor.send(QueueRequest)).map(
+            move |result, actor: &mut UI, ctx| match result {
+                Ok(queue) => {
+                    actor
+                        .library
+                        .queue
+                        .update_data::<selection::FindPrevious<selection::Keep>, _>(|data, _| {
+                            *data = queue
+                        });
+                    actor.invalidate_later(ctx);
+                }
+                Err(error) => {
+                    log::error!(target: "actix", "{}", error);
+                }
+            },
+        );
+        ctx.spawn(future);
+    }
+
+    // The episode that queue commands apply to: the one selected in the queue pane if it is
+    // focused or the one selected in the episodes list otherwise.
+    fn selected_episode_id(&self) -> Option<EpisodeId> {
+        match self.library.focus {
+            FocusedPane::Queue => self.library.queue.selection().map(|episode| episode.id),
+            _ => self.library.episodes.selection().map(|episode| episode.id),
+        }
+    }
+
+    fn load_bookmarks(&mut self, ctx: &mut <Self as Actor>::Context) {
+        let future = wrap_future(self.library_actor.send(BookmarksRequest)).map(
+            move |result, actor: &mut UI, ctx| match result {
+                Ok(bookmarks) => {
+                    actor
+                        .library
+                        .bookmarks
+                        .update_data::<selection::FindPrevious<selection::Keep>, _>(|data, _| {
+                            *data = bookmarks
+                        });
+                    actor.invalidate_later(ctx);
+                }
+                Err(error) => {
+                    log::error!(target: "actix", "{}", error);
+                }
+            },
+        );
+        ctx.spawn(future);
+    }
+
+    fn load_chapters(&mut self, episode_id: EpisodeId, ctx: &mut <Self as Actor>::Context) {
+        let future = wrap_future(self.library_actor.send(EpisodeChaptersRequest(episode_id))).map(
+            move |result, actor: &mut UI, ctx| match result {
+                Ok(chapters) => {
+                    let playing_id = actor.library.playing_episode.as_ref().map(|ep| ep.id);
+                    if playing_id == Some(episode_id) {
+                        actor.library.chapters = chapters;
+                        actor.invalidate_later(ctx);
+                    }
+                }
+                Err(error) => {
+                    log::error!(target: "actix", "{}", error);
+                }
+            },
+        );
+        ctx.spawn(future);
+    }
+
+    fn load_transcript(&mut self, ctx: &mut <Self as Actor>::Context) {
+        let episode_id = match &self.library.playing_episode {
+            Some(playing_episode) => playing_episode.id,
+            None => return,
+        };
+        if self.library.transcript.episode_id() == Some(episode_id) {
+            return;
+        }
+
+        self.library.transcript = TranscriptState::Loading(episode_id);
+        self.library.playing_cue = None;
+        let future = wrap_future(
+            self.library_actor
+                .send(EpisodeTranscriptRequest(episode_id)),
+        )
+        .map(move |result, actor: &mut UI, ctx| {
+            if actor.library.transcript.episode_id() != Some(episode_id) {
+                return;
+            }
+            actor.library.transcript = match result {
+                Ok(Some(cues)) if !cues.is_empty() => TranscriptState::Loaded(
+                    episode_id,
+                    ScrollableList::new(cues, actor.library.feeds.viewport().window_size(), 3),
+                ),
+                Ok(_) => TranscriptState::Unavailable(episode_id),
+                Err(error) => {
+                    log::error!(target: "actix", "{}", error);
+                    TranscriptState::Unavailable(episode_id)
+                }
+            };
+            if let Some(timing) = actor.playback_state.timing() {
+                actor.library.update_playing_cue(timing.position);
+            }
+            actor.invalidate(ctx);
+        });
+        ctx.spawn(future);
+    }
+
+    fn load_stats(&mut self, ctx: &mut <Self as Actor>::Context) {
+        // Periods are counted in whole days, today included
+        let since = self.library.stats_days.map(|days| {
+            let first_day = Local::today() - chrono::Duration::days(days.saturating_sub(1) as i64);
+            first_day.and_hms(0, 0, 0).with_timezone(&Utc)
+        });
+        self.library.stats = None;
+        let future = wrap_future(self.library_actor.send(ListeningStatsRequest { since })).map(
+            move |result, actor: &mut UI, ctx| {
+                let stats = match result {
+                    Ok(stats) => stats,
+                    Err(error) => {
+                        log::error!(target: "actix", "{}", error);
+                        ListeningStats::default()
+                    }
+                };
+                actor.library.stats = Some(ScrollableList::new(
+                    StatsRow::from_stats(stats),
+                    actor.library.feeds.viewport().window_size(),
+                    3,
+                ));
+                actor.invalidate(ctx);
+            },
+        );
+        ctx.spawn(future);
+    }
+
+    fn seek_to_chapter(&mut self, command: ChapterCommand) -> bool {
+        let position = match self.playback_state.timing() {
+            Some(timing) => timing.position,
+            None => return true,
+        };
+        let current = self.library.current_chapter_index(position);
+        let target = match command {
+            ChapterCommand::Next => current.map(|index| index + 1).unwrap_or(0),
+            ChapterCommand::Prev => match current {
+                Some(index)
+                    if index > 0
+                        && position < self.library.chapters[index].start + CHAPTER_RESTART_GAP =>
+                {
+                    index - 1
+                }
+                Some(index) => index,
+                None => 0,
+            },
+            ChapterCommand::Go(number) => number.wrapping_sub(1),
+        };
+
+        match self.library.chapters.get(target) {
+            Some(chapter) => {
+                self.player_actor
+                    .do_send(PlaybackCommand::Seek(chapter.start));
+                true
+            }
+            None if self.library.chapters.is_empty() => {
+                log::warn!("This episode has no chapters");
+                false
+            }
+            None => {
+                log::warn!("No such chapter");
+                false
+            }
+        }
+    }
+
     fn handle_command(&mut self, command: Command, ctx: &mut <Self as Actor>::Context) -> bool {
         match command {
             Command::Cursor(command) => {
@@ -350,16 +838,37 @@ impl UI {
                         }
                     }
                     FocusedPane::ErrorsLog => self.log_history.scroll(command),
+                    FocusedPane::Transcript => {
+                        if let TranscriptState::Loaded(_, list) = &mut self.library.transcript {
+                            list.scroll(command);
+                        }
+                    }
+                    FocusedPane::Queue => self.library.queue.scroll(command),
+                    FocusedPane::Stats => {
+                        if let Some(stats) = &mut self.library.stats {
+                            stats.scroll(command);
+                        }
+                    }
+                    FocusedPane::Bookmarks => self.library.bookmarks.scroll(command),
                 }
                 self.invalidate_later(ctx);
             }
             Command::SetFocus(focused_pane) => {
                 if self.library.focus != focused_pane {
                     self.library.focus = focused_pane;
+                    if focused_pane == FocusedPane::Transcript {
+                        self.load_transcript(ctx);
+                    }
+                    if focused_pane == FocusedPane::Stats {
+                        self.load_stats(ctx);
+                    }
+                    if focused_pane == FocusedPane::Bookmarks {
+                        self.load_bookmarks(ctx);
+                    }
                     self.invalidate(ctx);
                 }
             }
-            Command::Quit => System::current().stop(),
+            Command::Quit => self.quit(ctx),
             Command::Map(key, command) => {
                 let redefined = self.key_mapping.contains(key);
                 self.key_mapping.map(key, *command);
@@ -422,6 +931,40 @@ impl UI {
                 self.confirmation = Some(*confirmation);
                 self.invalidate(ctx);
             }
+            Command::PlayCurrent if self.library.focus == FocusedPane::Transcript => {
+                let playing_id = self.library.playing_episode.as_ref().map(|ep| ep.id);
+                if let TranscriptState::Loaded(episode_id, list) = &self.library.transcript {
+                    if let Some(cue) = list.selection().filter(|_| playing_id == Some(*episode_id))
+                    {
+                        self.player_actor.do_send(PlaybackCommand::Seek(cue.start));
+                    }
+                }
+            }
+            Command::PlayCurrent if self.library.focus == FocusedPane::Queue => {
+                if let Some(episode_id) = self.library.queue.selection().map(|episode| episode.id) {
+                    self.play_queued(episode_id, ctx);
+                }
+            }
+            Command::PlayCurrent if self.library.focus == FocusedPane::Bookmarks => {
+                if let Some(bookmark) = self.library.bookmarks.selection() {
+                    let (episode_id, position) = (bookmark.episode_id, bookmark.position);
+                    let playing_id = self.library.playing_episode.as_ref().map(|ep| ep.id);
+                    if playing_id == Some(episode_id) {
+                        self.player_actor.do_send(PlaybackCommand::Seek(position));
+                        return true;
+                    }
+                    self.log_history
+                        .update_data::<selection::DoNotUpdate, _>(|data, _| {
+                            data.clear_playback_display_error();
+                        });
+                    self.start_playback(
+                        episode_id,
+                        Some(position),
+                        InitialPlaybackState::Playing,
+                        ctx,
+                    );
+                }
+            }
             Command::PlayCurrent => {
                 let episode_id = if let Some(current_episode) = self.library.episodes.selection() {
                     let episode_id = current_episode.id;
@@ -443,9 +986,17 @@ impl UI {
                     return true;
                 };
                 self.invalidate_later(ctx);
-                self.start_playback(episode_id, InitialPlaybackState::Playing, ctx);
+                self.start_playback(episode_id, None, InitialPlaybackState::Playing, ctx);
             }
+            Command::Next => match self.library.queue.data().first().map(|episode| episode.id) {
+                Some(episode_id) => self.play_queued(episode_id, ctx),
+                None => {
+                    log::warn!("The queue is empty");
+                    return false;
+                }
+            },
             Command::Playback(command) => self.player_actor.do_send(command),
+            Command::Chapter(command) => return self.seek_to_chapter(command),
             Command::Finish => {
                 if let Some(playing) = &self.library.playing_episode {
                     self.player_actor.do_send(PlaybackCommand::Stop);
@@ -464,9 +1015,23 @@ impl UI {
                 }
             }
             Command::Volume(command) => self.player_actor.do_send(command),
-            Command::AddFeed(source) => self
-                .library_actor
-                .do_send(FeedUpdateRequest::AddFeed(NewFeedMetadata::new(source))),
+            Command::AddFeed {
+                source,
+                username,
+                password_command,
+            } => {
+                if username.is_none() && password_command.is_some() {
+                    log::warn!("The password command requires a user name");
+                    return false;
+                }
+                let credentials = username.map(|username| FeedCredentials {
+                    username,
+                    password_command,
+                });
+                self.library_actor.do_send(FeedUpdateRequest::AddFeed(
+                    NewFeedMetadata::new(source).with_credentials(credentials),
+                ))
+            }
             Command::AddGroup(name) => self
                 .library_actor
                 .do_send(FeedUpdateRequest::AddGroup(name)),
@@ -513,7 +1078,7 @@ impl UI {
             Command::PlaceGroup(position) => {
                 for index in (0..=self.library.feeds.selected_index()).rev() {
                     match self.library.feeds.data().get(index) {
-                        Some(FeedView::All | FeedView::New) => {
+                        Some(FeedView::All | FeedView::New | FeedView::Saved(_)) => {
                             log::error!("Select the group to change its position");
                             return false;
                         }
@@ -531,6 +1096,44 @@ impl UI {
                     }
                 }
             }
+            Command::View(ViewCommand::Add {
+                name,
+                status,
+                group,
+                min_minutes,
+                max_minutes,
+                newer_than_days,
+                older_than_days,
+                text,
+            }) => {
+                let group_id = match group {
+                    Some(group) => {
+                        let group_id = (self.library.feeds.data().iter())
+                            .filter_map(|entry| entry.as_group())
+                            .find(|entry| entry.name == group)
+                            .map(|entry| entry.id);
+                        match group_id {
+                            Some(group_id) => Some(group_id),
+                            None => {
+                                log::error!("Cannot find a group with this name");
+                                return false;
+                            }
+                        }
+                    }
+                    None => None,
+                };
+                let filter = EpisodesFilter {
+                    status,
+                    group_id,
+                    min_duration: min_minutes.map(|minutes| Duration::from_secs(minutes * 60)),
+                    max_duration: max_minutes.map(|minutes| Duration::from_secs(minutes * 60)),
+                    newer_than_days,
+                    older_than_days,
+                    text,
+                };
+                self.library_actor
+                    .do_send(FeedUpdateRequest::AddSavedView(name, filter));
+            }
             Command::Delete => match self.library.feeds.selection() {
                 Some(FeedView::Feed(selected_feed)) => {
                     self.library_actor
@@ -541,13 +1144,23 @@ impl UI {
                         .do_send(FeedUpdateRequest::DeleteGroup(selected_group.id));
                     self.load_feeds(ctx);
                 }
+                Some(FeedView::Saved(selected_view)) => {
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::DeleteSavedView(selected_view.id));
+                    self.load_feeds(ctx);
+                }
                 _ => {}
             },
-            Command::Update { current_only } => {
+            Command::Update {
+                current_only,
+                failed_only,
+            } => {
                 let query = if current_only {
                     self.selected_feed
                         .and_then(|feed| feed.as_feed().cloned())
                         .map(UpdateQuery::Single)
+                } else if failed_only {
+                    Some(UpdateQuery::Failed)
                 } else {
                     Some(UpdateQuery::All)
                 };
@@ -563,11 +1176,36 @@ impl UI {
                 }
             }
             Command::SetOption(options_update) => {
+                if let OptionsUpdate::UpdateInterval(minutes) = options_update {
+                    let interval = Some(Duration::from_secs(minutes * 60))
+                        .filter(|interval| !interval.is_zero());
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::SetUpdateInterval(interval));
+                }
                 let affects_episodes_list = options_update.affects_episodes_list();
+                let affects_network = options_update.affects_network();
+                if let OptionsUpdate::SkipSilence(skip) = options_update {
+                    let feed_skip = (self.library.playing_episode.as_ref())
+                        .and_then(|playing_episode| playing_episode.skip_silence);
+                    if feed_skip.is_none() {
+                        self.player_actor
+                            .do_send(PlaybackCommand::SetSkipSilence(skip));
+                    }
+                }
+                if let OptionsUpdate::AudioFilter(filter) = &options_update {
+                    self.player_actor
+                        .do_send(PlaybackCommand::SetAudioFilter(filter.clone()));
+                }
                 self.options.update(options_update);
                 if affects_episodes_list {
                     self.refresh_episodes(ctx, false);
                 }
+                if affects_network {
+                    let result = self.http_client.configure(self.options.network_config());
+                    if let Err(error) = result {
+                        log::error!(target: "networking", "Invalid network settings: {}", error);
+                    }
+                }
                 self.invalidate(ctx);
             }
             Command::SetFeedEnabled(enabled) => {
@@ -582,7 +1220,7 @@ impl UI {
                 condition,
             } => {
                 if update_all {
-                    if let Some(feed) = self.selected_feed {
+                    if let Some(mut query) = self.episodes_query() {
                         self.library
                             .episodes
                             .update_data::<selection::DoNotUpdate, _>(|data, _| {
@@ -593,7 +1231,6 @@ impl UI {
                                 }
                             });
 
-                        let mut query = EpisodesQuery::from_feed_view(feed);
                         if let Some(condition) = condition {
                             query = query.status(condition);
                         }
@@ -633,11 +1270,22 @@ impl UI {
                     let index = list.viewport().selected_index();
                     if let Some(item) = list.data().item_at(index) {
                         let url = item.feed_url.clone();
-                        self.handle_command(Command::AddFeed(url), ctx);
+                        let command = Command::AddFeed {
+                            source: url,
+                            username: None,
+                            password_command: None,
+                        };
+                        self.handle_command(command, ctx);
                         self.invalidate_later(ctx);
                     }
                 }
             }
+            Command::Find(query) => {
+                self.library.find_query = Some(query);
+                self.library.focus = FocusedPane::EpisodesList;
+                self.refresh_episodes(ctx, true);
+                self.invalidate(ctx);
+            }
             Command::Refresh => {
                 self.library
                     .episodes
@@ -650,6 +1298,164 @@ impl UI {
                 self.load_feeds(ctx);
                 self.invalidate(ctx);
             }
+            Command::Download => {
+                if let Some(episode) = self.library.episodes.selection() {
+                    self.download_manager_actor
+                        .do_send(DownloadRequest::Download(episode.id));
+                }
+            }
+            Command::CancelDownload => {
+                if let Some(episode) = self.library.episodes.selection() {
+                    self.download_manager_actor
+                        .do_send(DownloadRequest::Cancel(episode.id));
+                }
+            }
+            Command::QueueAdd => {
+                if let Some(episode) = self.library.episodes.selection() {
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::AddToQueue(episode.id));
+                    self.load_queue(ctx);
+                }
+            }
+            Command::QueueRemove => {
+                if let Some(episode_id) = self.selected_episode_id() {
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::RemoveFromQueue(episode_id));
+                    self.load_queue(ctx);
+                }
+            }
+            Command::QueueMove(position) => {
+                if let Some(episode_id) = self.selected_episode_id() {
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::SetQueuePosition(episode_id, position));
+                    self.load_queue(ctx);
+                }
+            }
+            Command::QueueClear => {
+                self.library_actor.do_send(FeedUpdateRequest::ClearQueue);
+                self.load_queue(ctx);
+            }
+            Command::Stats { days } => {
+                self.library.stats_days = days;
+                self.library.focus = FocusedPane::Stats;
+                self.load_stats(ctx);
+                self.invalidate(ctx);
+            }
+            Command::SetFeedPolicy(policy) => match self.selected_feed {
+                Some(FeedView::Feed(feed_id)) => {
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::SetFeedPolicy(feed_id, policy));
+                }
+                _ => log::warn!("Download policy can only be set for individual podcasts"),
+            },
+            Command::SetFeedRate(rate) => match self.selected_feed {
+                Some(FeedView::Feed(_)) if rate <= 0.0 => {
+                    log::warn!("Playback rate must be positive")
+                }
+                Some(FeedView::Feed(feed_id)) => {
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::SetFeedPlaybackRate(feed_id, rate));
+                    if let Some(playing_episode) = &mut self.library.playing_episode {
+                        if playing_episode.feed_id == feed_id {
+                            playing_episode.rate = rate;
+                            self.apply_playback_settings();
+                        }
+                    }
+                }
+                _ => log::warn!("Playback rate can only be set for individual podcasts"),
+            },
+            Command::MarkIntro { clear } => {
+                let feed_id = self.library.playing_episode.as_ref().map(|ep| ep.feed_id);
+                match (feed_id, self.playback_state.timing()) {
+                    (Some(feed_id), Some(timing)) => {
+                        let intro = if clear {
+                            Duration::ZERO
+                        } else {
+                            timing.position
+                        };
+                        self.library_actor
+                            .do_send(FeedUpdateRequest::SetFeedIntroSkip(feed_id, intro));
+                    }
+                    _ => log::warn!("Intro can only be marked while an episode is playing"),
+                }
+            }
+            Command::MarkOutro { clear } => {
+                let feed_id = self.library.playing_episode.as_ref().map(|ep| ep.feed_id);
+                match (feed_id, self.playback_state.timing()) {
+                    (Some(feed_id), Some(timing)) => {
+                        let outro = match timing.duration {
+                            _ if clear => Duration::ZERO,
+                            Some(duration) => duration.saturating_sub(timing.position),
+                            None => {
+                                log::warn!("The duration of the episode is not known yet");
+                                return false;
+                            }
+                        };
+                        self.library_actor
+                            .do_send(FeedUpdateRequest::SetFeedOutroSkip(feed_id, outro));
+                    }
+                    _ => log::warn!("Outro can only be marked while an episode is playing"),
+                }
+            }
+            Command::Bookmark(note) => {
+                let episode_id = self.library.playing_episode.as_ref().map(|ep| ep.id);
+                match (episode_id, self.playback_state.timing()) {
+                    (Some(episode_id), Some(timing)) => {
+                        self.library_actor.do_send(FeedUpdateRequest::AddBookmark(
+                            episode_id,
+                            timing.position,
+                            note,
+                        ));
+                        self.load_bookmarks(ctx);
+                        log::info!("Bookmark added");
+                    }
+                    _ => {
+                        log::warn!("Bookmarks can only be added while an episode is playing");
+                        return false;
+                    }
+                }
+            }
+            Command::BookmarkDelete => {
+                if self.library.focus == FocusedPane::Bookmarks {
+                    if let Some(bookmark) = self.library.bookmarks.selection() {
+                        self.library_actor
+                            .do_send(FeedUpdateRequest::DeleteBookmark(bookmark.id));
+                        self.load_bookmarks(ctx);
+                    }
+                }
+            }
+            Command::SetFeedSkipSilence(skip) => match self.selected_feed {
+                Some(FeedView::Feed(feed_id)) => {
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::SetFeedSkipSilence(feed_id, skip));
+                    if let Some(playing_episode) = &mut self.library.playing_episode {
+                        if playing_episode.feed_id == feed_id {
+                            playing_episode.skip_silence = skip;
+                            let skip = skip.unwrap_or(self.options.skip_silence);
+                            self.player_actor
+                                .do_send(PlaybackCommand::SetSkipSilence(skip));
+                        }
+                    }
+                }
+                _ => log::warn!("Silence skipping can only be set for individual podcasts"),
+            },
+            Command::SetFeedVolume(offset) => match self.selected_feed {
+                Some(FeedView::Feed(_)) if !(-100.0..=100.0).contains(&offset) => {
+                    log::warn!("Volume adjustment must be between -100% and 100%")
+                }
+                Some(FeedView::Feed(feed_id)) => {
+                    let offset = offset / 100.0;
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::SetFeedVolumeOffset(feed_id, offset));
+                    if let Some(playing_episode) = &mut self.library.playing_episode {
+                        if playing_episode.feed_id == feed_id {
+                            playing_episode.volume_offset = offset;
+                            self.apply_playback_settings();
+                        }
+                    }
+                }
+                _ => log::warn!("Volume can only be set for individual podcasts"),
+            },
             Command::SetEpisodeHidden(hidden) => {
                 let query = self
                     .library
@@ -678,6 +1484,14 @@ impl UI {
                     }
                 }
             }
+            Command::Sort(sort_key) => {
+                if let Some(view) = self.selected_feed {
+                    self.library.sort_keys.insert(view, sort_key);
+                    self.library_actor
+                        .do_send(FeedUpdateRequest::SetSortKey(view, sort_key));
+                    self.refresh_episodes(ctx, true);
+                }
+            }
             Command::Rename(name) => match self.selected_feed {
                 Some(FeedView::Feed(feed_id)) => {
                     self.library_actor
@@ -798,6 +1612,7 @@ impl UI {
                 Some(FeedView::All | FeedView::New) => SelectedItem::SpecialFeed,
                 Some(FeedView::Feed(_)) => SelectedItem::Feed,
                 Some(FeedView::Group(_)) => SelectedItem::Group,
+                Some(FeedView::Saved(_)) => SelectedItem::View,
                 None => SelectedItem::Nothing,
             },
             FocusedPane::EpisodesList if self.library.episodes.data().is_empty() => {
@@ -810,6 +1625,19 @@ impl UI {
             FocusedPane::Search => SelectedItem::Nothing,
             FocusedPane::ErrorsLog if self.log_history.data().is_empty() => SelectedItem::Nothing,
             FocusedPane::ErrorsLog => SelectedItem::LogEntry,
+            FocusedPane::Queue if self.library.queue.data().is_empty() => SelectedItem::Nothing,
+            FocusedPane::Queue => SelectedItem::Episode,
+            FocusedPane::Transcript => match &self.library.transcript {
+                TranscriptState::Loaded(_, list) if !list.data().is_empty() => {
+                    SelectedItem::TranscriptCue
+                }
+                _ => SelectedItem::Nothing,
+            },
+            FocusedPane::Stats => SelectedItem::Nothing,
+            FocusedPane::Bookmarks if self.library.bookmarks.data().is_empty() => {
+                SelectedItem::Nothing
+            }
+            FocusedPane::Bookmarks => SelectedItem::Bookmark,
         }
     }
 
@@ -843,9 +1671,69 @@ impl UI {
             }));
     }
 
+    fn report_added_episodes(&mut self) {
+        let added_episodes = std::mem::take(&mut self.library.added_episodes);
+        let total: usize = added_episodes.values().sum();
+        if total == 0 {
+            return;
+        }
+        let episodes = match total {
+            1 => "1 new episode".to_string(),
+            total => format!("{} new episodes", total),
+        };
+        if added_episodes.len() > 1 {
+            log::info!(target: "update", "{} in {} feeds", episodes, added_episodes.len());
+            return;
+        }
+        let feed_id = added_episodes.keys().next().cloned();
+        let feed_title = (self.library.feeds.data().iter())
+            .filter_map(FeedView::as_feed)
+            .find(|feed| Some(feed.id) == feed_id)
+            .map(|feed| feed.title.as_str());
+        match feed_title {
+            Some(feed_title) => log::info!(target: "update", "{} in {}", episodes, feed_title),
+            None => log::info!(target: "update", "{}", episodes),
+        }
+    }
+
+    // The query for the episodes displayed in the episodes pane: either the results of `find` or
+    // the episodes of the selected feed. Both are sorted according to the selected feed's view.
+    fn episodes_query(&self) -> Option<EpisodesQuery> {
+        let sort_key = self
+            .selected_feed
+            .and_then(|view| self.library.sort_keys.get(&view).cloned())
+            .unwrap_or_default();
+        let query = match (&self.library.find_query, self.selected_feed) {
+            (Some(text), _) => EpisodesQuery::default()
+                .include_feed_title()
+                .text(text.clone()),
+            (None, Some(feed_id)) => EpisodesQuery::from_feed_view(self.resolve_view(feed_id)?),
+            (None, None) => return None,
+        };
+        Some(query.sort_key(sort_key))
+    }
+
+    fn resolve_view(
+        &self,
+        view: FeedView<FeedId, GroupId, SavedViewId>,
+    ) -> Option<FeedView<FeedId, GroupId, &EpisodesFilter>> {
+        Some(match view {
+            FeedView::All => FeedView::All,
+            FeedView::New => FeedView::New,
+            FeedView::Saved(view_id) => {
+                let saved_view = (self.library.feeds.data().iter())
+                    .filter_map(FeedView::as_saved)
+                    .find(|saved_view| saved_view.id == view_id)?;
+                FeedView::Saved(&saved_view.filter)
+            }
+            FeedView::Feed(feed_id) => FeedView::Feed(feed_id),
+            FeedView::Group(group_id) => FeedView::Group(group_id),
+        })
+    }
+
     fn refresh_episodes(&mut self, ctx: &mut <UI as Actor>::Context, replace_current: bool) {
-        let feed_id = match self.selected_feed {
-            Some(feed_id) => feed_id,
+        let query = match self.episodes_query() {
+            Some(query) => query.with_hidden(self.options.hidden),
             None => return,
         };
         self.library
@@ -861,7 +1749,6 @@ impl UI {
             self.library.episodes_list_metadata = None;
         }
 
-        let query = EpisodesQuery::from_feed_view(feed_id).with_hidden(self.options.hidden);
         let address = ctx.address();
         let future = wrap_future(
             self.library_actor
@@ -945,6 +1832,7 @@ impl UI {
             return;
         }
         self.selected_feed = selected_id;
+        self.library.find_query = None;
 
         if selected_id.is_some() {
             self.refresh_episodes(ctx, true);
@@ -964,7 +1852,7 @@ impl UI {
         self.library.search = SearchState::Loading;
         self.invalidate(ctx);
 
-        let client = SearchClient::new();
+        let client = SearchClient::new().with_http_client(self.http_client.clone());
         ctx.spawn(
             wrap_future(async move { client.perform(&query).await }).map(
                 move |result, actor: &mut UI, ctx| {
@@ -994,15 +1882,23 @@ impl UI {
         ctx.spawn(
             wrap_future(self.library_actor.send(FeedSummariesRequest)).map(
                 move |data, actor: &mut UI, ctx| match data {
-                    Ok(FeedSummariesResponse { feeds, groups }) => {
+                    Ok(FeedSummariesResponse {
+                        feeds,
+                        groups,
+                        saved_views,
+                        sort_keys,
+                    }) => {
+                        actor.library.sort_keys = sort_keys;
                         actor
                             .library
                             .feeds
                             .update_data::<selection::FindPrevious, _>(|current_feeds, _| {
-                                let mut feed_views =
-                                    Vec::with_capacity(feeds.len() + groups.len() + 2);
+                                let mut feed_views = Vec::with_capacity(
+                                    feeds.len() + groups.len() + saved_views.len() + 2,
+                                );
                                 feed_views.push(FeedView::All);
                                 feed_views.push(FeedView::New);
+                                feed_views.extend(saved_views.into_iter().map(FeedView::Saved));
 
                                 let mut feeds_iter = feeds.into_iter().peekable();
                                 for group in once(None).chain(groups.into_iter().map(Some)) {
@@ -1040,6 +1936,7 @@ impl Actor for UI {
 
     fn started(&mut self, ctx: &mut Self::Context) {
         self.load_feeds(ctx);
+        self.load_queue(ctx);
 
         self.player_actor
             .do_send(hedgehog_player::ActorCommand::Subscribe(
@@ -1049,6 +1946,8 @@ impl Actor for UI {
             .do_send(hedgehog_library::FeedUpdateRequest::Subscribe(
                 ctx.address().recipient(),
             ));
+        self.download_manager_actor
+            .do_send(DownloadRequest::Subscribe(ctx.address().recipient()));
 
         ctx.add_stream(event::EventStream::new());
 
@@ -1066,7 +1965,7 @@ impl Actor for UI {
                 Err(error) => log::error!(target: "actix", "{}", error),
                 Ok(None) => {}
                 Ok(Some(episode_id)) => {
-                    actor.start_playback(episode_id, InitialPlaybackState::Paused, ctx);
+                    actor.start_playback(episode_id, None, InitialPlaybackState::Paused, ctx);
                 }
             }),
         );
@@ -1369,8 +2268,17 @@ impl Handler<PlayerNotification> for UI {
             },
             PlayerNotification::StateChanged(state) => {
                 self.playback_state.set_state(state);
+                if self.playback_state.status() != PlaybackStatus::Playing {
+                    self.finish_listening_session();
+                }
+                let is_started = state.map_or(false, |state| state.is_started());
+                if is_started && self.playback_settings_pending {
+                    self.playback_settings_pending = false;
+                    self.apply_playback_settings();
+                }
                 if state.is_none() {
                     self.library.playing_episode.take();
+                    self.library.chapters.clear();
                     self.status_writer_actor
                         .do_send(StatusWriterCommand::StopPlayback);
                 }
@@ -1380,7 +2288,12 @@ impl Handler<PlayerNotification> for UI {
                 self.playback_state.set_duration(duration);
                 self.invalidate(ctx);
             }
-            PlayerNotification::PositionSet { position, .. } => {
+            PlayerNotification::PositionSet { position, seeked } => {
+                if seeked {
+                    self.finish_listening_session();
+                } else {
+                    self.track_listening_session(position);
+                }
                 if let Some(playing_episode) = &self.library.playing_episode {
                     self.status_writer_actor
                         .do_send(StatusWriterCommand::set_position(
@@ -1389,22 +2302,60 @@ impl Handler<PlayerNotification> for UI {
                         ));
                 }
                 self.playback_state.set_position(position);
+                self.library.update_playing_cue(position);
+                self.invalidate(ctx);
+            }
+            PlayerNotification::SilenceSkipped(silence_skipped) => {
+                if let Some(session) = &mut self.listening_session {
+                    session.skipped_silence += silence_skipped.saturating_sub(self.silence_skipped);
+                }
+                self.silence_skipped = silence_skipped;
+                self.invalidate(ctx);
+            }
+            PlayerNotification::SleepTimer(sleep_timer) => {
+                self.sleep_timer = sleep_timer;
+                self.invalidate(ctx);
+            }
+            PlayerNotification::RateChanged(rate) => {
+                self.finish_listening_session();
+                self.playback_rate = rate;
                 self.invalidate(ctx);
             }
             PlayerNotification::Eos => {
-                if let Some(playing_episode) = &self.library.playing_episode {
+                let finished =
+                    (self.library.playing_episode.as_ref()).map(|ep| (ep.id, ep.feed_id));
+                let finished_id = finished.map(|(episode_id, _)| episode_id);
+                if let Some(finished_id) = finished_id {
                     self.status_writer_actor
-                        .do_send(StatusWriterCommand::set_finished(playing_episode.id));
+                        .do_send(StatusWriterCommand::set_finished(finished_id));
                     self.library
                         .episodes
                         .update_data::<selection::DoNotUpdate, _>(|data, _| {
                             let episode = data
-                                .find(|item| item.id == playing_episode.id)
+                                .find(|item| item.id == finished_id)
                                 .and_then(|index| data.item_at_mut(index));
                             if let Some(episode) = episode {
                                 episode.status = EpisodeSummaryStatus::Finished;
                             }
                         });
+                    let queue = self.library.queue.data();
+                    if queue.iter().any(|episode| episode.id == finished_id) {
+                        self.library_actor
+                            .do_send(FeedUpdateRequest::RemoveFromQueue(finished_id));
+                        self.load_queue(ctx);
+                    }
+                }
+                // The player resets the sleep timer only after reporting the end of the stream.
+                if self.sleep_timer == SleepTimer::EndOfEpisode {
+                    return;
+                }
+                let next_id = (self.library.queue.data().iter())
+                    .map(|episode| episode.id)
+                    .find(|episode_id| Some(*episode_id) != finished_id);
+                if let Some(next_id) = next_id {
+                    self.play_queued(next_id, ctx);
+                } else if let Some((finished_id, feed_id)) = finished {
+                    self.play_next(finished_id, feed_id, ctx);
                 }
             }
             PlayerNotification::Failure => {
@@ -1462,9 +2413,16 @@ impl Handler<FeedUpdateNotification> for UI {
                     });
                 if self.selected_feed == Some(FeedView::Feed(id))
                     || self.selected_feed.map(|view| view.as_feed().is_none()) == Some(true)
+                    || self.library.find_query.is_some()
                 {
                     self.refresh_episodes(ctx, false);
                 }
+                if self.library.updating_feeds.is_empty() {
+                    self.report_added_episodes();
+                }
+            }
+            FeedUpdateNotification::EpisodesAdded(id, episode_ids) => {
+                *self.library.added_episodes.entry(id).or_default() += episode_ids.len();
             }
             FeedUpdateNotification::FeedAdded(feed) => {
                 self.library
@@ -1505,8 +2463,22 @@ impl Handler<FeedUpdateNotification> for UI {
                     });
                 self.update_current_feed(ctx);
             }
+            FeedUpdateNotification::SavedViewAdded(saved_view) => {
+                self.library
+                    .feeds
+                    .update_data::<selection::Keep, _>(|feeds, _| {
+                        let index = feeds
+                            .iter()
+                            .position(|feed| {
+                                !matches!(feed, FeedView::All | FeedView::New | FeedView::Saved(_))
+                            })
+                            .unwrap_or(feeds.len());
+                        feeds.insert(index, FeedView::Saved(saved_view));
+                    });
+                self.update_current_feed(ctx);
+            }
             FeedUpdateNotification::NewCountUpdated(new_count) => {
-                if self.selected_feed == Some(FeedView::New) {
+                if matches!(self.selected_feed, Some(FeedView::New | FeedView::Saved(_))) {
                     self.refresh_episodes(ctx, false);
                 }
                 self.library
@@ -1521,11 +2493,36 @@ impl Handler<FeedUpdateNotification> for UI {
                         }
                     });
             }
+            FeedUpdateNotification::NextUpdateScheduled(next_update) => {
+                self.library.next_update = next_update;
+            }
         }
         self.invalidate(ctx);
     }
 }
 
+impl Handler<DownloadNotification> for UI {
+    type Result = ();
+
+    fn handle(&mut self, msg: DownloadNotification, ctx: &mut Self::Context) -> Self::Result {
+        let (episode_id, download) = match msg {
+            DownloadNotification::Progress(episode_id, progress) => (episode_id, Some(progress)),
+            DownloadNotification::Removed(episode_id) => (episode_id, None),
+        };
+        self.library
+            .episodes
+            .update_data::<selection::DoNotUpdate, _>(|data, _| {
+                let episode = data
+                    .find(|item| item.id == episode_id)
+                    .and_then(|index| data.item_at_mut(index));
+                if let Some(episode) = episode {
+                    episode.download = download;
+                }
+            });
+        self.invalidate_later(ctx);
+    }
+}
+
 impl Handler<LogEntry> for UI {
     type Result = ();
 
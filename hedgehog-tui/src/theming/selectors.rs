@@ -108,6 +108,7 @@ pub(crate) enum ListColumn {
     EpisodesCount,
     NewCount,
     Details,
+    Download,
 }
 
 impl ListColumn {
@@ -125,6 +126,7 @@ impl ListColumn {
             ListColumn::EpisodesCount,
             ListColumn::NewCount,
             ListColumn::Details,
+            ListColumn::Download,
         ]
     }
 }
@@ -142,6 +144,9 @@ pub(crate) enum ListState {
     EpisodeStarted,
     Search,
     LogEntry,
+    Transcript,
+    Stats,
+    Bookmarks,
 }
 
 impl ListState {
@@ -160,6 +165,9 @@ impl ListState {
                 callback(Some(ListState::EpisodeFinished));
                 callback(Some(ListState::Search));
                 callback(Some(ListState::LogEntry));
+                callback(Some(ListState::Transcript));
+                callback(Some(ListState::Stats));
+                callback(Some(ListState::Bookmarks));
             }
             Some(ListState::Feed) => {
                 callback(Some(ListState::FeedUpdating));
@@ -231,6 +239,9 @@ impl List {
                                 ":episode-finished" => ListState::EpisodeFinished,
                                 ":search" => ListState::Search,
                                 ":log-entry" => ListState::LogEntry,
+                                ":transcript" => ListState::Transcript,
+                                ":stats" => ListState::Stats,
+                                ":bookmarks" => ListState::Bookmarks,
                                 _ => break,
                             };
                             if list_item.state.is_some() {
@@ -256,6 +267,7 @@ impl List {
                     [".episodes-count"] => Some(ListColumn::EpisodesCount),
                     [".new-count"] => Some(ListColumn::NewCount),
                     [".details"] => Some(ListColumn::Details),
+                    [".download"] => Some(ListColumn::Download),
                     _ => return Err(()),
                 };
 
@@ -271,6 +283,7 @@ impl List {
         ".author",
         ".date",
         ".details",
+        ".download",
         ".duration",
         ".episode-number",
         ".episodes-count",
@@ -280,6 +293,7 @@ impl List {
         ".new-count",
         ".state",
         ".title",
+        ":bookmarks",
         ":episode",
         ":episode-error",
         ":episode-finished",
@@ -296,6 +310,8 @@ impl List {
         ":playing",
         ":search",
         ":selected",
+        ":stats",
+        ":transcript",
     ];
 
     fn completion_candidates(input: &[&str]) -> &'static [&'static str] {
@@ -475,6 +491,7 @@ impl StyleSelector for Empty {
 pub(crate) enum PlayerItem {
     EpisodeTitle,
     FeedTitle,
+    ChapterTitle,
     Status,
     Progress,
     Timing,
@@ -485,6 +502,7 @@ impl PlayerItem {
         [
             PlayerItem::EpisodeTitle,
             PlayerItem::FeedTitle,
+            PlayerItem::ChapterTitle,
             PlayerItem::Status,
             PlayerItem::Progress,
             PlayerItem::Timing,
@@ -514,6 +532,7 @@ impl Player {
                 ".progress" => (None, Some(PlayerItem::Progress)),
                 ".episode" => (None, Some(PlayerItem::EpisodeTitle)),
                 ".feed" => (None, Some(PlayerItem::FeedTitle)),
+                ".chapter" => (None, Some(PlayerItem::ChapterTitle)),
                 _ => return Err(()),
             };
             if status.is_some() && selector.status.is_some()
@@ -530,6 +549,7 @@ impl Player {
 
     // These arrays must remain sorted
     const COMPLETION_FIRST: &'static [&'static str] = &[
+        ".chapter",
         ".episode",
         ".feed",
         ".progress",
@@ -0,0 +1,122 @@
+use super::list::ListItemRenderingDelegate;
+use super::utils::DurationFormatter;
+use crate::options::Options;
+use crate::theming;
+use chrono::Local;
+use hedgehog_library::model::Bookmark;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::text::{Span, Spans};
+use tui::widgets::{Paragraph, Widget};
+use unicode_width::UnicodeWidthStr;
+
+pub(crate) struct BookmarkRowRenderer<'t> {
+    theme: &'t theming::Theme,
+    options: &'t Options,
+    position_width: u16,
+}
+
+impl<'t> BookmarkRowRenderer<'t> {
+    pub(crate) fn new(theme: &'t theming::Theme, options: &'t Options) -> Self {
+        BookmarkRowRenderer {
+            theme,
+            options,
+            position_width: 0,
+        }
+    }
+
+    pub(crate) fn position_width(mut self, position_width: u16) -> Self {
+        self.position_width = position_width;
+        self
+    }
+}
+
+impl<'t, 'a> ListItemRenderingDelegate<'a> for BookmarkRowRenderer<'t> {
+    type Item = (&'a Bookmark, bool);
+
+    fn render_item(&self, area: Rect, item: Self::Item, buf: &mut Buffer) {
+        let (item, selected) = item;
+        let item_selector = theming::ListItem {
+            selected,
+            focused: true,
+            missing_title: item.episode_title.is_none(),
+            state: Some(theming::ListState::Bookmarks),
+            ..Default::default()
+        };
+        let style = self.theme.get(theming::List::Item(item_selector));
+        buf.set_style(area, style);
+
+        let position = format!(
+            "{:>width$}",
+            DurationFormatter(item.position).to_string(),
+            width = self.position_width as usize
+        );
+        buf.set_stringn(
+            area.x + 1,
+            area.y,
+            &position,
+            area.width.saturating_sub(2) as usize,
+            self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Duration),
+            )),
+        );
+
+        let mut right = area.right().saturating_sub(1);
+        let date = format!(
+            "  {}",
+            (item.created_at.with_timezone(&Local)).format(&self.options.date_format)
+        );
+        let x = right.saturating_sub(date.width() as u16).max(area.x + 1);
+        buf.set_stringn(
+            x,
+            area.y,
+            &date,
+            right.saturating_sub(x) as usize,
+            self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Date),
+            )),
+        );
+        right = x;
+
+        let text_offset = area.x + self.position_width + 3;
+        if right <= text_offset {
+            return;
+        }
+        let mut text = Vec::new();
+        if let Some(feed_title) = &item.feed_title {
+            let feed_title_style = self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Author),
+            ));
+            text.push(Span::styled(feed_title.as_str(), feed_title_style));
+            text.push(Span::styled(" · ", feed_title_style));
+        }
+        let title = item.episode_title.as_deref().unwrap_or("Untitled");
+        text.push(Span::styled(
+            title,
+            self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Title),
+            )),
+        ));
+        if let Some(note) = &item.note {
+            let note_style = self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Details),
+            ));
+            text.push(Span::styled("  ", note_style));
+            text.push(Span::styled(note.as_str(), note_style));
+        }
+        Paragraph::new(Spans::from(text)).render(
+            Rect::new(text_offset, area.y, right - text_offset, area.height),
+            buf,
+        );
+    }
+
+    fn render_empty(&self, area: Rect, buf: &mut Buffer) {
+        let item_selector = theming::ListItem {
+            focused: true,
+            state: Some(theming::ListState::Bookmarks),
+            ..Default::default()
+        };
+        let style = self.theme.get(theming::List::Item(item_selector));
+        buf.set_style(area, style);
+    }
+}
@@ -0,0 +1,50 @@
+use super::bookmark_row::BookmarkRowRenderer;
+use super::utils::DurationFormatter;
+use super::{empty::EmptyView, list::List};
+use crate::options::Options;
+use crate::scrolling::ScrollableList;
+use crate::theming;
+use hedgehog_library::model::Bookmark;
+use tui::widgets::Widget;
+
+pub(crate) struct BookmarksWidget<'a> {
+    bookmarks: &'a ScrollableList<Vec<Bookmark>>,
+    theme: &'a theming::Theme,
+    options: &'a Options,
+}
+
+impl<'a> BookmarksWidget<'a> {
+    pub(crate) fn new(
+        bookmarks: &'a ScrollableList<Vec<Bookmark>>,
+        theme: &'a theming::Theme,
+        options: &'a Options,
+    ) -> Self {
+        BookmarksWidget {
+            bookmarks,
+            theme,
+            options,
+        }
+    }
+}
+
+impl<'a> Widget for BookmarksWidget<'a> {
+    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let bookmarks = self.bookmarks.data();
+        if bookmarks.is_empty() {
+            EmptyView::new(self.theme)
+                .title("There are no bookmarks")
+                .subtitle("Bookmark the current position by typing :bookmark<Enter>")
+                .focused(true)
+                .render(area, buf);
+            return;
+        }
+
+        let position_width = (bookmarks.iter())
+            .map(|bookmark| DurationFormatter(bookmark.position).width())
+            .max()
+            .unwrap_or(0);
+        let renderer =
+            BookmarkRowRenderer::new(self.theme, self.options).position_width(position_width);
+        List::new(renderer, self.bookmarks.visible_iter()).render(area, buf);
+    }
+}
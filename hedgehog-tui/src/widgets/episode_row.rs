@@ -5,8 +5,10 @@ use crate::theming;
 use crate::widgets::layout::{split_left, split_right};
 use crate::widgets::utils::DurationFormatter;
 use hedgehog_library::model::{
-    EpisodeId, EpisodeSummary, EpisodeSummaryStatus, EpisodesListMetadata,
+    DownloadProgress, DownloadState, EpisodeId, EpisodeSummary, EpisodeSummaryStatus,
+    EpisodesListMetadata,
 };
+use std::borrow::Cow;
 use tui::buffer::Buffer;
 use tui::layout::{Alignment, Rect};
 use tui::text::Span;
@@ -149,6 +151,18 @@ impl<'t> EpisodesListRowRenderer<'t> {
             }
         }
     }
+
+    fn download_label(&self, download: &DownloadProgress) -> Cow<'t, str> {
+        match download.state {
+            DownloadState::Queued => Cow::Borrowed(&self.options.label_download_queued),
+            DownloadState::InProgress => match download.percentage() {
+                Some(percentage) => Cow::Owned(format!(" {}% ", percentage)),
+                None => Cow::Owned(format!(" {}M ", download.bytes >> 20)),
+            },
+            DownloadState::Completed => Cow::Borrowed(&self.options.label_download_completed),
+            DownloadState::Failed => Cow::Borrowed(&self.options.label_download_failed),
+        }
+    }
 }
 
 impl<'t, 'a> ListItemRenderingDelegate<'a> for EpisodesListRowRenderer<'t> {
@@ -253,6 +267,25 @@ impl<'t, 'a> ListItemRenderingDelegate<'a> for EpisodesListRowRenderer<'t> {
                 area = rest;
             }
 
+            if let Some(download) = &item.download {
+                let download_label = self.download_label(download);
+                if !download_label.is_empty() {
+                    let label_width = download_label.width();
+                    let (rest, download_area) = split_right(area, label_width as u16);
+                    let style = self.theme.get(theming::List::Item(
+                        item_selector.with_column(theming::ListColumn::Download),
+                    ));
+                    buf.set_stringn(
+                        download_area.x,
+                        download_area.y,
+                        &download_label,
+                        label_width,
+                        style,
+                    );
+                    area = rest;
+                }
+            }
+
             if let Some(feed_title) = &item.feed_title {
                 if !feed_title.is_empty() && area.width > 60 {
                     let (title_area, feed_area) = split_left(area, title_width);
@@ -2,7 +2,9 @@ use super::animation::{AnimationController, LoadingIndicator};
 use super::{layout::split_right, list::ListItemRenderingDelegate};
 use crate::options::Options;
 use crate::theming::{self, Theme};
-use hedgehog_library::model::{FeedId, FeedStatus, FeedSummary, FeedView, GroupSummary};
+use hedgehog_library::model::{
+    FeedId, FeedStatus, FeedSummary, FeedView, GroupSummary, SavedViewSummary,
+};
 use std::collections::HashSet;
 use tui::buffer::Buffer;
 use tui::layout::Rect;
@@ -58,13 +60,16 @@ impl<'t> FeedsListRowRenderer<'t> {
 }
 
 impl<'t, 'a> ListItemRenderingDelegate<'a> for FeedsListRowRenderer<'t> {
-    type Item = (&'a FeedView<FeedSummary, GroupSummary>, bool);
+    type Item = (
+        &'a FeedView<FeedSummary, GroupSummary, SavedViewSummary>,
+        bool,
+    );
 
     fn render_item(&self, mut area: Rect, item: Self::Item, buf: &mut tui::buffer::Buffer) {
         let (item, selected) = item;
 
         match item {
-            FeedView::All | FeedView::New | FeedView::Group(_) => {
+            FeedView::All | FeedView::New | FeedView::Saved(_) | FeedView::Group(_) => {
                 let item_selector = theming::ListItem {
                     selected,
                     focused: self.focused,
@@ -80,6 +85,7 @@ impl<'t, 'a> ListItemRenderingDelegate<'a> for FeedsListRowRenderer<'t> {
                 let paragraph = Paragraph::new(match item {
                     FeedView::All => "All episodes",
                     FeedView::New => "New",
+                    FeedView::Saved(view) => &view.name,
                     FeedView::Group(group) => &group.name,
                     FeedView::Feed(_) => unreachable!(),
                 });
@@ -92,9 +92,23 @@ impl<'a> Widget for LibraryWidget<'a> {
                 .item_at(selected_feed_index)
                 .map(|item| item.as_ref().map_feed(|feed| feed.status));
 
-            if self.data.episodes.data().size() == 0 {
+            if self.data.episodes.data().size() == 0 && self.data.find_query.is_some() {
+                EmptyView::new(self.theme)
+                    .title("No episodes found")
+                    .subtitle(
+                        "There are no episodes matching all of the words you are looking for.",
+                    )
+                    .focused(self.data.focus == FocusedPane::EpisodesList)
+                    .render(layout[1], buf);
+            } else if self.data.episodes.data().size() == 0 {
                 match state {
                     Some(FeedView::All | FeedView::Group(_)) => {}
+                    Some(FeedView::Saved(_)) => {
+                        EmptyView::new(self.theme)
+                            .title("No episodes match this view")
+                            .focused(self.data.focus == FocusedPane::EpisodesList)
+                            .render(layout[1], buf);
+                    }
                     Some(FeedView::New) => {
                         EmptyView::new(self.theme)
                             .title("There are no new episodes.")
@@ -130,7 +144,10 @@ impl<'a> Widget for LibraryWidget<'a> {
                 }
             } else {
                 let mut sizing = EpisodesListSizing::compute(self.options, metadata);
-                if matches!(state, Some(FeedView::All | FeedView::New))
+                if matches!(
+                    state,
+                    Some(FeedView::All | FeedView::New | FeedView::Saved(_))
+                ) || self.data.find_query.is_some()
                     || !self.options.show_episode_number
                 {
                     sizing.hide_episode_numbers();
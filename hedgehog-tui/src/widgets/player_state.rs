@@ -1,13 +1,16 @@
 use crate::options::Options;
 use crate::theming;
 use crate::widgets::layout::shrink_h;
-use crate::widgets::utils::PlaybackTimingFormatter;
-use hedgehog_library::model::EpisodePlaybackData;
+use crate::widgets::utils::{DurationFormatter, PlaybackTimingFormatter};
+use hedgehog_library::model::{Chapter, EpisodePlaybackData};
+use hedgehog_player::sleep::SleepTimer;
 use hedgehog_player::state::{PlaybackState, PlaybackStatus};
+use std::time::Duration;
 use tui::buffer::Buffer;
 use tui::layout::Rect;
 use tui::text::{Span, Spans};
 use tui::widgets::{Paragraph, Widget};
+use unicode_width::UnicodeWidthStr;
 
 use super::layout::split_right;
 use super::progressbar::ProgressBar;
@@ -17,6 +20,10 @@ pub(crate) struct PlayerState<'a> {
     theme: &'a theming::Theme,
     options: &'a Options,
     episode: Option<&'a EpisodePlaybackData>,
+    chapter: Option<&'a Chapter>,
+    rate: f64,
+    silence_skipped: Duration,
+    sleep_timer: SleepTimer,
 }
 
 impl<'a> PlayerState<'a> {
@@ -31,8 +38,32 @@ impl<'a> PlayerState<'a> {
             theme,
             options,
             episode,
+            chapter: None,
+            rate: 1.0,
+            silence_skipped: Duration::ZERO,
+            sleep_timer: SleepTimer::Off,
         }
     }
+
+    pub(crate) fn chapter(mut self, chapter: Option<&'a Chapter>) -> Self {
+        self.chapter = chapter;
+        self
+    }
+
+    pub(crate) fn rate(mut self, rate: f64) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub(crate) fn silence_skipped(mut self, silence_skipped: Duration) -> Self {
+        self.silence_skipped = silence_skipped;
+        self
+    }
+
+    pub(crate) fn sleep_timer(mut self, sleep_timer: SleepTimer) -> Self {
+        self.sleep_timer = sleep_timer;
+        self
+    }
 }
 
 impl<'a> Widget for PlayerState<'a> {
@@ -72,6 +103,31 @@ impl<'a> Widget for PlayerState<'a> {
             );
             area.width -= width;
 
+            let mut labels = Vec::new();
+            match self.sleep_timer {
+                SleepTimer::After(remaining) => {
+                    labels.push(format!(" sleep {} ", DurationFormatter(remaining)))
+                }
+                SleepTimer::EndOfEpisode => labels.push(" sleep at end ".to_string()),
+                SleepTimer::Off => {}
+            }
+            if !self.silence_skipped.is_zero() {
+                labels.push(format!(" -{} ", DurationFormatter(self.silence_skipped)));
+            }
+            if self.rate != 1.0 {
+                labels.push(format!(" {}× ", self.rate));
+            }
+            for label in labels {
+                let width = (label.width() as u16).min(area.width);
+                buf.set_span(
+                    (area.x + area.width).saturating_sub(width),
+                    area.y,
+                    &Span::styled(label, style),
+                    width,
+                );
+                area.width -= width;
+            }
+
             if let Some(duration) = timing.duration {
                 if area.width > self.options.progress_bar_width * 2
                     && !self.options.progress_bar_chars.is_empty()
@@ -105,13 +161,27 @@ impl<'a> Widget for PlayerState<'a> {
         if let Some(title) = episode_title {
             text.push(Span::raw(title));
         }
+        if let Some(title) = self.chapter.and_then(|chapter| chapter.title.as_deref()) {
+            let style = self.theme.get(theming::Player {
+                status: Some(status),
+                subitem: Some(theming::PlayerItem::ChapterTitle),
+            });
+
+            if episode_title.is_some() {
+                text.push(Span::styled(" [", style));
+                text.push(Span::styled(title, style));
+                text.push(Span::styled("]", style));
+            } else {
+                text.push(Span::styled(title, style));
+            }
+        }
         if let Some(title) = self.episode.and_then(|ep| ep.feed_title.as_deref()) {
             let style = self.theme.get(theming::Player {
                 status: Some(status),
                 subitem: Some(theming::PlayerItem::FeedTitle),
             });
 
-            if episode_title.is_some() {
+            if !text.is_empty() {
                 text.push(Span::styled(" / ", style));
             }
             text.push(Span::styled(title, style));
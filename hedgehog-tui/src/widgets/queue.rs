@@ -0,0 +1,61 @@
+use super::episode_row::{EpisodesListRowRenderer, EpisodesListSizing};
+use super::{empty::EmptyView, list::List};
+use crate::options::Options;
+use crate::scrolling::ScrollableList;
+use crate::theming;
+use hedgehog_library::model::{EpisodeId, EpisodeSummary, EpisodesListMetadata};
+use tui::widgets::Widget;
+
+pub(crate) struct QueueWidget<'a> {
+    queue: &'a ScrollableList<Vec<EpisodeSummary>>,
+    theme: &'a theming::Theme,
+    options: &'a Options,
+    playing_id: Option<EpisodeId>,
+}
+
+impl<'a> QueueWidget<'a> {
+    pub(crate) fn new(
+        queue: &'a ScrollableList<Vec<EpisodeSummary>>,
+        theme: &'a theming::Theme,
+        options: &'a Options,
+        playing_id: Option<EpisodeId>,
+    ) -> Self {
+        QueueWidget {
+            queue,
+            theme,
+            options,
+            playing_id,
+        }
+    }
+}
+
+impl<'a> Widget for QueueWidget<'a> {
+    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        let episodes = self.queue.data();
+        if episodes.is_empty() {
+            EmptyView::new(self.theme)
+                .title("The queue is empty")
+                .subtitle("Add the selected episode to the queue by typing :queue-add<Enter>")
+                .focused(true)
+                .render(area, buf);
+            return;
+        }
+
+        let metadata = EpisodesListMetadata {
+            items_count: episodes.len(),
+            max_season_number: None,
+            max_episode_number: None,
+            max_duration: episodes.iter().filter_map(|episode| episode.duration).max(),
+            has_publication_date: episodes
+                .iter()
+                .any(|episode| episode.publication_date.is_some()),
+            reversed_order: false,
+        };
+        let sizing = EpisodesListSizing::compute(self.options, &metadata).with_width(area.width);
+        let renderer = EpisodesListRowRenderer::new(self.theme, true, self.options, sizing)
+            .with_playing_id(self.playing_id);
+        let items =
+            (self.queue.visible_iter()).map(|(episode, selected)| (Some(episode), selected));
+        List::new(renderer, items).render(area, buf);
+    }
+}
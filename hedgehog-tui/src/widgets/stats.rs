@@ -0,0 +1,52 @@
+use super::stats_row::{StatsRow, StatsRowRenderer};
+use super::utils::DurationFormatter;
+use super::{empty::EmptyView, list::List};
+use crate::options::Options;
+use crate::scrolling::ScrollableList;
+use crate::theming;
+use tui::widgets::Widget;
+
+pub(crate) struct StatsWidget<'a> {
+    stats: Option<&'a ScrollableList<Vec<StatsRow>>>,
+    theme: &'a theming::Theme,
+    options: &'a Options,
+}
+
+impl<'a> StatsWidget<'a> {
+    pub(crate) fn new(
+        stats: Option<&'a ScrollableList<Vec<StatsRow>>>,
+        theme: &'a theming::Theme,
+        options: &'a Options,
+    ) -> Self {
+        StatsWidget {
+            stats,
+            theme,
+            options,
+        }
+    }
+}
+
+impl<'a> Widget for StatsWidget<'a> {
+    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        match self.stats {
+            None => EmptyView::new(self.theme)
+                .title("Loading the statistics...")
+                .focused(true)
+                .render(area, buf),
+            Some(list) if list.data().is_empty() => EmptyView::new(self.theme)
+                .title("Nothing was listened to yet")
+                .subtitle("The time spent listening to podcasts is displayed here")
+                .focused(true)
+                .render(area, buf),
+            Some(list) => {
+                // The total is the first row and has the longest durations.
+                let duration_width = (list.data().first())
+                    .and_then(StatsRow::time)
+                    .map_or(0, |time| DurationFormatter(time.listened).width());
+                let renderer =
+                    StatsRowRenderer::new(self.theme, self.options).duration_width(duration_width);
+                List::new(renderer, list.visible_iter()).render(area, buf);
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::list::ListItemRenderingDelegate;
+use super::utils::DurationFormatter;
+use crate::options::Options;
+use crate::theming;
+use chrono::NaiveDate;
+use hedgehog_library::model::{ListeningStats, ListeningTime};
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use unicode_width::UnicodeWidthStr;
+
+pub(crate) enum StatsRow {
+    Heading(&'static str),
+    Total(ListeningTime),
+    Feed(String, ListeningTime),
+    Day(NaiveDate, ListeningTime),
+}
+
+impl StatsRow {
+    pub(crate) fn from_stats(stats: ListeningStats) -> Vec<StatsRow> {
+        if stats.days.is_empty() {
+            return Vec::new();
+        }
+        let mut rows = vec![StatsRow::Total(stats.total), StatsRow::Heading("Podcasts")];
+        rows.extend((stats.feeds.into_iter()).map(|feed| StatsRow::Feed(feed.title, feed.time)));
+        rows.push(StatsRow::Heading("Days"));
+        rows.extend((stats.days.into_iter()).map(|day| StatsRow::Day(day.date, day.time)));
+        rows
+    }
+
+    pub(crate) fn time(&self) -> Option<&ListeningTime> {
+        match self {
+            StatsRow::Heading(_) => None,
+            StatsRow::Total(time) | StatsRow::Feed(_, time) | StatsRow::Day(_, time) => Some(time),
+        }
+    }
+}
+
+pub(crate) struct StatsRowRenderer<'t> {
+    theme: &'t theming::Theme,
+    options: &'t Options,
+    duration_width: u16,
+}
+
+impl<'t> StatsRowRenderer<'t> {
+    pub(crate) fn new(theme: &'t theming::Theme, options: &'t Options) -> Self {
+        StatsRowRenderer {
+            theme,
+            options,
+            duration_width: 0,
+        }
+    }
+
+    pub(crate) fn duration_width(mut self, duration_width: u16) -> Self {
+        self.duration_width = duration_width;
+        self
+    }
+}
+
+impl<'t, 'a> ListItemRenderingDelegate<'a> for StatsRowRenderer<'t> {
+    type Item = (&'a StatsRow, bool);
+
+    fn render_item(&self, area: Rect, item: Self::Item, buf: &mut Buffer) {
+        let (item, selected) = item;
+        let item_selector = theming::ListItem {
+            selected,
+            focused: true,
+            state: Some(theming::ListState::Stats),
+            ..Default::default()
+        };
+        let style = self.theme.get(theming::List::Item(item_selector));
+        buf.set_style(area, style);
+
+        let mut right = area.right().saturating_sub(1);
+        if let Some(time) = item.time() {
+            let width = self.duration_width as usize;
+            let saved = format!(
+                "  saved {:>width$}",
+                DurationFormatter(time.time_saved()).to_string(),
+                width = width
+            );
+            let listened = format!(
+                "  {:>width$}",
+                DurationFormatter(time.listened).to_string(),
+                width = width
+            );
+            let columns = [
+                (saved, theming::ListColumn::Details),
+                (listened, theming::ListColumn::Duration),
+            ];
+            for (text, column) in columns {
+                let x = right.saturating_sub(text.width() as u16).max(area.x + 1);
+                buf.set_stringn(
+                    x,
+                    area.y,
+                    &text,
+                    right.saturating_sub(x) as usize,
+                    self.theme
+                        .get(theming::List::Item(item_selector.with_column(column))),
+                );
+                right = x;
+            }
+        }
+
+        let (indent, label) = match item {
+            StatsRow::Heading(title) => (1, title.to_string()),
+            StatsRow::Total(_) => (1, "Total".to_string()),
+            StatsRow::Feed(title, _) => (3, title.clone()),
+            StatsRow::Day(date, _) => (
+                3,
+                (date.and_hms(0, 0, 0).format(&self.options.date_format)).to_string(),
+            ),
+        };
+        let x = area.x + indent;
+        buf.set_stringn(
+            x,
+            area.y,
+            label,
+            right.saturating_sub(x) as usize,
+            self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Title),
+            )),
+        );
+    }
+
+    fn render_empty(&self, area: Rect, buf: &mut Buffer) {
+        let item_selector = theming::ListItem {
+            focused: true,
+            state: Some(theming::ListState::Stats),
+            ..Default::default()
+        };
+        let style = self.theme.get(theming::List::Item(item_selector));
+        buf.set_style(area, style);
+    }
+}
@@ -1,16 +1,37 @@
 use crate::logger::LogEntry;
 use crate::theming::{self, Theme};
+use chrono::{DateTime, Local, Utc};
+use tui::layout::Alignment;
 use tui::text::{Span, Spans};
 use tui::widgets::{Paragraph, Widget};
 
 pub(crate) struct LogEntryView<'a> {
     theme: &'a Theme,
     log_entry: Option<&'a LogEntry>,
+    next_update: Option<DateTime<Utc>>,
 }
 
 impl<'a> LogEntryView<'a> {
     pub(crate) fn new(log_entry: Option<&'a LogEntry>, theme: &'a Theme) -> Self {
-        LogEntryView { theme, log_entry }
+        LogEntryView {
+            theme,
+            log_entry,
+            next_update: None,
+        }
+    }
+
+    pub(crate) fn next_update(mut self, next_update: Option<DateTime<Utc>>) -> Self {
+        self.next_update = next_update;
+        self
+    }
+}
+
+fn format_next_update(next_update: DateTime<Utc>) -> String {
+    let next_update = next_update.with_timezone(&Local);
+    if next_update.date() == Local::today() {
+        format!("next update at {} ", next_update.format("%H:%M"))
+    } else {
+        format!("next update at {} ", next_update.format("%x %H:%M"))
     }
 }
 
@@ -35,7 +56,14 @@ impl<'a> Widget for LogEntryView<'a> {
                 paragraph.render(area, buf);
             }
             None => {
-                buf.set_style(area, self.theme.get(theming::StatusBar::Empty));
+                let style = self.theme.get(theming::StatusBar::Empty);
+                buf.set_style(area, style);
+                if let Some(next_update) = self.next_update {
+                    let paragraph = Paragraph::new(format_next_update(next_update))
+                        .alignment(Alignment::Right)
+                        .style(style);
+                    paragraph.render(area, buf);
+                }
             }
         }
     }
@@ -0,0 +1,58 @@
+use super::utils::DurationFormatter;
+use super::{empty::EmptyView, list::List, transcript_row::TranscriptRowRenderer};
+use crate::{screen::TranscriptState, theming};
+use std::time::Duration;
+use tui::widgets::Widget;
+
+pub(crate) struct TranscriptWidget<'a> {
+    transcript: &'a TranscriptState,
+    theme: &'a theming::Theme,
+    playing_cue: Option<usize>,
+}
+
+impl<'a> TranscriptWidget<'a> {
+    pub(crate) fn new(
+        transcript: &'a TranscriptState,
+        theme: &'a theming::Theme,
+        playing_cue: Option<usize>,
+    ) -> Self {
+        TranscriptWidget {
+            transcript,
+            theme,
+            playing_cue,
+        }
+    }
+}
+
+impl<'a> Widget for TranscriptWidget<'a> {
+    fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+        match self.transcript {
+            TranscriptState::None => EmptyView::new(self.theme)
+                .title("Nothing is playing")
+                .subtitle("The transcript of the playing episode is displayed here")
+                .focused(true)
+                .render(area, buf),
+            TranscriptState::Loading(_) => EmptyView::new(self.theme)
+                .title("Loading the transcript...")
+                .focused(true)
+                .render(area, buf),
+            TranscriptState::Unavailable(_) => EmptyView::new(self.theme)
+                .title("No transcript")
+                .subtitle("This episode does not provide a transcript")
+                .focused(true)
+                .render(area, buf),
+            TranscriptState::Loaded(_, list) => {
+                let last_start = list
+                    .data()
+                    .last()
+                    .map(|cue| cue.start)
+                    .unwrap_or(Duration::ZERO);
+                let playing_cue = self.playing_cue.and_then(|index| list.data().get(index));
+                let renderer = TranscriptRowRenderer::new(self.theme)
+                    .timestamp_width(DurationFormatter(last_start).width())
+                    .playing_cue(playing_cue);
+                List::new(renderer, list.visible_iter()).render(area, buf);
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use super::list::ListItemRenderingDelegate;
+use super::utils::DurationFormatter;
+use crate::theming;
+use hedgehog_library::model::TranscriptCue;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::text::{Span, Spans};
+use tui::widgets::{Paragraph, Widget};
+
+pub(crate) struct TranscriptRowRenderer<'t> {
+    theme: &'t theming::Theme,
+    timestamp_width: u16,
+    playing_cue: Option<&'t TranscriptCue>,
+}
+
+impl<'t> TranscriptRowRenderer<'t> {
+    pub(crate) fn new(theme: &'t theming::Theme) -> Self {
+        TranscriptRowRenderer {
+            theme,
+            timestamp_width: 0,
+            playing_cue: None,
+        }
+    }
+
+    pub(crate) fn timestamp_width(mut self, timestamp_width: u16) -> Self {
+        self.timestamp_width = timestamp_width;
+        self
+    }
+
+    pub(crate) fn playing_cue(mut self, playing_cue: Option<&'t TranscriptCue>) -> Self {
+        self.playing_cue = playing_cue;
+        self
+    }
+}
+
+impl<'t, 'a> ListItemRenderingDelegate<'a> for TranscriptRowRenderer<'t> {
+    type Item = (&'a TranscriptCue, bool);
+
+    fn render_item(&self, area: Rect, item: Self::Item, buf: &mut Buffer) {
+        let (item, selected) = item;
+        let item_selector = theming::ListItem {
+            selected,
+            focused: true,
+            playing: self
+                .playing_cue
+                .map_or(false, |cue| std::ptr::eq(cue, item)),
+            state: Some(theming::ListState::Transcript),
+            ..Default::default()
+        };
+        let style = self.theme.get(theming::List::Item(item_selector));
+        buf.set_style(area, style);
+
+        let timestamp = format!(
+            "{:>width$}",
+            DurationFormatter(item.start).to_string(),
+            width = self.timestamp_width as usize
+        );
+        buf.set_stringn(
+            area.x + 1,
+            area.y,
+            &timestamp,
+            area.width.saturating_sub(2) as usize,
+            self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Duration),
+            )),
+        );
+
+        let text_offset = self.timestamp_width + 3;
+        if area.width <= text_offset + 1 {
+            return;
+        }
+        let mut text = Vec::new();
+        if let Some(speaker) = &item.speaker {
+            let speaker_style = self.theme.get(theming::List::Item(
+                item_selector.with_column(theming::ListColumn::Author),
+            ));
+            text.push(Span::styled(speaker.as_str(), speaker_style));
+            text.push(Span::styled(": ", speaker_style));
+        }
+        let text_style = self.theme.get(theming::List::Item(
+            item_selector.with_column(theming::ListColumn::Title),
+        ));
+        text.push(Span::styled(item.text.as_str(), text_style));
+        Paragraph::new(Spans::from(text)).render(
+            Rect::new(
+                area.x + text_offset,
+                area.y,
+                area.width - text_offset - 1,
+                area.height,
+            ),
+            buf,
+        );
+    }
+
+    fn render_empty(&self, area: Rect, buf: &mut Buffer) {
+        let item_selector = theming::ListItem {
+            focused: true,
+            state: Some(theming::ListState::Transcript),
+            ..Default::default()
+        };
+        let style = self.theme.get(theming::List::Item(item_selector));
+        buf.set_style(area, style);
+    }
+}